@@ -0,0 +1,210 @@
+use crate::cmd::output::{confirm, or_exit, Output};
+use crate::cmd::table;
+use clap::{Args, Subcommand};
+use common::kafka;
+use common::kafka::acl::AclFilter;
+use common::kafka::client::Config;
+use common::kafka::types::{AclBindingEntry, AclOperation, AclPatternType, AclPermission, AclResourceType, AclResultEntry};
+use serde::Serialize;
+use std::borrow::Cow;
+use tabled::Tabled;
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(flatten_help = true)]
+pub struct AclArgs {
+    #[command(subcommand)]
+    pub(crate) command: Option<AclCommands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AclCommands {
+    /// List the ACL bindings matching the filter
+    List(AclFilterArgs),
+    /// Create an ACL binding
+    Create(AclBindingArgs),
+    /// Delete every ACL binding matching the filter
+    Delete(DeleteAclArgs),
+}
+
+#[derive(Debug, Args, Default)]
+pub struct AclFilterArgs {
+    /// Resource type: any, topic, group or cluster
+    #[arg(long)]
+    pub(crate) resource_type: Option<AclResourceType>,
+    /// Resource name
+    #[arg(long)]
+    pub(crate) resource_name: Option<String>,
+    /// Pattern type: any, match, literal or prefixed
+    #[arg(long)]
+    pub(crate) pattern_type: Option<AclPatternType>,
+    /// Principal, as in `User:alice`
+    #[arg(long)]
+    pub(crate) principal: Option<String>,
+    #[arg(long)]
+    pub(crate) host: Option<String>,
+    /// Operation, as in read, write or describe-configs
+    #[arg(long)]
+    pub(crate) operation: Option<AclOperation>,
+    /// Permission: any, allow or deny
+    #[arg(long)]
+    pub(crate) permission: Option<AclPermission>,
+}
+
+#[derive(Debug, Args)]
+pub struct AclBindingArgs {
+    /// Resource type: topic, group or cluster
+    #[arg(long)]
+    pub(crate) resource_type: AclResourceType,
+    /// Resource name, `*` for every resource and `kafka-cluster` for the cluster
+    #[arg(long)]
+    pub(crate) resource_name: String,
+    /// Pattern type: literal or prefixed
+    #[arg(long, default_value = "literal")]
+    pub(crate) pattern_type: AclPatternType,
+    /// Principal, as in `User:alice`
+    #[arg(long)]
+    pub(crate) principal: String,
+    #[arg(long, default_value = "*")]
+    pub(crate) host: String,
+    /// Operation, as in read, write or describe-configs
+    #[arg(long)]
+    pub(crate) operation: AclOperation,
+    /// Permission: allow or deny
+    #[arg(long, default_value = "allow")]
+    pub(crate) permission: AclPermission,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteAclArgs {
+    #[command(flatten)]
+    pub(crate) filter: AclFilterArgs,
+    /// Skip the confirmation
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+impl From<AclFilterArgs> for AclFilter {
+    fn from(args: AclFilterArgs) -> Self {
+        Self {
+            resource_type: args.resource_type.unwrap_or(AclResourceType::Any),
+            resource_name: args.resource_name,
+            pattern_type: args.pattern_type.unwrap_or(AclPatternType::Any),
+            principal: args.principal,
+            host: args.host,
+            operation: args.operation.unwrap_or(AclOperation::Any),
+            permission: args.permission.unwrap_or(AclPermission::Any),
+        }
+    }
+}
+
+pub(crate) fn list(config: &Config, args: AclFilterArgs, output: &Output) {
+    let acls = or_exit(kafka::acl::describe_acls(config, &args.into()), "Failed to describe acls")
+        .into_iter()
+        .map(AclBindingTable)
+        .collect();
+    output.print_aligned(acls, &[])
+}
+
+pub(crate) fn create(config: &Config, args: AclBindingArgs, output: &Output) {
+    let binding = AclBindingEntry {
+        resource_type: args.resource_type,
+        resource_name: args.resource_name,
+        pattern_type: args.pattern_type,
+        principal: args.principal,
+        host: args.host,
+        operation: args.operation,
+        permission: args.permission,
+    };
+    let results = or_exit(kafka::acl::create_acls(config, &[binding]), "Failed to create acls");
+    print_results(results, output);
+}
+
+/// Shows the bindings the filter matches and deletes them once confirmed.
+pub(crate) fn delete(config: &Config, args: DeleteAclArgs, output: &Output) {
+    let filter = args.filter.into();
+    or_exit(config.check_writable("delete acls"), "Failed to delete acls");
+    if !args.yes {
+        let matching = or_exit(kafka::acl::describe_acls(config, &filter), "Failed to describe acls");
+        if matching.is_empty() {
+            eprintln!("No acl matches the filter");
+            return;
+        }
+        let rows = matching.iter().cloned().map(AclBindingTable).collect::<Vec<_>>();
+        eprintln!("{}", table::create(rows, &[], false));
+        if !confirm(&format!("{} acls will be deleted.", matching.len())) {
+            eprintln!("Aborted");
+            std::process::exit(1);
+        }
+    }
+    let results = or_exit(kafka::acl::delete_acls(config, &filter), "Failed to delete acls");
+    print_results(results, output);
+}
+
+fn print_results(results: Vec<AclResultEntry>, output: &Output) {
+    let failed = results.iter().any(|r| r.error.is_some());
+    output.print_aligned(results.into_iter().map(AclResultTable).collect(), &[]);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn binding_fields(binding: &AclBindingEntry) -> Vec<Cow<'_, str>> {
+    vec![
+        binding.resource_type.name().into(),
+        binding.resource_name.as_str().into(),
+        binding.pattern_type.name().into(),
+        binding.principal.as_str().into(),
+        binding.host.as_str().into(),
+        binding.operation.name().into(),
+        binding.permission.name().into(),
+    ]
+}
+
+fn binding_headers() -> Vec<Cow<'static, str>> {
+    vec![
+        "Resource Type".into(),
+        "Resource Name".into(),
+        "Pattern".into(),
+        "Principal".into(),
+        "Host".into(),
+        "Operation".into(),
+        "Permission".into(),
+    ]
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct AclBindingTable(AclBindingEntry);
+
+impl Tabled for AclBindingTable {
+    const LENGTH: usize = 7;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        binding_fields(&self.0)
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        binding_headers()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct AclResultTable(AclResultEntry);
+
+impl Tabled for AclResultTable {
+    const LENGTH: usize = 8;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let mut fields = binding_fields(&self.0.binding);
+        fields.push(self.0.error.as_deref().unwrap_or_default().into());
+        fields
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        let mut headers = binding_headers();
+        headers.push("Error".into());
+        headers
+    }
+}
@@ -0,0 +1,68 @@
+use crate::cmd::output::Output;
+use clap::{Args, Subcommand};
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::types::{CheckStatus, CheckStepEntry};
+use serde::Serialize;
+use std::borrow::Cow;
+use tabled::Tabled;
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(flatten_help = true)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    pub(crate) command: Option<AuthCommands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommands {
+    /// Check DNS, TCP, TLS, credentials, token and metadata step by step
+    Check,
+}
+
+/// Prints the step by step report, exiting with a non-zero code when any step failed.
+pub(crate) async fn check(config: &Config, output: &Output) {
+    let steps = kafka::diagnostics::check(config).await;
+    let failed = steps.iter().any(|s| s.status == CheckStatus::Fail);
+
+    let rows: Vec<CheckStepTable> = steps.into_iter()
+        .map(CheckStepTable)
+        .collect();
+    output.print_aligned(rows, &[]);
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct CheckStepTable(CheckStepEntry);
+
+impl Tabled for CheckStepTable {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let status = match self.0.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Fail => "FAIL",
+            CheckStatus::Skip => "SKIP",
+        };
+        vec![
+            self.0.step.as_str().into(),
+            self.0.target.as_str().into(),
+            status.into(),
+            self.0.detail.as_str().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Step".into(),
+            "Target".into(),
+            "Status".into(),
+            "Detail".into(),
+        ]
+    }
+}
@@ -1,16 +1,93 @@
-use crate::cmd::table;
+use crate::cmd::output::{confirm, or_exit, Output};
 use clap::{Args, Subcommand};
 use common::kafka;
 use common::kafka::client::Config;
-use common::kafka::types::ListBrokerEntry;
+use common::kafka::election::ElectionType;
+use common::kafka::types::{format_bytes, DescribeBrokerEntry, DescribeConfigEntry, ElectLeaderEntry, ElectionStatus, HealthIssueEntry, ListBrokerEntry};
+use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use tabled::Tabled;
 
-pub fn list_brokers_cmd(config: &Config) {
-    let brokers = kafka::broker::list_brokers(config)
+pub fn list_brokers_cmd(config: &Config, output: &Output) {
+    let brokers = or_exit(kafka::broker::list_brokers(config), "Failed to list brokers")
         .iter().map(|e| ListBrokerTable(e.clone()))
         .collect();
-    println!("{}", table::create(brokers))
+    output.print_aligned(brokers, &[0, 2, 5, 6, 7])
+}
+
+pub async fn broker_configs_cmd(config: &Config, broker_id: i32, output: &Output) {
+    let configs = or_exit(
+        kafka::broker::describe_broker_configs(config, broker_id).await,
+        "Failed to describe broker configs",
+    );
+    let rows = configs.into_iter()
+        .map(DescribeConfigTable)
+        .collect();
+    output.print_aligned(rows, &[])
+}
+
+pub fn describe_cluster_cmd(config: &Config, output: &Output) {
+    let cluster = or_exit(kafka::broker::describe_cluster(config), "Failed to describe cluster");
+    if output.is_structured() {
+        output.print_value(&cluster);
+        return;
+    }
+    if output.is_table() {
+        println!("Cluster ID: {}", cluster.cluster_id.as_deref().unwrap_or("-"));
+        println!("Controller: {}", cluster.controller_id.map(|id| id.to_string()).unwrap_or("-".to_string()));
+    }
+    let brokers = cluster.brokers.into_iter()
+        .map(DescribeBrokerTable)
+        .collect();
+    output.print_aligned(brokers, &[0, 2])
+}
+
+/// Prints the health report, exiting with a non-zero code when any issue was found.
+pub async fn health_cmd(config: &Config, output: &Output) {
+    let report = or_exit(kafka::health::check_health(config).await, "Failed to check cluster health");
+    let healthy = report.issues.is_empty();
+    if output.is_structured() {
+        output.print_value(&report);
+    } else {
+        if output.is_table() {
+            println!(
+                "{} brokers, {} topics, {} partitions, {} issues",
+                report.brokers, report.topics, report.partitions, report.issues.len(),
+            );
+        }
+        let issues = report.issues.into_iter()
+            .map(HealthIssueTable)
+            .collect();
+        output.print_aligned(issues, &[2, 3]);
+    }
+    if !healthy {
+        std::process::exit(1);
+    }
+}
+
+/// Triggers the election and prints the result of each partition, exiting with a non-zero code when any failed.
+pub fn elect_leaders_cmd(config: &Config, args: ElectLeadersArgs, output: &Output) {
+    let election_type = if args.unclean { ElectionType::Unclean } else { ElectionType::Preferred };
+    let warning = "Unclean election can elect an out of sync replica and lose data.";
+    or_exit(config.check_writable("elect leaders"), "Failed to elect leaders");
+    if election_type == ElectionType::Unclean && !args.yes && !confirm(warning) {
+        eprintln!("Aborted");
+        std::process::exit(1);
+    }
+
+    let topics = args.topic
+        .map(|topic| BTreeMap::from([(topic, args.partitions)]))
+        .unwrap_or_default();
+    let results = or_exit(
+        kafka::election::elect_leaders(config, election_type, &topics),
+        "Failed to elect leaders",
+    );
+    let failed = results.iter().any(|r| r.status == ElectionStatus::Failed);
+    output.print_aligned(results.into_iter().map(ElectLeaderTable).collect(), &[1]);
+    if failed {
+        std::process::exit(1);
+    }
 }
 
 #[derive(Debug, Args)]
@@ -23,19 +100,86 @@ pub struct ClusterArgs {
 
 #[derive(Debug, Subcommand)]
 pub enum ClusterCommands {
-    Brokers,
+    Brokers(ListBrokersArgs),
+    /// Show the cluster id, controller and each broker's rack
+    Describe,
+    /// Report replication and leadership problems, exits non-zero when any is found
+    Health,
+    /// Move leadership back to the preferred replicas, of every partition unless a topic is given
+    ElectLeaders(ElectLeadersArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ElectLeadersArgs {
+    /// Only elect leaders of this topic
+    #[arg(short, long)]
+    pub(crate) topic: Option<String>,
+    /// Only elect leaders of these partitions of the topic
+    #[arg(short, long, requires = "topic", value_delimiter = ',')]
+    pub(crate) partitions: Vec<i32>,
+    /// Elect out of sync replicas when no in sync replica is available, this can lose data
+    #[arg(long)]
+    pub(crate) unclean: bool,
+    /// Skip the confirmation of an unclean election
+    #[arg(short, long)]
+    pub(crate) yes: bool,
 }
 
+#[derive(Debug, Args, Default)]
+pub struct ListBrokersArgs {
+    /// Show the configuration of the given broker
+    #[arg(short, long, value_name = "BROKER_ID")]
+    pub(crate) config: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
 struct ListBrokerTable(ListBrokerEntry);
 
 impl Tabled for ListBrokerTable {
-    const LENGTH: usize = 3;
+    const LENGTH: usize = 8;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.0.id.to_string().into(),
+            self.0.host.as_str().into(),
+            self.0.port.to_string().into(),
+            self.0.rack.as_deref().unwrap_or("-").into(),
+            if self.0.controller { "yes" } else { "" }.into(),
+            self.0.leaders.to_string().into(),
+            self.0.replicas.to_string().into(),
+            self.0.log_dir_size.map(format_bytes).unwrap_or("-".to_string()).into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "ID".into(),
+            "Host".into(),
+            "Port".into(),
+            "Rack".into(),
+            "Controller".into(),
+            "Leaders".into(),
+            "Replicas".into(),
+            "Log Dir Size".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct DescribeBrokerTable(DescribeBrokerEntry);
+
+impl Tabled for DescribeBrokerTable {
+    const LENGTH: usize = 5;
 
     fn fields(&self) -> Vec<Cow<'_, str>> {
         vec![
             self.0.id.to_string().into(),
             self.0.host.as_str().into(),
             self.0.port.to_string().into(),
+            self.0.rack.as_deref().unwrap_or("-").into(),
+            if self.0.controller { "yes" } else { "" }.into(),
         ]
     }
 
@@ -44,6 +188,93 @@ impl Tabled for ListBrokerTable {
             "ID".into(),
             "Host".into(),
             "Port".into(),
+            "Rack".into(),
+            "Controller".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct DescribeConfigTable(DescribeConfigEntry);
+
+impl Tabled for DescribeConfigTable {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let value = match (&self.0.value, self.0.is_sensitive) {
+            (_, true) => "(sensitive)",
+            (Some(value), false) => value.as_str(),
+            (None, false) => "",
+        };
+        vec![
+            self.0.name.as_str().into(),
+            value.into(),
+            self.0.source.as_str().into(),
+            if self.0.is_read_only { "yes" } else { "" }.into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Name".into(),
+            "Value".into(),
+            "Source".into(),
+            "Read Only".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct HealthIssueTable(HealthIssueEntry);
+
+impl Tabled for HealthIssueTable {
+    const LENGTH: usize = 5;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.0.kind.name().into(),
+            self.0.topic.as_deref().unwrap_or("").into(),
+            self.0.partition.map(|p| p.to_string()).unwrap_or_default().into(),
+            self.0.broker.map(|b| b.to_string()).unwrap_or_default().into(),
+            self.0.detail.as_str().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Issue".into(),
+            "Topic".into(),
+            "Partition".into(),
+            "Broker".into(),
+            "Detail".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct ElectLeaderTable(ElectLeaderEntry);
+
+impl Tabled for ElectLeaderTable {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.0.topic.as_str().into(),
+            self.0.partition.to_string().into(),
+            self.0.status.name().into(),
+            self.0.error.as_deref().unwrap_or_default().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Topic".into(),
+            "Partition".into(),
+            "Status".into(),
+            "Error".into(),
         ]
     }
 }
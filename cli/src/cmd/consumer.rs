@@ -1,8 +1,9 @@
-use crate::cmd::table;
+use crate::cmd::output::{or_exit, Output};
 use clap::{Args, Subcommand};
 use common::kafka;
 use common::kafka::client::Config;
-use common::kafka::types::ListConsumerGroupEntry;
+use common::kafka::types::{DeleteConsumerGroupEntry, ListConsumerGroupEntry};
+use serde::Serialize;
 use std::borrow::Cow;
 use tabled::Tabled;
 
@@ -32,29 +33,40 @@ pub struct DeleteConsumerArgs {
     pub(crate) consumer_group: Option<String>,
 }
 
-pub(crate) fn list(config: &Config, consumer_group: Option<String>) {
-    let groups:Vec<ListConsumerGroupEntryTable> = kafka::group::list(config, consumer_group)
+pub(crate) fn list(config: &Config, consumer_group: Option<String>, output: &Output) {
+    let groups:Vec<ListConsumerGroupEntryTable> = or_exit(kafka::group::list(config, consumer_group), "Failed to list consumer groups")
         .iter()
         .map(|group| ListConsumerGroupEntryTable(group.to_owned()))
         .collect();
 
-    println!("{}", table::create(groups));
+    output.print_aligned(groups, &[2]);
 }
 
-pub(crate) async fn delete(config: &Config, consumer_group: Option<String>) {
-    let result = kafka::group::delete(config, consumer_group).await;
-    result.iter().for_each(|res| println!("{:?}", res))
+pub(crate) async fn delete(config: &Config, consumer_group: Option<String>, output: &Output) {
+    let result: Vec<DeleteConsumerGroupEntryTable> = or_exit(kafka::group::delete(config, consumer_group).await, "Failed to delete consumer groups")
+        .into_iter()
+        .map(|res| match res {
+            Ok(name) => DeleteConsumerGroupEntry { name, error: None },
+            Err((name, error)) => DeleteConsumerGroupEntry { name, error: Some(error.to_string()) },
+        })
+        .map(DeleteConsumerGroupEntryTable)
+        .collect();
+
+    output.print_aligned(result, &[]);
 }
 
+#[derive(Serialize)]
+#[serde(transparent)]
 struct ListConsumerGroupEntryTable(ListConsumerGroupEntry);
 
 impl Tabled for ListConsumerGroupEntryTable {
-    const LENGTH: usize = 2;
+    const LENGTH: usize = 3;
 
     fn fields(&self) -> Vec<Cow<'_, str>> {
         vec![
             self.0.name.as_str().into(),
             self.0.state.as_str().into(),
+            self.0.members.to_string().into(),
         ]
     }
 
@@ -62,7 +74,32 @@ impl Tabled for ListConsumerGroupEntryTable {
         vec![
             "Name".into(),
             "State".into(),
+            "Members".into(),
         ]
     }
 }
 
+#[derive(Serialize)]
+#[serde(transparent)]
+struct DeleteConsumerGroupEntryTable(DeleteConsumerGroupEntry);
+
+impl Tabled for DeleteConsumerGroupEntryTable {
+    const LENGTH: usize = 3;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let status = if self.0.error.is_none() { "deleted" } else { "failed" };
+        vec![
+            self.0.name.as_str().into(),
+            status.into(),
+            self.0.error.as_deref().unwrap_or_default().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Name".into(),
+            "Status".into(),
+            "Error".into(),
+        ]
+    }
+}
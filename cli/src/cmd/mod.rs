@@ -1,3 +1,9 @@
+pub mod acl;
+pub mod auth;
 pub mod broker;
 pub mod consumer;
-pub(crate) mod table;
\ No newline at end of file
+pub mod output;
+pub mod quota;
+pub mod reassign;
+pub mod topic;
+pub(crate) mod table;
@@ -0,0 +1,132 @@
+use crate::cmd::table;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use tabled::Tabled;
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Colored table
+    #[default]
+    Table,
+    Json,
+    Yaml,
+    /// Comma separated values with a header row
+    Csv,
+    /// Tab separated values without a header row
+    Plain,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Output {
+    format: OutputFormat,
+    color: bool,
+}
+
+impl Output {
+    pub(crate) fn new(format: OutputFormat, color: bool) -> Self {
+        Self { format, color }
+    }
+
+    /// Whether the format renders whole documents rather than rows.
+    pub(crate) fn is_structured(&self) -> bool {
+        matches!(self.format, OutputFormat::Json | OutputFormat::Yaml)
+    }
+
+    /// Whether the format is meant for humans, summaries and hints are only printed for tables.
+    pub(crate) fn is_table(&self) -> bool {
+        self.format == OutputFormat::Table
+    }
+
+    /// Prints a single document for structured formats, rows should be printed with [`Output::print_aligned`] otherwise.
+    pub(crate) fn print_value<T: Serialize>(&self, value: &T) {
+        match self.format {
+            OutputFormat::Yaml => print!(
+                "{}",
+                serde_yaml::to_string(value).expect("failed to serialize yaml")
+            ),
+            _ => println!(
+                "{}",
+                serde_json::to_string_pretty(value).expect("failed to serialize json")
+            ),
+        }
+    }
+
+    /// Prints the items in the selected format, right aligning only the given columns in tables.
+    pub(crate) fn print_aligned<T: Tabled + Serialize>(&self, items: Vec<T>, numeric_columns: &[usize]) {
+        match self.format {
+            OutputFormat::Table => println!("{}", table::create(items, numeric_columns, self.color)),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&items).expect("failed to serialize json")
+            ),
+            OutputFormat::Yaml => print!(
+                "{}",
+                serde_yaml::to_string(&items).expect("failed to serialize yaml")
+            ),
+            OutputFormat::Csv => {
+                println!("{}", csv_line(T::headers().iter().map(|h| h.as_ref())));
+                items.iter()
+                    .for_each(|item| println!("{}", csv_line(item.fields().iter().map(|f| f.as_ref()))));
+            }
+            OutputFormat::Plain => {
+                items.iter()
+                    .for_each(|item| println!("{}", item.fields().join("\t")));
+            }
+        }
+    }
+}
+
+fn csv_line<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    values
+        .map(|value| {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Unwraps the result or prints the error to stderr and exits with a non-zero code.
+pub(crate) fn or_exit<T, E: Display>(result: Result<T, E>, message: &str) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("{message}: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Asks on the terminal before a destructive operation, only an explicit `yes` confirms.
+pub(crate) fn confirm(warning: &str) -> bool {
+    eprint!("{warning} Type 'yes' to continue: ");
+    std::io::stderr().flush().ok();
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).is_ok() && answer.trim() == "yes"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_line_leaves_plain_values_as_is() {
+        assert_eq!(csv_line(["orders", "3", ""].into_iter()), "orders,3,");
+    }
+
+    #[test]
+    fn csv_line_quotes_values_with_commas_and_line_breaks() {
+        assert_eq!(csv_line(["a,b", "c"].into_iter()), "\"a,b\",c");
+        assert_eq!(csv_line(["line\nbreak", "cr\r"].into_iter()), "\"line\nbreak\",\"cr\r\"");
+    }
+
+    #[test]
+    fn csv_line_doubles_quotes() {
+        assert_eq!(csv_line(["say \"hi\""].into_iter()), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::cmd::output::{or_exit, Output};
+use clap::{Args, Subcommand};
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::quota::{EntityName, QuotaEntity};
+use common::kafka::types::{ClientQuotaEntry, QuotaKey};
+use serde::Serialize;
+use std::borrow::Cow;
+use tabled::Tabled;
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(flatten_help = true)]
+pub struct QuotaArgs {
+    #[command(subcommand)]
+    pub(crate) command: Option<QuotaCommands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum QuotaCommands {
+    /// List the quotas of the matching users and client ids, all quotas by default
+    List(QuotaEntityArgs),
+    /// Set quotas of a user, a client id or both
+    Set(SetQuotaArgs),
+    /// Remove quotas of a user, a client id or both
+    Remove(RemoveQuotaArgs),
+}
+
+#[derive(Debug, Args, Default)]
+pub struct QuotaEntityArgs {
+    #[arg(long, conflicts_with = "default_user")]
+    pub(crate) user: Option<String>,
+    /// The default user quota, applying to users without one of their own
+    #[arg(long)]
+    pub(crate) default_user: bool,
+    #[arg(long, conflicts_with = "default_client_id")]
+    pub(crate) client_id: Option<String>,
+    /// The default client id quota, applying to client ids without one of their own
+    #[arg(long)]
+    pub(crate) default_client_id: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SetQuotaArgs {
+    #[command(flatten)]
+    pub(crate) entity: QuotaEntityArgs,
+    /// Produce rate limit, in bytes per second per broker
+    #[arg(long)]
+    pub(crate) producer_byte_rate: Option<f64>,
+    /// Fetch rate limit, in bytes per second per broker
+    #[arg(long)]
+    pub(crate) consumer_byte_rate: Option<f64>,
+    /// Share of request handler and network threads, in percent per broker
+    #[arg(long)]
+    pub(crate) request_percentage: Option<f64>,
+    /// Rate of partition creations and deletions, per second
+    #[arg(long)]
+    pub(crate) controller_mutation_rate: Option<f64>,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoveQuotaArgs {
+    #[command(flatten)]
+    pub(crate) entity: QuotaEntityArgs,
+    /// Quotas to remove, as in producer_byte_rate
+    #[arg(short, long, required = true, value_delimiter = ',')]
+    pub(crate) quota: Vec<QuotaKey>,
+}
+
+impl From<QuotaEntityArgs> for QuotaEntity {
+    fn from(args: QuotaEntityArgs) -> Self {
+        let name = |name: Option<String>, default: bool| match (name, default) {
+            (Some(name), _) => Some(EntityName::Named(name)),
+            (None, true) => Some(EntityName::Default),
+            (None, false) => None,
+        };
+        Self {
+            user: name(args.user, args.default_user),
+            client_id: name(args.client_id, args.default_client_id),
+        }
+    }
+}
+
+pub(crate) fn list(config: &Config, args: QuotaEntityArgs, output: &Output) {
+    let quotas = or_exit(kafka::quota::describe_quotas(config, &args.into()), "Failed to describe quotas")
+        .into_iter()
+        .map(ClientQuotaTable)
+        .collect();
+    output.print_aligned(quotas, &[1, 2, 3, 4])
+}
+
+pub(crate) fn set(config: &Config, args: SetQuotaArgs, output: &Output) {
+    let set = [
+        (QuotaKey::ProducerByteRate, args.producer_byte_rate),
+        (QuotaKey::ConsumerByteRate, args.consumer_byte_rate),
+        (QuotaKey::RequestPercentage, args.request_percentage),
+        (QuotaKey::ControllerMutationRate, args.controller_mutation_rate),
+    ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect::<Vec<_>>();
+    alter(config, args.entity.into(), &set, &[], output)
+}
+
+pub(crate) fn remove(config: &Config, args: RemoveQuotaArgs, output: &Output) {
+    alter(config, args.entity.into(), &[], &args.quota, output)
+}
+
+// Applies the change and prints the resulting quotas of the entity.
+fn alter(config: &Config, entity: QuotaEntity, set: &[(QuotaKey, f64)], remove: &[QuotaKey], output: &Output) {
+    or_exit(kafka::quota::alter_quotas(config, &entity, set, remove), "Failed to alter quotas");
+    let quotas = or_exit(kafka::quota::describe_quotas(config, &entity), "Failed to describe quotas")
+        .into_iter()
+        .map(ClientQuotaTable)
+        .collect();
+    output.print_aligned(quotas, &[1, 2, 3, 4])
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct ClientQuotaTable(ClientQuotaEntry);
+
+impl Tabled for ClientQuotaTable {
+    const LENGTH: usize = 5;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let mut fields: Vec<Cow<'_, str>> = vec![kafka::quota::entity_label(&self.0.entity).into()];
+        fields.extend(QuotaKey::VARIANTS.iter().map(|key| {
+            self.0.quotas.get(key.name()).map(|v| v.to_string()).unwrap_or_default().into()
+        }));
+        fields
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Entity".into(),
+            "Producer Byte Rate".into(),
+            "Consumer Byte Rate".into(),
+            "Request Percentage".into(),
+            "Controller Mutation Rate".into(),
+        ]
+    }
+}
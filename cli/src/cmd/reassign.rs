@@ -0,0 +1,299 @@
+use crate::cmd::output::{or_exit, Output};
+use clap::{Args, Subcommand};
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::types::{PartitionResultEntry, ReassignmentPlan, ReassignmentStatus, ReassignmentStatusEntry};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tabled::Tabled;
+
+const WAIT_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Args)]
+pub struct ReassignArgs {
+    #[command(subcommand)]
+    pub(crate) command: ReassignCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReassignCommands {
+    /// Generate a balanced, rack aware plan for the topics
+    Plan(PlanArgs),
+    /// Start the reassignments of a plan
+    Execute(ExecuteArgs),
+    /// Show the progress of a plan
+    Status(StatusArgs),
+    /// Cancel the ongoing reassignments of a plan, or all of them
+    Cancel(CancelArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct PlanArgs {
+    /// Topics to reassign
+    #[arg(short, long, required = true, value_delimiter = ',')]
+    pub(crate) topics: Vec<String>,
+    /// Brokers to place the replicas on, defaults to every broker
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) brokers: Vec<i32>,
+    /// Write the plan to this file, in the `kafka-reassign-partitions` format
+    #[arg(short, long)]
+    pub(crate) file: Option<PathBuf>,
+    /// Write the current assignment of the moved partitions to this file, to roll back with
+    #[arg(long)]
+    pub(crate) rollback_file: Option<PathBuf>,
+    /// Open the written plan in $EDITOR before validating it
+    #[arg(long, requires = "file")]
+    pub(crate) edit: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ExecuteArgs {
+    /// Plan file, in the `kafka-reassign-partitions` format
+    #[arg(short, long)]
+    pub(crate) plan: PathBuf,
+    /// Limit replication traffic of the moved partitions, in bytes per second
+    #[arg(long, value_name = "BYTES_PER_SEC")]
+    pub(crate) throttle: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+pub struct StatusArgs {
+    /// Plan file, in the `kafka-reassign-partitions` format
+    #[arg(short, long)]
+    pub(crate) plan: PathBuf,
+    /// Poll until no reassignment of the plan is in progress
+    #[arg(short, long)]
+    pub(crate) wait: bool,
+    /// Remove the replication throttle once every reassignment of the plan completed
+    #[arg(long)]
+    pub(crate) remove_throttle: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CancelArgs {
+    /// Plan file, cancels every ongoing reassignment when not given
+    #[arg(short, long)]
+    pub(crate) plan: Option<PathBuf>,
+}
+
+pub(crate) fn plan(config: &Config, args: PlanArgs, output: &Output) {
+    let mut proposal = or_exit(
+        kafka::reassign::plan(config, &args.topics, &args.brokers),
+        "Failed to generate reassignment plan",
+    );
+    if let Some(file) = &args.rollback_file {
+        write_plan(file, &proposal.current);
+    }
+    if let Some(file) = &args.file {
+        write_plan(file, &proposal.proposed);
+        if args.edit {
+            edit(file);
+            proposal.proposed = read_plan(file);
+        }
+    }
+
+    if output.is_structured() {
+        output.print_value(&proposal);
+        return;
+    }
+    if output.is_table() {
+        println!("{} partitions to move", proposal.proposed.partitions.len());
+    }
+    let rows = proposal.proposed.partitions.iter()
+        .map(|proposed| {
+            let current = proposal.current.partitions.iter()
+                .find(|c| c.topic == proposed.topic && c.partition == proposed.partition)
+                .map(|c| c.replicas.clone())
+                .unwrap_or_default();
+            PlannedMoveTable {
+                topic: proposed.topic.clone(),
+                partition: proposed.partition,
+                current,
+                proposed: proposed.replicas.clone(),
+            }
+        })
+        .collect();
+    output.print_aligned(rows, &[1])
+}
+
+pub(crate) fn execute(config: &Config, args: ExecuteArgs, output: &Output) {
+    let plan = read_plan(&args.plan);
+    let results = or_exit(
+        kafka::reassign::execute(config, &plan, args.throttle),
+        "Failed to execute reassignment plan",
+    );
+    print_results(results, output);
+    if output.is_table() && args.throttle.is_some() {
+        println!("Remove the throttle once done with `reassign status --remove-throttle`");
+    }
+}
+
+pub(crate) async fn status(config: &Config, args: StatusArgs, output: &Output) {
+    let plan = read_plan(&args.plan);
+    let mut statuses = fetch_status(config, &plan);
+    while args.wait && in_progress(&statuses) > 0 {
+        let completed = statuses.iter().filter(|s| s.status == ReassignmentStatus::Completed).count();
+        eprintln!("{completed} of {} partitions completed, {} in progress", statuses.len(), in_progress(&statuses));
+        tokio::time::sleep(WAIT_INTERVAL).await;
+        statuses = fetch_status(config, &plan);
+    }
+
+    let completed = statuses.iter().all(|s| s.status == ReassignmentStatus::Completed);
+    if output.is_table() {
+        println!(
+            "{} of {} partitions completed",
+            statuses.iter().filter(|s| s.status == ReassignmentStatus::Completed).count(),
+            statuses.len(),
+        );
+    }
+    output.print_aligned(statuses.into_iter().map(ReassignmentStatusTable).collect(), &[1]);
+
+    if args.remove_throttle {
+        if completed {
+            or_exit(kafka::reassign::remove_throttle(config, &plan), "Failed to remove replication throttle");
+            eprintln!("Replication throttle removed");
+        } else {
+            eprintln!("Throttle kept, not every reassignment of the plan completed");
+        }
+    }
+}
+
+pub(crate) fn cancel(config: &Config, args: CancelArgs, output: &Output) {
+    let plan = args.plan.as_deref().map(read_plan);
+    let results = or_exit(
+        kafka::reassign::cancel(config, plan.as_ref()),
+        "Failed to cancel reassignments",
+    );
+    print_results(results, output);
+}
+
+fn fetch_status(config: &Config, plan: &ReassignmentPlan) -> Vec<ReassignmentStatusEntry> {
+    or_exit(kafka::reassign::status(config, plan), "Failed to fetch reassignment status")
+}
+
+fn in_progress(statuses: &[ReassignmentStatusEntry]) -> usize {
+    statuses.iter().filter(|s| s.status == ReassignmentStatus::InProgress).count()
+}
+
+fn print_results(results: Vec<PartitionResultEntry>, output: &Output) {
+    let failed = results.iter().any(|r| r.error.is_some());
+    output.print_aligned(results.into_iter().map(PartitionResultTable).collect(), &[1]);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn read_plan(path: &Path) -> ReassignmentPlan {
+    let content = or_exit(std::fs::read_to_string(path), &format!("Failed to read {}", path.display()));
+    or_exit(serde_json::from_str(&content), &format!("Failed to parse {}", path.display()))
+}
+
+fn write_plan(path: &Path, plan: &ReassignmentPlan) {
+    let content = serde_json::to_string_pretty(plan).expect("failed to serialize json");
+    or_exit(std::fs::write(path, content + "\n"), &format!("Failed to write {}", path.display()));
+}
+
+fn edit(path: &Path) {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let status = or_exit(Command::new(&editor).arg(path).status(), &format!("Failed to run {editor}"));
+    if !status.success() {
+        eprintln!("{editor} exited with {status}");
+        std::process::exit(1);
+    }
+}
+
+fn replicas(replicas: &[i32]) -> String {
+    replicas.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[derive(Serialize)]
+struct PlannedMoveTable {
+    topic: String,
+    partition: i32,
+    current: Vec<i32>,
+    proposed: Vec<i32>,
+}
+
+impl Tabled for PlannedMoveTable {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.topic.as_str().into(),
+            self.partition.to_string().into(),
+            replicas(&self.current).into(),
+            replicas(&self.proposed).into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Topic".into(),
+            "Partition".into(),
+            "Current".into(),
+            "Proposed".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct ReassignmentStatusTable(ReassignmentStatusEntry);
+
+impl Tabled for ReassignmentStatusTable {
+    const LENGTH: usize = 6;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.0.topic.as_str().into(),
+            self.0.partition.to_string().into(),
+            self.0.status.name().into(),
+            replicas(&self.0.replicas).into(),
+            replicas(&self.0.adding).into(),
+            replicas(&self.0.removing).into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Topic".into(),
+            "Partition".into(),
+            "Status".into(),
+            "Replicas".into(),
+            "Adding".into(),
+            "Removing".into(),
+        ]
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub(crate) struct PartitionResultTable(pub(crate) PartitionResultEntry);
+
+impl Tabled for PartitionResultTable {
+    const LENGTH: usize = 4;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let status = if self.0.error.is_none() { "ok" } else { "failed" };
+        vec![
+            self.0.topic.as_str().into(),
+            self.0.partition.to_string().into(),
+            status.into(),
+            self.0.error.as_deref().unwrap_or_default().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Topic".into(),
+            "Partition".into(),
+            "Status".into(),
+            "Error".into(),
+        ]
+    }
+}
@@ -21,17 +21,20 @@ pub(crate) fn even_odd_rows(len: usize, has_header: bool, even_color: Color, odd
     )
 }
 
-pub(crate) fn create<T: Tabled>(items: Vec<T>) -> Table {
+/// Creates a table right aligning only the given numeric columns.
+pub(crate) fn create<T: Tabled>(items: Vec<T>, numeric_columns: &[usize], color: bool) -> Table {
     let style = Style::modern()
         .remove_horizontal();
 
-    Table::new(items.iter().clone())
-        .with(style)
-        .with(even_odd_rows(items.len(), true, Color::empty(), odd_color()))
-        .with(Colorization::exact([head_color()], Rows::first()))
-        .modify(Columns::single(1), NUMERIC_SETTINGS)
-        .modify(Columns::single(2), NUMERIC_SETTINGS)
-        .modify(Columns::single(3), NUMERIC_SETTINGS)
-        .modify(Columns::single(4), NUMERIC_SETTINGS)
-        .to_owned()
+    let mut table = Table::new(items.iter().clone());
+    table.with(style);
+    if color {
+        table
+            .with(even_odd_rows(items.len(), true, Color::empty(), odd_color()))
+            .with(Colorization::exact([head_color()], Rows::first()));
+    }
+    for column in numeric_columns {
+        table.modify(Columns::single(*column), NUMERIC_SETTINGS);
+    }
+    table
 }
@@ -1 +1,131 @@
-use clap::Args;
+use crate::cmd::output::{confirm, or_exit, Output};
+use crate::cmd::table;
+use chrono::DateTime;
+use clap::{ArgGroup, Args, Subcommand};
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::topic::TruncateTarget;
+use common::kafka::types::DeleteRecordsEntry;
+use serde::Serialize;
+use std::borrow::Cow;
+use tabled::Tabled;
+
+#[derive(Debug, Args)]
+pub struct TopicArgs {
+    #[command(subcommand)]
+    pub(crate) command: TopicCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TopicCommands {
+    /// Delete the records of topics before an offset, a timestamp or the high watermark
+    Truncate(TruncateArgs),
+}
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("target").required(true).args(["offset", "timestamp", "all"])))]
+pub struct TruncateArgs {
+    /// Topics to truncate
+    #[arg(short, long, required = true, value_delimiter = ',')]
+    pub(crate) topics: Vec<String>,
+    /// Partitions to truncate, defaults to every partition
+    #[arg(short, long, value_delimiter = ',')]
+    pub(crate) partitions: Vec<i32>,
+    /// Delete the records before this offset
+    #[arg(long)]
+    pub(crate) offset: Option<i64>,
+    /// Delete the records before this time, in milliseconds since the epoch or RFC 3339
+    #[arg(long, value_parser = parse_timestamp)]
+    pub(crate) timestamp: Option<i64>,
+    /// Delete every record, up to the high watermark
+    #[arg(long)]
+    pub(crate) all: bool,
+    /// Only show what would be deleted
+    #[arg(long)]
+    pub(crate) dry_run: bool,
+    /// Skip the confirmation
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+}
+
+fn parse_timestamp(value: &str) -> Result<i64, String> {
+    value.parse::<i64>()
+        .or_else(|_| DateTime::parse_from_rfc3339(value).map(|t| t.timestamp_millis()))
+        .map_err(|_| "expected milliseconds since the epoch or an RFC 3339 time".to_string())
+}
+
+/// Shows what the truncation deletes and applies it once confirmed.
+pub(crate) fn truncate(config: &Config, args: TruncateArgs, output: &Output) {
+    let target = match (args.offset, args.timestamp) {
+        (Some(offset), _) => TruncateTarget::Offset(offset),
+        (None, Some(timestamp)) => TruncateTarget::Timestamp(timestamp),
+        (None, None) => TruncateTarget::HighWatermark,
+    };
+    if !args.dry_run {
+        or_exit(config.check_writable("delete records"), "Failed to delete records");
+    }
+    let plan = or_exit(
+        kafka::topic::plan_delete_records(config, &args.topics, &args.partitions, target),
+        "Failed to plan truncation",
+    );
+    let records = plan.iter().map(DeleteRecordsEntry::records).sum::<i64>();
+
+    if args.dry_run {
+        if output.is_table() {
+            println!("{records} records would be deleted");
+        }
+        output.print_aligned(plan.into_iter().map(DeleteRecordsTable).collect(), &[1, 2, 3, 4, 5]);
+        return;
+    }
+    if records == 0 {
+        eprintln!("No records to delete");
+        return;
+    }
+    if !args.yes {
+        let rows = plan.iter().cloned().map(DeleteRecordsTable).collect::<Vec<_>>();
+        eprintln!("{}", table::create(rows, &[1, 2, 3, 4, 5], false));
+        if !confirm(&format!("{records} records will be deleted.")) {
+            eprintln!("Aborted");
+            std::process::exit(1);
+        }
+    }
+
+    let results = or_exit(kafka::topic::delete_records(config, &plan), "Failed to delete records");
+    let failed = results.iter().any(|r| r.error.is_some());
+    output.print_aligned(results.into_iter().map(DeleteRecordsTable).collect(), &[1, 2, 3, 4, 5]);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct DeleteRecordsTable(DeleteRecordsEntry);
+
+impl Tabled for DeleteRecordsTable {
+    const LENGTH: usize = 7;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.0.topic.as_str().into(),
+            self.0.partition.to_string().into(),
+            self.0.low_watermark.to_string().into(),
+            self.0.high_watermark.to_string().into(),
+            self.0.offset.to_string().into(),
+            self.0.records().to_string().into(),
+            self.0.error.as_deref().unwrap_or_default().into(),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        vec![
+            "Topic".into(),
+            "Partition".into(),
+            "Low Watermark".into(),
+            "High Watermark".into(),
+            "Delete Before".into(),
+            "Records".into(),
+            "Error".into(),
+        ]
+    }
+}
@@ -1,10 +1,19 @@
 mod cmd;
 
-use crate::cmd::broker::{ClusterArgs, ClusterCommands};
+use crate::cmd::acl::{AclArgs, AclCommands, AclFilterArgs};
+use crate::cmd::auth::{AuthArgs, AuthCommands};
+use crate::cmd::broker::{ClusterArgs, ClusterCommands, ListBrokersArgs};
 use crate::cmd::consumer::{ConsumerArgs, ConsumerCommands, ListConsumerArgs};
+use crate::cmd::output::{Output, OutputFormat};
+use crate::cmd::quota::{QuotaArgs, QuotaCommands, QuotaEntityArgs};
+use crate::cmd::reassign::{ReassignArgs, ReassignCommands};
+use crate::cmd::topic::{TopicArgs, TopicCommands};
 use clap::{Parser, Subcommand, ValueEnum};
 use common::kafka;
+use common::kafka::iam::{self, TokenOptions};
 use core::time::Duration;
+use std::ffi::OsString;
+use std::io::IsTerminal;
 
 #[derive(Debug, Parser)] // requires `derive` feature
 #[command(name = "kafka-utils")]
@@ -20,6 +29,27 @@ struct Cli {
     timeout: u64,
     #[arg(short, long, default_value = "eu-west-1")]
     aws_region: String,
+    /// Lifetime in seconds of the IAM auth token, at most 900
+    #[arg(long, default_value = "900", value_parser = clap::value_parser!(u64).range(1..=iam::MAX_EXPIRY_SECONDS))]
+    iam_token_lifetime: u64,
+    /// Sign the IAM auth token for the FIPS endpoint
+    #[arg(long)]
+    iam_fips: bool,
+    /// Sign the IAM auth token for a custom endpoint url
+    #[arg(long, conflicts_with = "iam_fips")]
+    iam_endpoint: Option<String>,
+    /// Output format
+    #[arg(short, long, global = true, default_value_t = OutputFormat::Table, value_enum)]
+    output: OutputFormat,
+    /// When to color table output
+    #[arg(long, global = true, default_value_t = ColorWhen::Auto, value_enum)]
+    color: ColorWhen,
+    /// Disable colored output, same as `--color never`
+    #[arg(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+    /// Refuse every operation changing the cluster
+    #[arg(long, global = true)]
+    read_only: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -28,6 +58,17 @@ enum Commands {
     Cluster(ClusterArgs),
     #[command(arg_required_else_help = true)]
     Consumers(ConsumerArgs),
+    /// Manage the records of topics
+    Topics(TopicArgs),
+    /// Plan, execute and follow partition reassignments
+    Reassign(ReassignArgs),
+    /// List, create and delete ACL bindings
+    Acls(AclArgs),
+    /// List, set and remove client quotas
+    Quotas(QuotaArgs),
+    Auth(AuthArgs),
+    /// Alias for `auth check`
+    Doctor,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq)]
@@ -37,6 +78,28 @@ enum ColorWhen {
     Never,
 }
 
+impl ColorWhen {
+    /// Resolves whether to color, `auto` honours a non-empty `NO_COLOR` and only colors terminals.
+    fn enabled(self) -> bool {
+        self.enabled_with(std::env::var_os("NO_COLOR"), std::io::stdout().is_terminal())
+    }
+
+    fn enabled_with(self, no_color: Option<OsString>, terminal: bool) -> bool {
+        match self {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => no_color.is_none_or(|value| value.is_empty()) && terminal,
+        }
+    }
+}
+
+impl Cli {
+    /// `--no-color` stands for `--color never`.
+    fn color(&self) -> ColorWhen {
+        if self.no_color { ColorWhen::Never } else { self.color }
+    }
+}
+
 impl std::fmt::Display for ColorWhen {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.to_possible_value()
@@ -52,19 +115,35 @@ async fn main() {
 
     let cli = Cli::parse();
 
+    let output = Output::new(cli.output, cli.color().enabled());
+
     let config = kafka::client::create_config(
         cli.bootstrap_servers,
         cli.iam_auth,
         cli.aws_region,
-        Duration::from_millis(cli.timeout.into()),
-    );
+        TokenOptions::new(cli.iam_token_lifetime, cli.iam_fips, cli.iam_endpoint),
+        Duration::from_millis(cli.timeout),
+    )
+        .read_only(cli.read_only);
 
     match cli.command {
         Commands::Cluster(cluster) => {
-            let cluster_cmd = cluster.command.unwrap_or(ClusterCommands::Brokers);
+            let cluster_cmd = cluster.command.unwrap_or(ClusterCommands::Brokers(ListBrokersArgs::default()));
             match cluster_cmd {
-                ClusterCommands::Brokers => {
-                    cmd::broker::list_brokers_cmd(&config)
+                ClusterCommands::Brokers(args) => {
+                    match args.config {
+                        Some(broker_id) => cmd::broker::broker_configs_cmd(&config, broker_id, &output).await,
+                        None => cmd::broker::list_brokers_cmd(&config, &output),
+                    }
+                }
+                ClusterCommands::Describe => {
+                    cmd::broker::describe_cluster_cmd(&config, &output)
+                }
+                ClusterCommands::Health => {
+                    cmd::broker::health_cmd(&config, &output).await
+                }
+                ClusterCommands::ElectLeaders(args) => {
+                    cmd::broker::elect_leaders_cmd(&config, args, &output)
                 }
             }
         }
@@ -72,12 +151,116 @@ async fn main() {
             let consumer_cmd = consumer.command.unwrap_or(ConsumerCommands::List(ListConsumerArgs { consumer_group: None }));
             match consumer_cmd {
                 ConsumerCommands::List(args) => {
-                    cmd::consumer::list(&config, args.consumer_group)
+                    cmd::consumer::list(&config, args.consumer_group, &output)
                 }
                 ConsumerCommands::Delete(args) => {
-                    cmd::consumer::delete(&config, args.consumer_group).await
+                    cmd::consumer::delete(&config, args.consumer_group, &output).await
+                }
+            }
+        }
+        Commands::Topics(topics) => {
+            match topics.command {
+                TopicCommands::Truncate(args) => {
+                    cmd::topic::truncate(&config, args, &output)
+                }
+            }
+        }
+        Commands::Reassign(reassign) => {
+            match reassign.command {
+                ReassignCommands::Plan(args) => {
+                    cmd::reassign::plan(&config, args, &output)
+                }
+                ReassignCommands::Execute(args) => {
+                    cmd::reassign::execute(&config, args, &output)
+                }
+                ReassignCommands::Status(args) => {
+                    cmd::reassign::status(&config, args, &output).await
+                }
+                ReassignCommands::Cancel(args) => {
+                    cmd::reassign::cancel(&config, args, &output)
+                }
+            }
+        }
+        Commands::Acls(acls) => {
+            let acl_cmd = acls.command.unwrap_or(AclCommands::List(AclFilterArgs::default()));
+            match acl_cmd {
+                AclCommands::List(args) => {
+                    cmd::acl::list(&config, args, &output)
+                }
+                AclCommands::Create(args) => {
+                    cmd::acl::create(&config, args, &output)
+                }
+                AclCommands::Delete(args) => {
+                    cmd::acl::delete(&config, args, &output)
+                }
+            }
+        }
+        Commands::Quotas(quotas) => {
+            let quota_cmd = quotas.command.unwrap_or(QuotaCommands::List(QuotaEntityArgs::default()));
+            match quota_cmd {
+                QuotaCommands::List(args) => {
+                    cmd::quota::list(&config, args, &output)
+                }
+                QuotaCommands::Set(args) => {
+                    cmd::quota::set(&config, args, &output)
+                }
+                QuotaCommands::Remove(args) => {
+                    cmd::quota::remove(&config, args, &output)
                 }
             }
         }
+        Commands::Auth(auth) => {
+            let auth_cmd = auth.command.unwrap_or(AuthCommands::Check);
+            match auth_cmd {
+                AuthCommands::Check => {
+                    cmd::auth::check(&config, &output).await
+                }
+            }
+        }
+        Commands::Doctor => {
+            cmd::auth::check(&config, &output).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(args: &[&str]) -> ColorWhen {
+        let args = ["kafka-utils", "-b", "broker:9092"].iter().chain(args).chain(&["cluster", "brokers"]);
+        Cli::try_parse_from(args).unwrap().color()
+    }
+
+    #[test]
+    fn color_flags_resolve_to_a_mode() {
+        assert_eq!(color(&[]), ColorWhen::Auto);
+        assert_eq!(color(&["--color", "always"]), ColorWhen::Always);
+        assert_eq!(color(&["--color", "never"]), ColorWhen::Never);
+        assert_eq!(color(&["--no-color"]), ColorWhen::Never);
+    }
+
+    #[test]
+    fn no_color_conflicts_with_color() {
+        let args = ["kafka-utils", "-b", "broker:9092", "--no-color", "--color", "always", "cluster", "brokers"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn auto_colors_terminals_without_no_color() {
+        assert!(ColorWhen::Auto.enabled_with(None, true));
+        assert!(!ColorWhen::Auto.enabled_with(None, false));
+        assert!(!ColorWhen::Auto.enabled_with(Some("1".into()), true));
+    }
+
+    #[test]
+    fn auto_ignores_an_empty_no_color() {
+        assert!(ColorWhen::Auto.enabled_with(Some("".into()), true));
+    }
+
+    #[test]
+    fn always_and_never_ignore_the_environment() {
+        assert!(ColorWhen::Always.enabled_with(Some("1".into()), false));
+        assert!(!ColorWhen::Never.enabled_with(None, true));
     }
 }
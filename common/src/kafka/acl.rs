@@ -0,0 +1,306 @@
+use crate::kafka::client::{Config, ReadOnlyError};
+use crate::kafka::native;
+use crate::kafka::types::{AclBindingEntry, AclOperation, AclPatternType, AclPermission, AclResourceType, AclResultEntry};
+use rdkafka::bindings as rdsys;
+use rdkafka::error::KafkaError;
+use std::ffi::{c_char, CString};
+use std::ptr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AclError {
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+    #[error("invalid acl: {0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// Selects ACL bindings, `None` and the `Any` variants match everything.
+#[derive(Debug, Clone)]
+pub struct AclFilter {
+    pub resource_type: AclResourceType,
+    pub resource_name: Option<String>,
+    pub pattern_type: AclPatternType,
+    pub principal: Option<String>,
+    pub host: Option<String>,
+    pub operation: AclOperation,
+    pub permission: AclPermission,
+}
+
+impl Default for AclFilter {
+    fn default() -> Self {
+        Self {
+            resource_type: AclResourceType::Any,
+            resource_name: None,
+            pattern_type: AclPatternType::Any,
+            principal: None,
+            host: None,
+            operation: AclOperation::Any,
+            permission: AclPermission::Any,
+        }
+    }
+}
+
+pub fn describe_acls(config: &Config, filter: &AclFilter) -> Result<Vec<AclBindingEntry>, AclError> {
+    let filter = NativeBinding::filter(filter)?;
+    let mut acls = native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_DESCRIBEACLS,
+        |rk, options, queue| unsafe { rdsys::rd_kafka_DescribeAcls(rk, filter.0, options, queue) },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_DescribeAcls_result(event);
+            let mut count = 0;
+            let acls = rdsys::rd_kafka_DescribeAcls_result_acls(result, &mut count);
+            Ok(native::as_slice(acls, count).iter().map(|acl| binding(*acl)).collect::<Vec<_>>())
+        },
+    )?;
+    acls.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    Ok(acls)
+}
+
+/// Creates the bindings, returning the outcome of each.
+pub fn create_acls(config: &Config, bindings: &[AclBindingEntry]) -> Result<Vec<AclResultEntry>, AclError> {
+    config.check_writable("create acls")?;
+    let natives = bindings.iter()
+        .map(NativeBinding::binding)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut pointers = natives.iter().map(|b| b.0).collect::<Vec<_>>();
+    let errors = native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_CREATEACLS,
+        |rk, options, queue| unsafe {
+            rdsys::rd_kafka_CreateAcls(rk, pointers.as_mut_ptr(), pointers.len(), options, queue)
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_CreateAcls_result(event);
+            let mut count = 0;
+            let results = rdsys::rd_kafka_CreateAcls_result_acls(result, &mut count);
+            Ok(native::as_slice(results, count)
+                .iter()
+                .map(|r| native::error_message(rdsys::rd_kafka_acl_result_error(*r)))
+                .collect::<Vec<_>>())
+        },
+    )?;
+    Ok(bindings.iter()
+        .cloned()
+        .zip(errors.into_iter().chain(std::iter::repeat(None)))
+        .map(|(binding, error)| AclResultEntry { binding, error })
+        .collect())
+}
+
+/// Deletes every binding matching the filter, returning the deleted bindings.
+pub fn delete_acls(config: &Config, filter: &AclFilter) -> Result<Vec<AclResultEntry>, AclError> {
+    config.check_writable("delete acls")?;
+    let filter = NativeBinding::filter(filter)?;
+    let mut filters = [filter.0];
+    let responses = native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_DELETEACLS,
+        |rk, options, queue| unsafe {
+            rdsys::rd_kafka_DeleteAcls(rk, filters.as_mut_ptr(), filters.len(), options, queue)
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_DeleteAcls_result(event);
+            let mut count = 0;
+            let responses = rdsys::rd_kafka_DeleteAcls_result_responses(result, &mut count);
+            Ok(native::as_slice(responses, count)
+                .iter()
+                .map(|response| {
+                    if let Some(error) = native::error_message(rdsys::rd_kafka_DeleteAcls_result_response_error(*response)) {
+                        return Err(error);
+                    }
+                    let mut count = 0;
+                    let matching = rdsys::rd_kafka_DeleteAcls_result_response_matching_acls(*response, &mut count);
+                    Ok(native::as_slice(matching, count)
+                        .iter()
+                        .map(|acl| AclResultEntry {
+                            binding: binding(*acl),
+                            error: native::error_message(rdsys::rd_kafka_AclBinding_error(*acl)),
+                        })
+                        .collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>())
+        },
+    )?;
+
+    let mut deleted = Vec::new();
+    for response in responses {
+        deleted.extend(response.map_err(AclError::Rejected)?);
+    }
+    Ok(deleted)
+}
+
+fn sort_key(acl: &AclBindingEntry) -> (&'static str, &str, &str, &'static str) {
+    (acl.resource_type.name(), &acl.resource_name, &acl.principal, acl.operation.name())
+}
+
+/// An owned native binding or filter, destroyed on drop.
+struct NativeBinding(*mut rdsys::rd_kafka_AclBinding_t);
+
+impl NativeBinding {
+    fn binding(binding: &AclBindingEntry) -> Result<Self, AclError> {
+        Self::new(
+            false,
+            binding.resource_type,
+            Some(&binding.resource_name),
+            binding.pattern_type,
+            Some(&binding.principal),
+            Some(&binding.host),
+            binding.operation,
+            binding.permission,
+        )
+    }
+
+    fn filter(filter: &AclFilter) -> Result<Self, AclError> {
+        Self::new(
+            true,
+            filter.resource_type,
+            filter.resource_name.as_deref(),
+            filter.pattern_type,
+            filter.principal.as_deref(),
+            filter.host.as_deref(),
+            filter.operation,
+            filter.permission,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        is_filter: bool,
+        resource_type: AclResourceType,
+        resource_name: Option<&str>,
+        pattern_type: AclPatternType,
+        principal: Option<&str>,
+        host: Option<&str>,
+        operation: AclOperation,
+        permission: AclPermission,
+    ) -> Result<Self, AclError> {
+        let resource_name = c_string(resource_name)?;
+        let principal = c_string(principal)?;
+        let host = c_string(host)?;
+        let mut errstr = [0 as c_char; 512];
+        let new = if is_filter { rdsys::rd_kafka_AclBindingFilter_new } else { rdsys::rd_kafka_AclBinding_new };
+        let ptr = unsafe {
+            new(
+                native_resource_type(resource_type),
+                as_ptr(&resource_name),
+                native_pattern_type(pattern_type),
+                as_ptr(&principal),
+                as_ptr(&host),
+                native_operation(operation),
+                native_permission(permission),
+                errstr.as_mut_ptr(),
+                errstr.len(),
+            )
+        };
+        if ptr.is_null() {
+            let message = unsafe { native::to_string(errstr.as_ptr()) }.unwrap_or_default();
+            return Err(AclError::Invalid(message));
+        }
+        Ok(Self(ptr))
+    }
+}
+
+impl Drop for NativeBinding {
+    fn drop(&mut self) {
+        unsafe { rdsys::rd_kafka_AclBinding_destroy(self.0) }
+    }
+}
+
+fn c_string(value: Option<&str>) -> Result<Option<CString>, AclError> {
+    value.map(CString::new)
+        .transpose()
+        .map_err(|e| AclError::Invalid(e.to_string()))
+}
+
+fn as_ptr(value: &Option<CString>) -> *const c_char {
+    value.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null())
+}
+
+/// Reads a binding returned by the admin API.
+///
+/// # Safety
+/// `acl` must point to a valid binding.
+unsafe fn binding(acl: *const rdsys::rd_kafka_AclBinding_t) -> AclBindingEntry {
+    AclBindingEntry {
+        resource_type: match rdsys::rd_kafka_AclBinding_restype(acl) {
+            rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC => AclResourceType::Topic,
+            rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP => AclResourceType::Group,
+            rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER => AclResourceType::Cluster,
+            rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_ANY => AclResourceType::Any,
+            _ => AclResourceType::Unknown,
+        },
+        resource_name: native::to_string(rdsys::rd_kafka_AclBinding_name(acl)).unwrap_or_default(),
+        pattern_type: match rdsys::rd_kafka_AclBinding_resource_pattern_type(acl) {
+            rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_ANY => AclPatternType::Any,
+            rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_MATCH => AclPatternType::Match,
+            rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_LITERAL => AclPatternType::Literal,
+            rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_PREFIXED => AclPatternType::Prefixed,
+            _ => AclPatternType::Unknown,
+        },
+        principal: native::to_string(rdsys::rd_kafka_AclBinding_principal(acl)).unwrap_or_default(),
+        host: native::to_string(rdsys::rd_kafka_AclBinding_host(acl)).unwrap_or_default(),
+        operation: AclOperation::VARIANTS.iter()
+            .find(|o| native_operation(**o) == rdsys::rd_kafka_AclBinding_operation(acl))
+            .copied()
+            .unwrap_or(AclOperation::Unknown),
+        permission: match rdsys::rd_kafka_AclBinding_permission_type(acl) {
+            rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_ANY => AclPermission::Any,
+            rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_ALLOW => AclPermission::Allow,
+            rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_DENY => AclPermission::Deny,
+            _ => AclPermission::Unknown,
+        },
+    }
+}
+
+fn native_resource_type(resource_type: AclResourceType) -> rdsys::rd_kafka_ResourceType_t {
+    match resource_type {
+        AclResourceType::Any => rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_ANY,
+        AclResourceType::Topic => rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+        AclResourceType::Group => rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP,
+        AclResourceType::Cluster => rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+        AclResourceType::Unknown => rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_UNKNOWN,
+    }
+}
+
+fn native_pattern_type(pattern_type: AclPatternType) -> rdsys::rd_kafka_ResourcePatternType_t {
+    match pattern_type {
+        AclPatternType::Any => rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_ANY,
+        AclPatternType::Match => rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_MATCH,
+        AclPatternType::Literal => rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_LITERAL,
+        AclPatternType::Prefixed => rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_PREFIXED,
+        AclPatternType::Unknown => rdsys::rd_kafka_ResourcePatternType_t::RD_KAFKA_RESOURCE_PATTERN_UNKNOWN,
+    }
+}
+
+fn native_operation(operation: AclOperation) -> rdsys::rd_kafka_AclOperation_t {
+    use rdsys::rd_kafka_AclOperation_t::*;
+    match operation {
+        AclOperation::Any => RD_KAFKA_ACL_OPERATION_ANY,
+        AclOperation::All => RD_KAFKA_ACL_OPERATION_ALL,
+        AclOperation::Read => RD_KAFKA_ACL_OPERATION_READ,
+        AclOperation::Write => RD_KAFKA_ACL_OPERATION_WRITE,
+        AclOperation::Create => RD_KAFKA_ACL_OPERATION_CREATE,
+        AclOperation::Delete => RD_KAFKA_ACL_OPERATION_DELETE,
+        AclOperation::Alter => RD_KAFKA_ACL_OPERATION_ALTER,
+        AclOperation::Describe => RD_KAFKA_ACL_OPERATION_DESCRIBE,
+        AclOperation::ClusterAction => RD_KAFKA_ACL_OPERATION_CLUSTER_ACTION,
+        AclOperation::DescribeConfigs => RD_KAFKA_ACL_OPERATION_DESCRIBE_CONFIGS,
+        AclOperation::AlterConfigs => RD_KAFKA_ACL_OPERATION_ALTER_CONFIGS,
+        AclOperation::IdempotentWrite => RD_KAFKA_ACL_OPERATION_IDEMPOTENT_WRITE,
+        AclOperation::Unknown => RD_KAFKA_ACL_OPERATION_UNKNOWN,
+    }
+}
+
+fn native_permission(permission: AclPermission) -> rdsys::rd_kafka_AclPermissionType_t {
+    match permission {
+        AclPermission::Any => rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_ANY,
+        AclPermission::Allow => rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_ALLOW,
+        AclPermission::Deny => rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_DENY,
+        AclPermission::Unknown => rdsys::rd_kafka_AclPermissionType_t::RD_KAFKA_ACL_PERMISSION_TYPE_UNKNOWN,
+    }
+}
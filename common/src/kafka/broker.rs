@@ -1,21 +1,208 @@
-use crate::kafka::client::{create_base_client, Config};
-use crate::kafka::types::ListBrokerEntry;
+use crate::kafka::client::{create_admin_client, create_base_client, Config};
+use crate::kafka::native;
+use crate::kafka::protocol::{BrokerConnection, Decoder, Encoder, ProtocolError, DESCRIBE_LOG_DIRS};
+use crate::kafka::types::{DescribeBrokerEntry, DescribeClusterEntry, DescribeConfigEntry, ListBrokerEntry};
+use rdkafka::admin::{AdminOptions, ConfigSource, ResourceSpecifier};
+use rdkafka::bindings as rdsys;
 use rdkafka::consumer::Consumer;
+use rdkafka::error::KafkaError;
+use std::collections::HashMap;
+use std::thread;
 
-pub fn list_brokers<'a>(config: &Config) -> Vec<ListBrokerEntry> {
-    let result = create_base_client(config)
-        .fetch_metadata(None, config.timeout);
-
-    result.expect("Failed to fetch metadata")
-        .brokers()
-        .iter()
-        .map(|broker|
-            ListBrokerEntry {
-                id: broker.id(),
-                host: broker.host().to_string(),
-                port: broker.port(),
+/// Lists the brokers with their share of the partitions and the size of their log dirs.
+///
+/// Racks come from DescribeCluster and log dir sizes from DescribeLogDirs, both are left empty
+/// when the cluster does not support or allow them. Brokers are asked for their log dirs in parallel,
+/// so an unreachable one only costs its connect timeout.
+pub fn list_brokers(config: &Config) -> Result<Vec<ListBrokerEntry>, KafkaError> {
+    let mut brokers = broker_entries(config)?;
+    thread::scope(|scope| {
+        for broker in brokers.iter_mut() {
+            scope.spawn(move || {
+                broker.log_dir_size = log_dir_size(config, &broker.host, broker.port as u16)
+                    .inspect_err(|e| tracing::debug!("Failed to describe log dirs of broker {}: {}", broker.id, e))
+                    .ok();
+            });
+        }
+    });
+    Ok(brokers)
+}
+
+// Everything but the log dir sizes, which are asked to each broker.
+fn broker_entries(config: &Config) -> Result<Vec<ListBrokerEntry>, KafkaError> {
+    let client = create_base_client(config);
+    let metadata = client.fetch_metadata(None, config.timeout)?;
+    let controller_id = unsafe {
+        rdsys::rd_kafka_controllerid(client.client().native_ptr(), config.timeout.as_millis() as i32)
+    };
+    let racks = describe_cluster(config)
+        .map(|cluster| cluster.brokers.into_iter().map(|b| (b.id, b.rack)).collect::<HashMap<_, _>>())
+        .unwrap_or_else(|e| {
+            tracing::debug!("Failed to describe cluster, racks are unknown: {}", e);
+            HashMap::new()
+        });
+
+    let mut leaders = HashMap::new();
+    let mut replicas = HashMap::new();
+    for partition in metadata.topics().iter().flat_map(|t| t.partitions()) {
+        *leaders.entry(partition.leader()).or_insert(0) += 1;
+        for replica in partition.replicas() {
+            *replicas.entry(*replica).or_insert(0) += 1;
+        }
+    }
+
+    let mut brokers = metadata.brokers().iter()
+        .map(|broker| ListBrokerEntry {
+            id: broker.id(),
+            host: broker.host().to_string(),
+            port: broker.port(),
+            rack: racks.get(&broker.id()).cloned().flatten(),
+            controller: broker.id() == controller_id,
+            leaders: leaders.get(&broker.id()).copied().unwrap_or(0),
+            replicas: replicas.get(&broker.id()).copied().unwrap_or(0),
+            log_dir_size: None,
+        })
+        .collect::<Vec<_>>();
+    brokers.sort_by_key(|b| b.id);
+    Ok(brokers)
+}
+
+// Log dirs are described by the broker owning them.
+fn log_dir_size(config: &Config, host: &str, port: u16) -> Result<i64, ProtocolError> {
+    let mut connection = BrokerConnection::open(config, host, port)?;
+    let mut body = Encoder::new();
+    // every topic
+    body.array_len(None);
+    let response = connection.request(DESCRIBE_LOG_DIRS, 1, false, body)?;
+    parse_log_dir_size(&response)
+}
+
+/// Sums the partition sizes of a DescribeLogDirs v1 response, offline dirs have no partitions.
+fn parse_log_dir_size(response: &[u8]) -> Result<i64, ProtocolError> {
+    let mut decoder = Decoder::new(response);
+    let _throttle_time_ms = decoder.i32()?;
+    let mut size = 0;
+    for _ in 0..decoder.array_len()? {
+        let _error_code = decoder.i16()?;
+        let _log_dir = decoder.string()?;
+        for _ in 0..decoder.array_len()? {
+            let _topic = decoder.string()?;
+            for _ in 0..decoder.array_len()? {
+                let _partition = decoder.i32()?;
+                size += decoder.i64()?;
+                let _offset_lag = decoder.i64()?;
+                let _is_future = decoder.bool()?;
             }
-        )
-        .collect::<Vec<_>>()
+        }
+    }
+    Ok(size)
+}
+
+pub fn describe_cluster(config: &Config) -> Result<DescribeClusterEntry, KafkaError> {
+    native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_DESCRIBECLUSTER,
+        |rk, options, queue| unsafe { rdsys::rd_kafka_DescribeCluster(rk, options, queue) },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_DescribeCluster_result(event);
+            let cluster_id = native::to_string(rdsys::rd_kafka_DescribeCluster_result_cluster_id(result));
+            let controller_id = native::node(rdsys::rd_kafka_DescribeCluster_result_controller(result))
+                .map(|node| node.id);
+
+            let mut count = 0;
+            let nodes = rdsys::rd_kafka_DescribeCluster_result_nodes(result, &mut count);
+            let mut brokers = native::as_slice(nodes, count)
+                .iter()
+                .filter_map(|node| native::node(*node))
+                .map(|node| DescribeBrokerEntry {
+                    controller: controller_id == Some(node.id),
+                    id: node.id,
+                    host: node.host,
+                    port: node.port,
+                    rack: node.rack,
+                })
+                .collect::<Vec<_>>();
+            brokers.sort_by_key(|b| b.id);
+
+            Ok(DescribeClusterEntry {
+                cluster_id,
+                controller_id,
+                brokers,
+            })
+        },
+    )
 }
 
+pub async fn describe_broker_configs(config: &Config, broker_id: i32) -> Result<Vec<DescribeConfigEntry>, KafkaError> {
+    describe_configs(config, ResourceSpecifier::Broker(broker_id)).await
+}
+
+/// The config entries of the resource, sorted by name.
+pub(crate) async fn describe_configs(config: &Config, resource: ResourceSpecifier<'_>) -> Result<Vec<DescribeConfigEntry>, KafkaError> {
+    let result = create_admin_client(config)
+        .describe_configs(&[resource], &AdminOptions::new().request_timeout(Some(config.timeout)))
+        .await?;
+
+    let resource = result.into_iter()
+        .next()
+        .ok_or(KafkaError::AdminOpCreation("no config resource returned".to_string()))?
+        .map_err(KafkaError::AdminOp)?;
+
+    let mut entries = resource.entries
+        .into_iter()
+        .map(|entry| DescribeConfigEntry {
+            name: entry.name,
+            value: entry.value,
+            source: config_source_name(&entry.source).to_string(),
+            is_default: entry.is_default,
+            is_read_only: entry.is_read_only,
+            is_sensitive: entry.is_sensitive,
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+pub(crate) fn config_source_name(source: &ConfigSource) -> &'static str {
+    match source {
+        ConfigSource::Unknown => "unknown",
+        ConfigSource::DynamicTopic => "dynamic-topic",
+        ConfigSource::DynamicBroker => "dynamic-broker",
+        ConfigSource::DynamicDefaultBroker => "dynamic-default-broker",
+        ConfigSource::StaticBroker => "static-broker",
+        ConfigSource::Default => "default",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_partition_sizes_over_log_dirs() {
+        let mut response = vec![];
+        response.extend(0i32.to_be_bytes()); // throttle
+        response.extend(2i32.to_be_bytes()); // log dirs
+        // an online dir with two partitions of one topic
+        response.extend(0i16.to_be_bytes());
+        response.extend(5i16.to_be_bytes());
+        response.extend(b"/data");
+        response.extend(1i32.to_be_bytes());
+        response.extend(6i16.to_be_bytes());
+        response.extend(b"orders");
+        response.extend(2i32.to_be_bytes());
+        for (partition, size) in [(0i32, 1024i64), (1, 2048)] {
+            response.extend(partition.to_be_bytes());
+            response.extend(size.to_be_bytes());
+            response.extend(0i64.to_be_bytes());
+            response.push(0);
+        }
+        // an offline dir
+        response.extend(57i16.to_be_bytes());
+        response.extend(6i16.to_be_bytes());
+        response.extend(b"/data2");
+        response.extend(0i32.to_be_bytes());
+
+        assert_eq!(parse_log_dir_size(&response).unwrap(), 3072);
+    }
+}
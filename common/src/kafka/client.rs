@@ -1,8 +1,9 @@
-use crate::kafka::iam::generate_auth_token;
+use crate::kafka::iam::{generate_auth_token, AuthToken, SignerError, TokenOptions};
 use aws_types::region::Region;
 use rdkafka::admin::AdminClient;
 use rdkafka::client::OAuthToken;
 use rdkafka::consumer::{BaseConsumer, ConsumerContext};
+use rdkafka::error::KafkaError;
 use rdkafka::{ClientConfig, ClientContext};
 use std::error::Error;
 use std::thread;
@@ -10,19 +11,20 @@ use std::time::Duration;
 use tokio::runtime::Handle;
 use tokio::time::timeout;
 
-pub fn create_config(bootstrap_servers: String, iam_auth: bool, region: String, timeout: Duration) -> Config {
+pub fn create_config(bootstrap_servers: String, iam_auth: bool, region: String, token_options: TokenOptions, timeout: Duration) -> Config {
     let aws_region = Region::new(region);
     let mut config = ClientConfig::new();
     config.set("bootstrap.servers", bootstrap_servers);
     if iam_auth {
-        println!("Using iam authentication");
+        tracing::info!("Using iam authentication");
         config.set("security.protocol", "sasl_ssl");
         config.set("sasl.mechanisms", "OAUTHBEARER");
     }
     Config {
         client_config: config,
-        context: IamClientContext::new(aws_region, Handle::current()),
+        context: IamClientContext::new(aws_region, token_options, Handle::current()),
         timeout,
+        read_only: false,
     }
 }
 
@@ -33,6 +35,21 @@ pub fn create_base_client(config: &Config) -> BaseConsumer<IamClientContext> {
         .expect("Consumer creation failed")
 }
 
+/// A consumer for reading assigned partitions, it never joins a group nor commits offsets.
+///
+/// With `partition_eof`, reaching the end of a partition is reported as an error when polling.
+pub fn create_reader(config: &Config, partition_eof: bool) -> BaseConsumer<IamClientContext> {
+    let mut client_config = config.client_config.clone();
+    client_config
+        // librdkafka only assigns partitions to consumers with a group, assigning does not join it.
+        .set("group.id", "kafka-utils-reader")
+        .set("enable.auto.commit", "false")
+        .set("enable.partition.eof", partition_eof.to_string());
+    client_config
+        .create_with_context(config.context.clone())
+        .expect("Consumer creation failed")
+}
+
 pub fn create_admin_client(config: &Config) -> AdminClient<IamClientContext> {
     config
         .client_config
@@ -43,31 +60,37 @@ pub fn create_admin_client(config: &Config) -> AdminClient<IamClientContext> {
 #[derive(Clone)]
 pub struct IamClientContext {
     region: Region,
+    token_options: TokenOptions,
     rt: Handle,
 }
 
 impl IamClientContext {
-    pub fn new(region: Region, rt: Handle) -> Self {
-        Self { region, rt }
+    pub fn new(region: Region, token_options: TokenOptions, rt: Handle) -> Self {
+        Self { region, token_options, rt }
+    }
+
+    /// Generates an auth token from blocking code, on its own thread as the caller may block a runtime worker.
+    fn auth_token(&self) -> Result<AuthToken, SignerError> {
+        let region = self.region.clone();
+        let token_options = self.token_options.clone();
+        let rt = self.rt.clone();
+        let handle = thread::spawn(move || {
+            rt.block_on(async {
+                timeout(Duration::from_secs(10), generate_auth_token(region.clone(), &token_options)).await
+            })
+        });
+        handle.join().unwrap()
+            .map_err(|_| SignerError::ConstructAuthToken("timed out generating the auth token".to_string()))?
     }
 }
 impl ClientContext for IamClientContext {
     const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
     fn generate_oauth_token(&self, _oauthbearer_config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
-        let region = self.region.clone();
-        let rt = self.rt.clone();
-        let (token, expiration_time_ms) = {
-            let handle = thread::spawn(move || {
-                rt.block_on(async {
-                    timeout(Duration::from_secs(10), generate_auth_token(region.clone())).await
-                })
-            });
-            handle.join().unwrap()??
-        };
+        let auth_token = self.auth_token()?;
         Ok(OAuthToken {
-            token,
-            principal_name: "".to_string(),
-            lifetime_ms: expiration_time_ms,
+            token: auth_token.token,
+            principal_name: auth_token.principal_name,
+            lifetime_ms: auth_token.expiration_time_ms,
         })
     }
 }
@@ -79,4 +102,81 @@ pub struct Config {
     client_config: ClientConfig,
     context: IamClientContext,
     pub(crate) timeout: Duration,
-}
\ No newline at end of file
+    read_only: bool,
+}
+
+/// An operation changing the cluster, refused because the connection is read-only.
+#[derive(thiserror::Error, Debug)]
+#[error("refused to {operation}, the connection is read-only")]
+pub struct ReadOnlyError {
+    pub operation: String,
+}
+
+/// The error of an operation changing the cluster, refused before reaching it or failed on it.
+#[derive(thiserror::Error, Debug)]
+pub enum WriteError {
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+}
+
+impl Config {
+    /// Makes every operation changing the cluster fail before reaching it, reading is left as is.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Refuses the operation changing the cluster when read-only, `operation` as in `delete topics`.
+    pub fn check_writable(&self, operation: &str) -> Result<(), ReadOnlyError> {
+        if self.read_only {
+            return Err(ReadOnlyError { operation: operation.to_string() });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn bootstrap_servers(&self) -> String {
+        self.client_config
+            .get("bootstrap.servers")
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    pub(crate) fn iam_auth(&self) -> bool {
+        self.client_config.get("sasl.mechanisms") == Some("OAUTHBEARER")
+    }
+
+    pub(crate) fn region(&self) -> Region {
+        self.context.region.clone()
+    }
+
+    pub(crate) fn token_options(&self) -> TokenOptions {
+        self.context.token_options.clone()
+    }
+
+    /// An IAM auth token, for connections made outside of librdkafka.
+    pub(crate) fn auth_token(&self) -> Result<AuthToken, SignerError> {
+        self.context.auth_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::topic;
+
+    #[tokio::test]
+    async fn read_only_refuses_writes_before_connecting() {
+        // nothing listens there, the write must fail without trying to connect.
+        let config = create_config("127.0.0.1:1".to_string(), false, "eu-west-1".to_string(), TokenOptions::default(), Duration::from_secs(30))
+            .read_only(true);
+        let err = topic::delete_topics(&config, vec!["orders".to_string()]).await.unwrap_err();
+        assert!(matches!(err, WriteError::ReadOnly(_)));
+        assert_eq!(err.to_string(), "refused to delete topics, the connection is read-only");
+    }
+}
@@ -0,0 +1,108 @@
+use crate::kafka::client::{create_base_client, Config, ReadOnlyError};
+use crate::kafka::protocol::{self, BrokerConnection, Decoder, Encoder, ProtocolError};
+use crate::kafka::types::{ElectLeaderEntry, ElectionStatus};
+use rdkafka::consumer::Consumer;
+use rdkafka::error::KafkaError;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+const ELECTION_NOT_NEEDED: i16 = 84;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElectionType {
+    /// Moves leadership back to the first replica, when it is in sync.
+    Preferred,
+    /// Elects an out of sync replica when no in sync replica is available, possibly losing data.
+    Unclean,
+}
+
+impl ElectionType {
+    fn code(self) -> i8 {
+        match self {
+            ElectionType::Preferred => 0,
+            ElectionType::Unclean => 1,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ElectionError {
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error("unknown topic {0}")]
+    UnknownTopic(String),
+}
+
+/// Triggers a leader election for every partition of the cluster when `topics` is empty, otherwise for the
+/// listed partitions of each topic, or all of a topic's partitions when none are listed.
+pub fn elect_leaders(
+    config: &Config,
+    election_type: ElectionType,
+    topics: &BTreeMap<String, Vec<i32>>,
+) -> Result<Vec<ElectLeaderEntry>, ElectionError> {
+    config.check_writable("elect leaders")?;
+    let topics = resolve_partitions(config, topics)?;
+
+    let mut body = Encoder::new();
+    body.i8(election_type.code());
+    body.array_len((!topics.is_empty()).then_some(topics.len()));
+    for (topic, partitions) in &topics {
+        body.string(topic);
+        body.array_len(Some(partitions.len()));
+        partitions.iter().for_each(|p| body.i32(*p));
+    }
+    body.i32(config.timeout.as_millis() as i32);
+
+    let mut connection = BrokerConnection::controller(config)?;
+    let response = connection.request(protocol::ELECT_LEADERS, 1, false, body)?;
+    let mut decoder = Decoder::new(&response);
+    decoder.i32()?;
+    protocol::check_error(decoder.i16()?, None)?;
+
+    let mut results = Vec::new();
+    for _ in 0..decoder.array_len()? {
+        let topic = decoder.string()?;
+        for _ in 0..decoder.array_len()? {
+            let partition = decoder.i32()?;
+            let error_code = decoder.i16()?;
+            let error_message = decoder.nullable_string()?;
+            let (status, error) = match error_code {
+                0 => (ElectionStatus::Elected, None),
+                ELECTION_NOT_NEEDED => (ElectionStatus::NotNeeded, None),
+                code => (ElectionStatus::Failed, Some(protocol::error_message(code, &error_message))),
+            };
+            results.push(ElectLeaderEntry { topic: topic.clone(), partition, status, error });
+        }
+    }
+    results.sort_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)));
+    Ok(results)
+}
+
+// The API takes explicit partitions, topics listed without any get all of theirs from the metadata.
+fn resolve_partitions(config: &Config, topics: &BTreeMap<String, Vec<i32>>) -> Result<BTreeMap<String, Vec<i32>>, ElectionError> {
+    if topics.values().all(|partitions| !partitions.is_empty()) {
+        return Ok(topics.clone());
+    }
+    let metadata = create_base_client(config)
+        .fetch_metadata(None, config.timeout)?;
+    topics.iter()
+        .map(|(topic, partitions)| {
+            if !partitions.is_empty() {
+                return Ok((topic.clone(), partitions.clone()));
+            }
+            let all = metadata.topics()
+                .iter()
+                .find(|t| t.name() == topic)
+                .ok_or_else(|| ElectionError::UnknownTopic(topic.clone()))?
+                .partitions()
+                .iter()
+                .map(|p| p.id())
+                .collect();
+            Ok((topic.clone(), all))
+        })
+        .collect()
+}
@@ -1,13 +1,21 @@
-use crate::kafka::client::{create_admin_client, create_base_client, Config};
-use crate::kafka::types::ListConsumerGroupEntry;
-use rdkafka::admin::{AdminOptions, GroupResult};
-use rdkafka::consumer::Consumer;
+use crate::kafka::client::{create_admin_client, create_base_client, Config, IamClientContext, WriteError};
+use crate::kafka::native;
+use crate::kafka::protocol::{Decoder, ProtocolError};
+use crate::kafka::types::{GroupPartitionLagEntry, ListConsumerGroupEntry, PartitionResultEntry, TopicGroupLagEntry};
+use rdkafka::admin::{AdminClient, AdminOptions, GroupResult};
+use rdkafka::bindings as rdsys;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::KafkaError;
 use rdkafka::groups::GroupInfo;
+use rdkafka::types::{RDKafkaErrorCode, RDKafkaRespErr};
+use rdkafka::TopicPartitionList;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::CString;
+use std::time::Duration;
 
-pub fn list(config: &Config, consumer_group: Option<String>) -> Vec<ListConsumerGroupEntry> {
+pub fn list(config: &Config, consumer_group: Option<String>) -> Result<Vec<ListConsumerGroupEntry>, KafkaError> {
     let result = create_base_client(config)
-        .fetch_group_list(None, config.timeout)
-        .expect("could not fetch group list");
+        .fetch_group_list(None, config.timeout)?;
 
     let mut groups: Vec<ListConsumerGroupEntry> = result.groups()
         .iter()
@@ -16,10 +24,11 @@ pub fn list(config: &Config, consumer_group: Option<String>) -> Vec<ListConsumer
             ListConsumerGroupEntry {
                 name: g.name().to_string(),
                 state: g.state().to_string(),
+                members: g.members().len(),
             }
         ).collect();
     groups.sort_by_key(|i| i.name.clone());
-    groups
+    Ok(groups)
 }
 
 fn filter_group(group: &GroupInfo, group_query: Option<String>) -> bool {
@@ -29,10 +38,9 @@ fn filter_group(group: &GroupInfo, group_query: Option<String>) -> bool {
     }
 }
 
-pub async fn delete(config: &Config, consumer_group: Option<String>) -> Vec<GroupResult> {
+pub async fn delete(config: &Config, consumer_group: Option<String>) -> Result<Vec<GroupResult>, WriteError> {
     let result = create_base_client(config)
-        .fetch_group_list(None, config.timeout)
-        .expect("could not fetch group list");
+        .fetch_group_list(None, config.timeout)?;
 
     let mut groups = result.groups()
         .iter()
@@ -40,11 +48,276 @@ pub async fn delete(config: &Config, consumer_group: Option<String>) -> Vec<Grou
         .collect::<Vec<_>>();
     groups.sort_by_key(|i| i.name());
 
-    let groups_to_delete: Vec<&str>  = groups.iter().map(|g| g.name())
+    let groups_to_delete: Vec<String> = groups.iter().map(|g| g.name().to_string())
         .collect();
 
-    create_admin_client(&config)
-        .delete_groups(&groups_to_delete, &AdminOptions::new())
-        .await
-        .expect("could not delete groups")
-}
\ No newline at end of file
+    delete_groups(config, &groups_to_delete).await
+}
+
+pub async fn delete_groups(config: &Config, groups: &[String]) -> Result<Vec<GroupResult>, WriteError> {
+    config.check_writable("delete consumer groups")?;
+    let groups = groups.iter().map(|g| g.as_str()).collect::<Vec<_>>();
+    Ok(create_admin_client(config)
+        .delete_groups(&groups, &AdminOptions::new())
+        .await?)
+}
+
+/// The lag of every partition the group committed an offset for or is assigned, with the member owning it.
+pub fn describe_lag(config: &Config, group: &str) -> Result<Vec<GroupPartitionLagEntry>, KafkaError> {
+    let client = create_base_client(config);
+    let group_list = client.fetch_group_list(Some(group), config.timeout)?;
+
+    let mut owners = HashMap::new();
+    for info in group_list.groups().iter().filter(|g| g.protocol_type() == "consumer") {
+        for member in info.members() {
+            // a member still joining has no assignment yet, nor does an unreadable one.
+            let assignment = member.assignment().and_then(|a| parse_assignment(a).ok()).unwrap_or_default();
+            for partition in assignment {
+                owners.insert(partition, member);
+            }
+        }
+    }
+    let committed = committed_offsets(&create_admin_client(config), config, group)?;
+
+    let partitions = committed.keys().chain(owners.keys()).cloned().collect::<BTreeSet<_>>();
+    Ok(partitions.into_iter()
+        .map(|(topic, partition)| {
+            let high_watermark = client.fetch_watermarks(&topic, partition, config.timeout)
+                .ok()
+                .map(|(_, high)| high);
+            let committed = committed.get(&(topic.clone(), partition)).copied();
+            let owner = owners.get(&(topic.clone(), partition));
+            GroupPartitionLagEntry {
+                lag: committed.zip(high_watermark).map(|(committed, high)| (high - committed).max(0)),
+                client_id: owner.map(|m| m.client_id().to_string()),
+                host: owner.map(|m| m.client_host().to_string()),
+                topic,
+                partition,
+                committed,
+                high_watermark,
+            }
+        })
+        .collect())
+}
+
+/// The lag of each group summed over the partitions it committed offsets for, in the order of `groups`.
+///
+/// The groups share one admin client, and the watermarks of a partition are fetched once for all of them.
+/// A group whose offsets can't be read gets its own error.
+pub fn total_lag(config: &Config, groups: &[String]) -> Vec<Result<i64, KafkaError>> {
+    let admin = create_admin_client(config);
+    let mut watermarks = HighWatermarks::new(config);
+    groups.iter()
+        .map(|group| {
+            let committed = committed_offsets(&admin, config, group)?;
+            Ok(committed.iter()
+                .filter_map(|((topic, partition), offset)| watermarks.get(topic, *partition).map(|high| (high - offset).max(0)))
+                .sum())
+        })
+        .collect()
+}
+
+// High watermarks fetched at most once per partition, `None` for partitions they can't be fetched for.
+struct HighWatermarks {
+    client: BaseConsumer<IamClientContext>,
+    timeout: Duration,
+    fetched: HashMap<(String, i32), Option<i64>>,
+}
+
+impl HighWatermarks {
+    fn new(config: &Config) -> Self {
+        Self { client: create_base_client(config), timeout: config.timeout, fetched: HashMap::new() }
+    }
+
+    fn get(&mut self, topic: &str, partition: i32) -> Option<i64> {
+        let key = (topic.to_string(), partition);
+        if let Some(high) = self.fetched.get(&key) {
+            return *high;
+        }
+        let high = self.client.fetch_watermarks(topic, partition, self.timeout).ok().map(|(_, high)| high);
+        self.fetched.insert(key, high);
+        high
+    }
+}
+
+/// The consumer groups with offsets committed on the topic, with their lag on it.
+///
+/// Groups whose offsets can't be read are left out rather than failing the others.
+pub fn topic_lag(config: &Config, topic: &str) -> Result<Vec<TopicGroupLagEntry>, KafkaError> {
+    let client = create_base_client(config);
+    let metadata = client.fetch_metadata(Some(topic), config.timeout)?;
+    let high_watermarks = metadata.topics()
+        .iter()
+        .filter(|t| t.name() == topic)
+        .flat_map(|t| t.partitions())
+        .filter_map(|p| client.fetch_watermarks(topic, p.id(), config.timeout).ok().map(|(_, high)| (p.id(), high)))
+        .collect::<HashMap<_, _>>();
+
+    let group_list = client.fetch_group_list(None, config.timeout)?;
+    let admin = create_admin_client(config);
+    let mut groups = vec![];
+    for group in group_list.groups().iter().filter(|g| g.protocol_type() == "consumer") {
+        let committed = match committed_offsets(&admin, config, group.name()) {
+            Ok(committed) => committed,
+            Err(err) => {
+                tracing::debug!("Failed to fetch the committed offsets of group {}: {}", group.name(), err);
+                continue;
+            }
+        };
+        let committed = committed
+            .into_iter()
+            .filter(|((t, _), _)| t == topic)
+            .map(|((_, partition), offset)| (partition, offset))
+            .collect::<Vec<_>>();
+        if committed.is_empty() {
+            continue;
+        }
+        groups.push(TopicGroupLagEntry {
+            group: group.name().to_string(),
+            state: group.state().to_string(),
+            members: group.members().len(),
+            partitions: committed.len(),
+            lag: committed.iter()
+                .filter_map(|(partition, offset)| high_watermarks.get(partition).map(|high| (high - offset).max(0)))
+                .sum(),
+        });
+    }
+    groups.sort_by(|a, b| a.group.cmp(&b.group));
+    Ok(groups)
+}
+
+/// Reads the partitions of a consumer protocol member assignment.
+fn parse_assignment(assignment: &[u8]) -> Result<Vec<(String, i32)>, ProtocolError> {
+    let mut decoder = Decoder::new(assignment);
+    let _version = decoder.i16()?;
+    let mut partitions = vec![];
+    for _ in 0..decoder.array_len()? {
+        let topic = decoder.string()?;
+        for _ in 0..decoder.array_len()? {
+            partitions.push((topic.clone(), decoder.i32()?));
+        }
+    }
+    Ok(partitions)
+}
+
+// The offsets the group committed, on every partition.
+fn committed_offsets(
+    client: &AdminClient<IamClientContext>,
+    config: &Config,
+    group: &str,
+) -> Result<HashMap<(String, i32), i64>, KafkaError> {
+    let group = CString::new(group).map_err(|e| KafkaError::AdminOpCreation(e.to_string()))?;
+    native::admin_request_on(
+        client,
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_LISTCONSUMERGROUPOFFSETS,
+        |rk, options, queue| unsafe {
+            let mut request = [rdsys::rd_kafka_ListConsumerGroupOffsets_new(group.as_ptr(), std::ptr::null())];
+            rdsys::rd_kafka_ListConsumerGroupOffsets(rk, request.as_mut_ptr(), request.len(), options, queue);
+            rdsys::rd_kafka_ListConsumerGroupOffsets_destroy(request[0]);
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_ListConsumerGroupOffsets_result(event);
+            let mut count = 0;
+            let groups = rdsys::rd_kafka_ListConsumerGroupOffsets_result_groups(result, &mut count);
+            let mut offsets = HashMap::new();
+            for group in native::as_slice(groups, count) {
+                let error = rdsys::rd_kafka_group_result_error(*group);
+                if !error.is_null() {
+                    return Err(KafkaError::AdminOp(rdsys::rd_kafka_error_code(error).into()));
+                }
+                let list = rdsys::rd_kafka_group_result_partitions(*group);
+                if list.is_null() {
+                    continue;
+                }
+                for partition in native::as_slice((*list).elems, (*list).cnt as usize) {
+                    // partitions without a committed offset are listed with an invalid one.
+                    if partition.err == RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR && partition.offset >= 0 {
+                        let topic = native::to_string(partition.topic).unwrap_or_default();
+                        offsets.insert((topic, partition.partition), partition.offset);
+                    }
+                }
+            }
+            Ok(offsets)
+        },
+    )
+}
+
+/// Where to move the committed offsets of a group to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetResetTarget {
+    /// The low watermark, the group reads the partitions again.
+    Earliest,
+    /// The high watermark, the group skips its lag.
+    Latest,
+}
+
+/// Commits the target offset of each partition for the group, which must have no active member.
+pub fn reset_offsets(config: &Config, group: &str, partitions: &[(String, i32)], target: OffsetResetTarget) -> Result<Vec<PartitionResultEntry>, WriteError> {
+    config.check_writable("reset offsets")?;
+    let client = create_base_client(config);
+    let mut offsets = TopicPartitionList::new();
+    for (topic, partition) in partitions {
+        let (low, high) = client.fetch_watermarks(topic, *partition, config.timeout)?;
+        let offset = match target {
+            OffsetResetTarget::Earliest => low,
+            OffsetResetTarget::Latest => high,
+        };
+        offsets.add_partition_offset(topic, *partition, rdkafka::Offset::Offset(offset))?;
+    }
+    let group = CString::new(group).map_err(|e| KafkaError::AdminOpCreation(e.to_string()))?;
+    native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_ALTERCONSUMERGROUPOFFSETS,
+        |rk, options, queue| unsafe {
+            let mut request = [rdsys::rd_kafka_AlterConsumerGroupOffsets_new(group.as_ptr(), offsets.ptr())];
+            rdsys::rd_kafka_AlterConsumerGroupOffsets(rk, request.as_mut_ptr(), request.len(), options, queue);
+            rdsys::rd_kafka_AlterConsumerGroupOffsets_destroy(request[0]);
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_AlterConsumerGroupOffsets_result(event);
+            let mut count = 0;
+            let groups = rdsys::rd_kafka_AlterConsumerGroupOffsets_result_groups(result, &mut count);
+            let mut results = vec![];
+            for group in native::as_slice(groups, count) {
+                let error = rdsys::rd_kafka_group_result_error(*group);
+                if !error.is_null() {
+                    return Err(KafkaError::AdminOp(rdsys::rd_kafka_error_code(error).into()));
+                }
+                let list = rdsys::rd_kafka_group_result_partitions(*group);
+                if list.is_null() {
+                    continue;
+                }
+                for partition in native::as_slice((*list).elems, (*list).cnt as usize) {
+                    results.push(PartitionResultEntry {
+                        topic: native::to_string(partition.topic).unwrap_or_default(),
+                        partition: partition.partition,
+                        error: (partition.err != RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR)
+                            .then(|| RDKafkaErrorCode::from(partition.err).to_string()),
+                    });
+                }
+            }
+            Ok(results)
+        },
+    ).map_err(WriteError::Kafka)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_member_assignment() {
+        let assignment = [
+            0, 1, // version
+            0, 0, 0, 2, // topics
+            0, 6, b'o', b'r', b'd', b'e', b'r', b's', 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3,
+            0, 5, b'u', b's', b'e', b'r', b's', 0, 0, 0, 1, 0, 0, 0, 1,
+            255, 255, 255, 255, // null user data
+        ];
+        assert_eq!(
+            parse_assignment(&assignment).unwrap(),
+            vec![("orders".to_string(), 0), ("orders".to_string(), 3), ("users".to_string(), 1)],
+        );
+    }
+}
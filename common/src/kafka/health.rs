@@ -0,0 +1,258 @@
+use crate::kafka::client::{create_admin_client, create_base_client, Config};
+use crate::kafka::types::{HealthIssueEntry, HealthIssueKind, HealthReportEntry};
+use rdkafka::admin::{AdminOptions, ResourceSpecifier};
+use rdkafka::consumer::Consumer;
+use rdkafka::error::KafkaError;
+use rdkafka::metadata::Metadata;
+use std::collections::{BTreeMap, HashMap};
+
+const MIN_INSYNC_REPLICAS: &str = "min.insync.replicas";
+
+/// Placement of a single partition as reported by the cluster metadata.
+#[derive(Debug, Clone)]
+pub(crate) struct PartitionPlacement {
+    pub(crate) topic: String,
+    pub(crate) partition: i32,
+    pub(crate) leader: i32,
+    pub(crate) replicas: Vec<i32>,
+    pub(crate) isr: Vec<i32>,
+}
+
+/// Computes the cluster health from the metadata and the topics' `min.insync.replicas`.
+///
+/// The metadata is fetched on the blocking pool, only describing the configs is awaited.
+pub async fn check_health(config: &Config) -> Result<HealthReportEntry, KafkaError> {
+    let metadata_config = config.clone();
+    let (brokers, partitions, topics) = tokio::task::spawn_blocking(move || {
+        let metadata = create_base_client(&metadata_config)
+            .fetch_metadata(None, metadata_config.timeout)?;
+        let brokers = metadata.brokers().iter().map(|b| b.id()).collect::<Vec<_>>();
+        let topics = metadata.topics().iter().map(|t| t.name().to_string()).collect::<Vec<_>>();
+        Ok::<_, KafkaError>((brokers, partition_placements(&metadata), topics))
+    })
+        .await
+        .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))?;
+
+    let topics = topics.iter().map(String::as_str).collect::<Vec<_>>();
+    let min_insync_replicas = fetch_min_insync_replicas(config, &topics).await;
+
+    Ok(evaluate(&brokers, &partitions, &min_insync_replicas))
+}
+
+pub(crate) fn partition_placements(metadata: &Metadata) -> Vec<PartitionPlacement> {
+    metadata.topics()
+        .iter()
+        .flat_map(|topic| topic.partitions().iter().map(|partition| PartitionPlacement {
+            topic: topic.name().to_string(),
+            partition: partition.id(),
+            leader: partition.leader(),
+            replicas: partition.replicas().to_vec(),
+            isr: partition.isr().to_vec(),
+        }))
+        .collect()
+}
+
+// Topics whose config can't be described are left out, their partitions are not checked against the minimum.
+async fn fetch_min_insync_replicas(config: &Config, topics: &[&str]) -> HashMap<String, usize> {
+    if topics.is_empty() {
+        return HashMap::new();
+    }
+    let resources = topics.iter()
+        .map(|t| ResourceSpecifier::Topic(t))
+        .collect::<Vec<_>>();
+    let results = match create_admin_client(config)
+        .describe_configs(&resources, &AdminOptions::new().request_timeout(Some(config.timeout)))
+        .await
+    {
+        Ok(results) => results,
+        Err(_) => return HashMap::new(),
+    };
+
+    results.into_iter()
+        .flatten()
+        .filter_map(|resource| {
+            let topic = match &resource.specifier {
+                rdkafka::admin::OwnedResourceSpecifier::Topic(topic) => topic.clone(),
+                _ => return None,
+            };
+            let min_isr = resource.get(MIN_INSYNC_REPLICAS)?
+                .value
+                .as_ref()?
+                .parse::<usize>()
+                .ok()?;
+            Some((topic, min_isr))
+        })
+        .collect()
+}
+
+pub(crate) fn evaluate(
+    brokers: &[i32],
+    partitions: &[PartitionPlacement],
+    min_insync_replicas: &HashMap<String, usize>,
+) -> HealthReportEntry {
+    let mut issues = Vec::new();
+
+    for p in partitions {
+        if p.leader < 0 || !brokers.contains(&p.leader) {
+            issues.push(partition_issue(HealthIssueKind::Offline, p, "no leader".to_string()));
+        }
+        if p.isr.len() < p.replicas.len() {
+            issues.push(partition_issue(
+                HealthIssueKind::UnderReplicated,
+                p,
+                format!("isr {:?} of replicas {:?}", p.isr, p.replicas),
+            ));
+        }
+        if let Some(min_isr) = min_insync_replicas.get(&p.topic) {
+            if p.isr.len() < *min_isr {
+                issues.push(partition_issue(
+                    HealthIssueKind::BelowMinIsr,
+                    p,
+                    format!("{} in sync, {MIN_INSYNC_REPLICAS} is {min_isr}", p.isr.len()),
+                ));
+            }
+        }
+        if p.leader >= 0 && p.replicas.first().is_some_and(|preferred| *preferred != p.leader) {
+            issues.push(partition_issue(
+                HealthIssueKind::NonPreferredLeader,
+                p,
+                format!("leader {}, preferred {}", p.leader, p.replicas[0]),
+            ));
+        }
+    }
+
+    if !partitions.is_empty() {
+        let mut leaders = brokers.iter().map(|b| (*b, 0)).collect::<BTreeMap<_, _>>();
+        for p in partitions {
+            if let Some(count) = leaders.get_mut(&p.leader) {
+                *count += 1;
+            }
+        }
+        leaders.into_iter()
+            .filter(|(_, count)| *count == 0)
+            .for_each(|(broker, _)| issues.push(HealthIssueEntry {
+                kind: HealthIssueKind::BrokerWithoutLeadership,
+                topic: None,
+                partition: None,
+                broker: Some(broker),
+                detail: "leads no partitions".to_string(),
+            }));
+    }
+
+    let mut by_topic: BTreeMap<&str, Vec<&PartitionPlacement>> = BTreeMap::new();
+    partitions.iter().for_each(|p| by_topic.entry(p.topic.as_str()).or_default().push(p));
+    let topics = by_topic.len();
+    for (topic, topic_partitions) in by_topic {
+        if let Some((min, max)) = replica_spread(brokers, &topic_partitions) {
+            if max - min > 1 {
+                issues.push(HealthIssueEntry {
+                    kind: HealthIssueKind::SkewedTopic,
+                    topic: Some(topic.to_string()),
+                    partition: None,
+                    broker: None,
+                    detail: format!("{min} to {max} replicas per broker"),
+                });
+            }
+        }
+    }
+
+    HealthReportEntry {
+        brokers: brokers.len(),
+        topics,
+        partitions: partitions.len(),
+        issues,
+    }
+}
+
+// Lowest and highest number of replicas of the topic hosted by any broker of the cluster.
+fn replica_spread(brokers: &[i32], partitions: &[&PartitionPlacement]) -> Option<(usize, usize)> {
+    let mut replicas = brokers.iter().map(|b| (*b, 0)).collect::<HashMap<_, _>>();
+    for replica in partitions.iter().flat_map(|p| p.replicas.iter()) {
+        if let Some(count) = replicas.get_mut(replica) {
+            *count += 1;
+        }
+    }
+    let min = *replicas.values().min()?;
+    let max = *replicas.values().max()?;
+    Some((min, max))
+}
+
+fn partition_issue(kind: HealthIssueKind, partition: &PartitionPlacement, detail: String) -> HealthIssueEntry {
+    HealthIssueEntry {
+        kind,
+        topic: Some(partition.topic.clone()),
+        partition: Some(partition.partition),
+        broker: None,
+        detail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placement(topic: &str, partition: i32, leader: i32, replicas: &[i32], isr: &[i32]) -> PartitionPlacement {
+        PartitionPlacement {
+            topic: topic.to_string(),
+            partition,
+            leader,
+            replicas: replicas.to_vec(),
+            isr: isr.to_vec(),
+        }
+    }
+
+    fn kinds(report: &HealthReportEntry) -> Vec<HealthIssueKind> {
+        report.issues.iter().map(|i| i.kind.clone()).collect()
+    }
+
+    #[test]
+    fn healthy_cluster_has_no_issues() {
+        let partitions = vec![
+            placement("orders", 0, 1, &[1, 2], &[1, 2]),
+            placement("orders", 1, 2, &[2, 1], &[2, 1]),
+        ];
+
+        let report = evaluate(&[1, 2], &partitions, &HashMap::new());
+
+        assert!(report.issues.is_empty());
+        assert_eq!((report.brokers, report.topics, report.partitions), (2, 1, 2));
+    }
+
+    #[test]
+    fn reports_partition_issues() {
+        let partitions = vec![
+            placement("orders", 0, -1, &[1, 2], &[]),
+            placement("orders", 1, 2, &[1, 2], &[2]),
+        ];
+        let min_isr = HashMap::from([("orders".to_string(), 2)]);
+
+        let report = evaluate(&[1, 2], &partitions, &min_isr);
+
+        assert_eq!(kinds(&report), vec![
+            HealthIssueKind::Offline,
+            HealthIssueKind::UnderReplicated,
+            HealthIssueKind::BelowMinIsr,
+            HealthIssueKind::UnderReplicated,
+            HealthIssueKind::BelowMinIsr,
+            HealthIssueKind::NonPreferredLeader,
+            HealthIssueKind::BrokerWithoutLeadership,
+        ]);
+        assert_eq!(report.issues[6].broker, Some(1));
+    }
+
+    #[test]
+    fn reports_skewed_topics() {
+        let partitions = vec![
+            placement("orders", 0, 1, &[1], &[1]),
+            placement("orders", 1, 1, &[1], &[1]),
+            placement("orders", 2, 2, &[2], &[2]),
+        ];
+
+        let report = evaluate(&[1, 2, 3], &partitions, &HashMap::new());
+
+        assert_eq!(kinds(&report), vec![
+            HealthIssueKind::BrokerWithoutLeadership,
+            HealthIssueKind::SkewedTopic,
+        ]);
+    }
+}
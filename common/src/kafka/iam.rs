@@ -3,7 +3,10 @@ use aws_config::BehaviorVersion;
 use aws_credential_types::{provider::ProvideCredentials, Credentials};
 use aws_types::{region::Region, sdk_config::SharedCredentialsProvider};
 use base64::Engine;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::{Duration, SystemTime};
 use url::Url;
 
@@ -20,13 +23,27 @@ const LIB_NAME: &str = env!("CARGO_PKG_NAME");
 /// Tepresents the default session name for assuming a role.
 const DEFAULT_SESSION_NAME: &str = "MSKSASLDefaultSession";
 /// The default expiration time in seconds.
-const DEFAULT_EXPIRY_SECONDS: i64 = 900;
+pub const DEFAULT_EXPIRY_SECONDS: i64 = 900;
+/// The longest lifetime in seconds MSK accepts for an auth token.
+pub const MAX_EXPIRY_SECONDS: u64 = 900;
+/// The key for the expiration parameter added by the signer.
+const EXPIRES_KEY: &str = "X-Amz-Expires";
+/// The key for the signing date parameter added by the signer.
+const DATE_KEY: &str = "X-Amz-Date";
 /// The template for the Kafka endpoint URL
 macro_rules! ENDPOINT_URL_TEMPLATE {
     () => {
-        "https://kafka.{}.amazonaws.com"
+        "https://{}.{}.{}"
     };
 }
+/// The service host prefix for the standard endpoint.
+const ENDPOINT_PREFIX: &str = "kafka";
+/// The service host prefix for the FIPS endpoint.
+const FIPS_ENDPOINT_PREFIX: &str = "kafka-fips";
+/// The DNS suffix of the `aws` partition.
+const DNS_SUFFIX: &str = "amazonaws.com";
+/// The DNS suffix of the `aws-cn` partition.
+const CN_DNS_SUFFIX: &str = "amazonaws.com.cn";
 /// Indicates whether credentials should be debugged
 #[cfg(debug_assertions)]
 const AWS_DEBUG_CREDS: bool = false;
@@ -35,22 +52,86 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 type SignerResult<T> = Result<T, SignerError>;
 
+/// Principals already resolved, by access key id, so tokens are refreshed without calling STS.
+static PRINCIPALS: LazyLock<PrincipalCache> = LazyLock::new(PrincipalCache::default);
+
+/// Endpoint the auth token is signed for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TokenEndpoint {
+    /// `kafka.<region>.amazonaws.com`, or `.amazonaws.com.cn` for China regions.
+    #[default]
+    Regional,
+    /// `kafka-fips.<region>.amazonaws.com`.
+    Fips,
+    /// A fully qualified endpoint url, e.g. a VPC endpoint.
+    Custom(String),
+}
+
+/// Options used when signing an auth token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenOptions {
+    pub expiry: Duration,
+    pub endpoint: TokenEndpoint,
+}
+
+impl TokenOptions {
+    /// Builds the options from command line style arguments, a custom endpoint takes precedence over `fips`.
+    pub fn new(expiry_seconds: u64, fips: bool, endpoint: Option<String>) -> Self {
+        let endpoint = match endpoint {
+            Some(url) => TokenEndpoint::Custom(url),
+            None if fips => TokenEndpoint::Fips,
+            None => TokenEndpoint::Regional,
+        };
+        Self {
+            expiry: Duration::from_secs(expiry_seconds),
+            endpoint,
+        }
+    }
+}
+
+impl Default for TokenOptions {
+    fn default() -> Self {
+        Self {
+            expiry: Duration::from_secs(DEFAULT_EXPIRY_SECONDS as u64),
+            endpoint: TokenEndpoint::default(),
+        }
+    }
+}
+
+/// Identity behind a set of resolved credentials.
+#[derive(Debug, Clone)]
+pub struct CallerIdentity {
+    pub access_key_id: String,
+    pub temporary: bool,
+    pub expiry: Option<DateTime<Utc>>,
+    pub arn: String,
+}
+
+/// A signed auth token along with the principal it was generated for.
+#[derive(Debug, Clone)]
+pub struct AuthToken {
+    pub token: String,
+    pub expiration_time_ms: i64,
+    pub principal_name: String,
+}
+
 /// [`generate_auth_token`] generates base64 encoded signed url as auth token from default credentials.
 /// Loads the IAM credentials from default credentials provider chain.
-pub async fn generate_auth_token(region: Region) -> SignerResult<(String, i64)> {
+pub async fn generate_auth_token(region: Region, options: &TokenOptions) -> SignerResult<AuthToken> {
     let credentials = load_default_credentials(region.clone()).await?;
 
-    construct_auth_token(region, credentials).await
+    construct_auth_token(region, credentials, options).await
 }
 
 /// [`generate_auth_token_from_profile`] generates base64 encoded signed url as auth token by loading IAM credentials from an AWS named profile.
 pub async fn generate_auth_token_from_profile(
     region: Region,
     aws_profile: String,
-) -> SignerResult<(String, i64)> {
+    options: &TokenOptions,
+) -> SignerResult<AuthToken> {
     let credentials = load_credentials_from_profile(region.clone(), aws_profile).await?;
 
-    construct_auth_token(region, credentials).await
+    construct_auth_token(region, credentials, options).await
 }
 
 /// [`generate_auth_token_from_role`] generates base64 encoded signed url as auth token by loading IAM credentials from an aws role Arn
@@ -58,14 +139,15 @@ pub async fn generate_auth_token_from_role(
     region: Region,
     role_arn: String,
     mut sts_session_name: String,
-) -> SignerResult<(String, i64)> {
+    options: &TokenOptions,
+) -> SignerResult<AuthToken> {
     if sts_session_name.is_empty() {
         sts_session_name = DEFAULT_SESSION_NAME.to_string();
     }
     let credentials =
         load_credentials_from_role_arn(region.clone(), role_arn, sts_session_name).await?;
 
-    construct_auth_token(region, credentials).await
+    construct_auth_token(region, credentials, options).await
 }
 
 /// [`generate_auth_token_from_credentials_provider`] generates base64 encoded signed url as auth token by loading IAM credentials
@@ -73,10 +155,11 @@ pub async fn generate_auth_token_from_role(
 pub async fn generate_auth_token_from_credentials_provider(
     region: Region,
     credentials_provider: SharedCredentialsProvider,
-) -> SignerResult<(String, i64)> {
+    options: &TokenOptions,
+) -> SignerResult<AuthToken> {
     let credentials = load_credentials_from_credentials_provider(credentials_provider).await?;
 
-    construct_auth_token(region, credentials).await
+    construct_auth_token(region, credentials, options).await
 }
 
 // Loads credentials from the default credential chain.
@@ -133,19 +216,23 @@ async fn load_credentials_from_credentials_provider(
 async fn construct_auth_token(
     region: Region,
     credentials: Credentials,
-) -> SignerResult<(String, i64)> {
-    let endpoint_url = format!(ENDPOINT_URL_TEMPLATE! {}, region);
+    options: &TokenOptions,
+) -> SignerResult<AuthToken> {
+    let endpoint_url = endpoint_url(&region, &options.endpoint);
 
     #[cfg(debug_assertions)]
     if AWS_DEBUG_CREDS {
         log_caller_identity(region.clone(), credentials.clone()).await;
     }
 
+    let principal_name = PRINCIPALS
+        .get_or_resolve(credentials.access_key_id(), caller_identity_arn(region.clone(), credentials.clone()));
+
     let mut url = build_url(&endpoint_url).map_err(|e| {
         SignerError::ConstructAuthToken(format!("failed to build request for signing: {e}"))
     })?;
 
-    sign_url(&mut url, region, credentials).map_err(|e| {
+    sign_url(&mut url, region, credentials, options.expiry, SystemTime::now()).map_err(|e| {
         SignerError::ConstructAuthToken(format!("failed to sign request with aws sig v4: {e}"))
     })?;
 
@@ -157,7 +244,57 @@ async fn construct_auth_token(
 
     add_user_agent(&mut url);
 
-    Ok((base64_encode(url), expiration_time_ms))
+    Ok(AuthToken {
+        token: base64_encode(url),
+        expiration_time_ms,
+        principal_name,
+    })
+}
+
+/// The principals of credentials, rotated credentials come with a new access key id.
+#[derive(Debug, Default)]
+struct PrincipalCache {
+    principals: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl PrincipalCache {
+    /// The cached principal of the access key, empty until resolved.
+    ///
+    /// Resolving asks STS, it runs in the background so generating a token never waits on it, the tokens
+    /// refreshed after carry the principal. A failure is cached as an empty principal too, it is only
+    /// informative and STS is not asked again.
+    fn get_or_resolve(&self, access_key_id: &str, resolve: impl Future<Output = SignerResult<String>> + Send + 'static) -> String {
+        let mut principals = self.principals.lock().unwrap();
+        if let Some(principal) = principals.get(access_key_id) {
+            return principal.clone();
+        }
+        // empty while resolving, so concurrent refreshes do not ask again.
+        principals.insert(access_key_id.to_string(), String::new());
+        let principals = self.principals.clone();
+        let access_key_id = access_key_id.to_string();
+        tokio::spawn(async move {
+            let principal = resolve.await.unwrap_or_else(|e| {
+                tracing::debug!("failed to resolve principal from caller identity: {e}");
+                String::new()
+            });
+            principals.lock().unwrap().insert(access_key_id, principal);
+        });
+        String::new()
+    }
+}
+
+// Resolves the endpoint url to sign for the given region.
+fn endpoint_url(region: &Region, endpoint: &TokenEndpoint) -> String {
+    let dns_suffix = if region.as_ref().starts_with("cn-") {
+        CN_DNS_SUFFIX
+    } else {
+        DNS_SUFFIX
+    };
+    match endpoint {
+        TokenEndpoint::Regional => format!(ENDPOINT_URL_TEMPLATE! {}, ENDPOINT_PREFIX, region, dns_suffix),
+        TokenEndpoint::Fips => format!(ENDPOINT_URL_TEMPLATE! {}, FIPS_ENDPOINT_PREFIX, region, dns_suffix),
+        TokenEndpoint::Custom(url) => url.to_string(),
+    }
 }
 
 // Build https url with `Action` parameters in order to sign.
@@ -168,7 +305,13 @@ fn build_url(endpoint_url: &str) -> Result<Url, String> {
 }
 
 // Sign url with aws sig v4.
-fn sign_url(url: &mut Url, region: Region, credentials: Credentials) -> Result<(), String> {
+fn sign_url(
+    url: &mut Url,
+    region: Region,
+    credentials: Credentials,
+    expiry: Duration,
+    time: SystemTime,
+) -> Result<(), String> {
     use aws_sigv4::http_request::{
         sign, SignableBody, SignableRequest, SignatureLocation, SigningSettings,
     };
@@ -176,13 +319,13 @@ fn sign_url(url: &mut Url, region: Region, credentials: Credentials) -> Result<(
 
     let mut signing_settings = SigningSettings::default();
     signing_settings.signature_location = SignatureLocation::QueryParams;
-    signing_settings.expires_in = Some(Duration::from_secs(DEFAULT_EXPIRY_SECONDS as u64));
+    signing_settings.expires_in = Some(expiry);
     let identity = credentials.into();
     let signing_params = v4::SigningParams::builder()
         .identity(&identity)
         .region(region.as_ref())
         .name(SIGNING_NAME)
-        .time(SystemTime::now())
+        .time(time)
         .settings(signing_settings)
         .build()
         .map_err(|e| format!("failed to build signing parameters: {e}"))?;
@@ -209,15 +352,25 @@ fn sign_url(url: &mut Url, region: Region, credentials: Credentials) -> Result<(
 fn get_expiration_time_ms(signed_url: &Url) -> Result<i64, String> {
     let (_name, value) = &signed_url
         .query_pairs()
-        .find(|(name, _value)| name == "X-Amz-Date")
+        .find(|(name, _value)| name == DATE_KEY)
         .unwrap_or_else(|| ("".into(), "".into()));
 
     let date_time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
-        .map_err(|_e| format!("failed to parse 'X-Amz-Date' param {value} from signed url"))?;
+        .map_err(|_e| format!("failed to parse '{DATE_KEY}' param {value} from signed url"))?;
+
+    let expiry_seconds = match signed_url
+        .query_pairs()
+        .find(|(name, _value)| name == EXPIRES_KEY)
+    {
+        Some((_name, value)) => value
+            .parse::<i64>()
+            .map_err(|_e| format!("failed to parse '{EXPIRES_KEY}' param {value} from signed url"))?,
+        None => DEFAULT_EXPIRY_SECONDS,
+    };
 
     let signing_time_ms = date_time.and_utc().timestamp_millis();
 
-    Ok(signing_time_ms + DEFAULT_EXPIRY_SECONDS * 1000)
+    Ok(signing_time_ms + expiry_seconds * 1000)
 }
 
 // Base64 encode with raw url encoding.
@@ -234,6 +387,39 @@ fn add_user_agent(signed_url: &mut Url) {
         .append_pair(USER_AGENT_KEY, &user_agent);
 }
 
+/// [`caller_identity`] describes the credentials picked up by the default credentials provider chain.
+pub async fn caller_identity(region: Region) -> SignerResult<CallerIdentity> {
+    let credentials = load_default_credentials(region.clone()).await?;
+    let access_key_id = credentials.access_key_id().to_string();
+    let temporary = credentials.session_token().is_some();
+    let expiry = credentials.expiry().map(DateTime::<Utc>::from);
+    let arn = caller_identity_arn(region, credentials).await?;
+
+    Ok(CallerIdentity {
+        access_key_id,
+        temporary,
+        expiry,
+        arn,
+    })
+}
+
+// Resolves the ARN of the identity the credentials belong to.
+async fn caller_identity_arn(region: Region, aws_credentials: Credentials) -> SignerResult<String> {
+    let config = aws_config::defaults(BehaviorVersion::latest())
+        .region(region)
+        .credentials_provider(SharedCredentialsProvider::new(aws_credentials))
+        .load()
+        .await;
+
+    aws_sdk_sts::Client::new(&config)
+        .get_caller_identity()
+        .send()
+        .await
+        .map_err(|e| SignerError::CallerIdentity(e.to_string()))?
+        .arn
+        .ok_or_else(|| SignerError::CallerIdentity("caller identity has no arn".to_string()))
+}
+
 /// Log caller identity to debug which credentials are being picked up
 #[cfg(debug_assertions)]
 async fn log_caller_identity(region: Region, aws_credentials: Credentials) {
@@ -264,4 +450,140 @@ pub enum SignerError {
     ProvideCredentials(#[from] CredentialsError),
     #[error("failed construct auth token: {0}")]
     ConstructAuthToken(String),
-}
\ No newline at end of file
+    #[error("failed to get caller identity: {0}")]
+    CallerIdentity(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    // 2023-11-14T22:13:20Z
+    const SIGNING_TIME_SECONDS: u64 = 1_700_000_000;
+
+    fn credentials() -> Credentials {
+        Credentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", None, None, "test")
+    }
+
+    fn signing_time() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(SIGNING_TIME_SECONDS)
+    }
+
+    fn signed_url(expiry: Duration) -> Url {
+        let mut url = build_url("https://kafka.us-east-1.amazonaws.com").unwrap();
+        sign_url(&mut url, Region::new("us-east-1"), credentials(), expiry, signing_time()).unwrap();
+        url
+    }
+
+    fn query_param(url: &Url, key: &str) -> Option<String> {
+        url.query_pairs()
+            .find(|(name, _value)| name == key)
+            .map(|(_name, value)| value.to_string())
+    }
+
+    #[test]
+    fn build_url_appends_action() {
+        let url = build_url("https://kafka.us-east-1.amazonaws.com").unwrap();
+
+        assert_eq!(url.as_str(), "https://kafka.us-east-1.amazonaws.com/?Action=kafka-cluster%3AConnect");
+    }
+
+    #[test]
+    fn build_url_rejects_invalid_url() {
+        assert!(build_url("not a url").is_err());
+    }
+
+    #[test]
+    fn endpoint_url_resolves_partitions() {
+        let region = Region::new("eu-west-1");
+        let cn_region = Region::new("cn-north-1");
+
+        assert_eq!(endpoint_url(&region, &TokenEndpoint::Regional), "https://kafka.eu-west-1.amazonaws.com");
+        assert_eq!(endpoint_url(&region, &TokenEndpoint::Fips), "https://kafka-fips.eu-west-1.amazonaws.com");
+        assert_eq!(endpoint_url(&cn_region, &TokenEndpoint::Regional), "https://kafka.cn-north-1.amazonaws.com.cn");
+        assert_eq!(
+            endpoint_url(&region, &TokenEndpoint::Custom("https://vpce.example.com".to_string())),
+            "https://vpce.example.com"
+        );
+    }
+
+    #[test]
+    fn token_options_prefers_custom_endpoint() {
+        let options = TokenOptions::new(300, true, Some("https://vpce.example.com".to_string()));
+
+        assert_eq!(options.expiry, Duration::from_secs(300));
+        assert_eq!(options.endpoint, TokenEndpoint::Custom("https://vpce.example.com".to_string()));
+        assert_eq!(TokenOptions::new(900, true, None).endpoint, TokenEndpoint::Fips);
+    }
+
+    #[test]
+    fn sign_url_adds_signature_params() {
+        let url = signed_url(Duration::from_secs(300));
+
+        assert_eq!(query_param(&url, ACTION_TYPE).as_deref(), Some(ACTION_NAME));
+        assert_eq!(query_param(&url, "X-Amz-Algorithm").as_deref(), Some("AWS4-HMAC-SHA256"));
+        assert_eq!(
+            query_param(&url, "X-Amz-Credential").as_deref(),
+            Some("AKIDEXAMPLE/20231114/us-east-1/kafka-cluster/aws4_request")
+        );
+        assert_eq!(query_param(&url, DATE_KEY).as_deref(), Some("20231114T221320Z"));
+        assert_eq!(query_param(&url, EXPIRES_KEY).as_deref(), Some("300"));
+        assert_eq!(query_param(&url, "X-Amz-SignedHeaders").as_deref(), Some("host"));
+        let signature = query_param(&url, "X-Amz-Signature").unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn sign_url_is_deterministic() {
+        assert_eq!(signed_url(Duration::from_secs(900)), signed_url(Duration::from_secs(900)));
+        assert_ne!(signed_url(Duration::from_secs(900)), signed_url(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn expiration_time_uses_signed_expiry() {
+        let url = signed_url(Duration::from_secs(300));
+
+        let expected = (SIGNING_TIME_SECONDS as i64 + 300) * 1000;
+        assert_eq!(get_expiration_time_ms(&url).unwrap(), expected);
+    }
+
+    #[test]
+    fn expiration_time_defaults_without_expires_param() {
+        let url = Url::parse("https://kafka.us-east-1.amazonaws.com/?X-Amz-Date=20231114T221320Z").unwrap();
+
+        let expected = (SIGNING_TIME_SECONDS as i64 + DEFAULT_EXPIRY_SECONDS) * 1000;
+        assert_eq!(get_expiration_time_ms(&url).unwrap(), expected);
+    }
+
+    // Lets the background resolutions of the current thread runtime finish.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn principal_is_resolved_once_per_access_key_in_the_background() {
+        let cache = PrincipalCache::default();
+        let arn = "arn:aws:iam::123456789012:user/alice".to_string();
+
+        let resolved = arn.clone();
+        assert_eq!(cache.get_or_resolve("AKID1", async move { Ok(resolved) }), "");
+        settle().await;
+        let cached = cache.get_or_resolve("AKID1", async { Err(SignerError::CallerIdentity("not called".to_string())) });
+        assert_eq!(cached, arn);
+
+        assert_eq!(cache.get_or_resolve("AKID2", async { Err(SignerError::CallerIdentity("denied".to_string())) }), "");
+        settle().await;
+        assert_eq!(cache.get_or_resolve("AKID2", async { Ok("not called".to_string()) }), "");
+    }
+
+    #[test]
+    fn expiration_time_requires_signing_date() {
+        let url = build_url("https://kafka.us-east-1.amazonaws.com").unwrap();
+
+        assert!(get_expiration_time_ms(&url).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use crate::kafka::client::{create_base_client, create_reader, Config, IamClientContext};
+use crate::kafka::types::{MessageEntry, MessagePageEntry};
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::{BorrowedMessage, Headers, Message};
+use rdkafka::types::RDKafkaErrorCode;
+use rdkafka::{Offset, TopicPartitionList};
+use std::time::{Duration, Instant};
+
+/// Where a page of records starts in a partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartPosition {
+    Beginning,
+    /// The last page of the partition.
+    End,
+    Offset(i64),
+    /// The first offset with a timestamp at or after this one, in milliseconds since the epoch.
+    Timestamp(i64),
+}
+
+/// The number of partitions of the topic.
+pub fn partition_count(config: &Config, topic: &str) -> Result<i32, KafkaError> {
+    let client = create_base_client(config);
+    let metadata = client.fetch_metadata(Some(topic), config.timeout)?;
+    metadata.topics().iter()
+        .find(|t| t.name() == topic && t.error().is_none())
+        .map(|t| t.partitions().len() as i32)
+        .ok_or(KafkaError::MetadataFetch(RDKafkaErrorCode::UnknownTopicOrPartition))
+}
+
+/// Reads up to `count` records of the partition, starting at `start`.
+///
+/// Offsets are clamped to the watermarks, a timestamp after the last record resolves to an empty page.
+/// Fewer records are returned when the partition ends, or when reading takes longer than the timeout.
+pub fn fetch_page(config: &Config, topic: &str, partition: i32, start: StartPosition, count: usize) -> Result<MessagePageEntry, KafkaError> {
+    let consumer = create_reader(config, true);
+    let (low, high) = consumer.fetch_watermarks(topic, partition, config.timeout)?;
+    let start = match start {
+        StartPosition::Timestamp(timestamp) => {
+            let mut timestamps = TopicPartitionList::new();
+            timestamps.add_partition_offset(topic, partition, Offset::Offset(timestamp))?;
+            let offsets = consumer.offsets_for_times(timestamps, config.timeout)?;
+            match offsets.find_partition(topic, partition).map(|e| e.offset()) {
+                Some(Offset::Offset(offset)) => StartPosition::Offset(offset),
+                _ => StartPosition::Offset(high),
+            }
+        }
+        start => start,
+    };
+    let offset = start_offset(start, low, high, count);
+
+    let mut page = MessagePageEntry {
+        topic: topic.to_string(),
+        partition,
+        low_watermark: low,
+        high_watermark: high,
+        messages: vec![],
+    };
+    if offset >= high || count == 0 {
+        return Ok(page);
+    }
+
+    let mut assignment = TopicPartitionList::new();
+    assignment.add_partition_offset(topic, partition, Offset::Offset(offset))?;
+    consumer.assign(&assignment)?;
+    let started = Instant::now();
+    while page.messages.len() < count && started.elapsed() < config.timeout {
+        let message = match consumer.poll(config.timeout.saturating_sub(started.elapsed())) {
+            Some(Ok(message)) => message,
+            Some(Err(KafkaError::PartitionEOF(_))) | None => break,
+            Some(Err(err)) => return Err(err),
+        };
+        page.messages.push(message_entry(&message));
+        if message.offset() + 1 >= high {
+            break;
+        }
+    }
+    Ok(page)
+}
+
+/// Follows the partitions of topics from their end, reading records as they are produced.
+pub struct Tail {
+    consumer: BaseConsumer<IamClientContext>,
+}
+
+impl Tail {
+    /// Starts at the end of every partition of the topics.
+    pub fn start(config: &Config, topics: &[String]) -> Result<Self, KafkaError> {
+        // the end of a partition is only where new records show up.
+        let consumer = create_reader(config, false);
+        let mut assignment = TopicPartitionList::new();
+        for topic in topics {
+            for partition in 0..partition_count(config, topic)? {
+                assignment.add_partition_offset(topic, partition, Offset::End)?;
+            }
+        }
+        consumer.assign(&assignment)?;
+        Ok(Self { consumer })
+    }
+
+    /// The next record, `None` when none was produced within the timeout.
+    pub fn poll(&self, timeout: Duration) -> Result<Option<MessageEntry>, KafkaError> {
+        match self.consumer.poll(timeout) {
+            Some(Ok(message)) => Ok(Some(message_entry(&message))),
+            Some(Err(err)) => Err(err),
+            None => Ok(None),
+        }
+    }
+}
+
+fn message_entry(message: &BorrowedMessage) -> MessageEntry {
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    MessageEntry {
+        topic: message.topic().to_string(),
+        partition: message.partition(),
+        offset: message.offset(),
+        timestamp: message.timestamp().to_millis(),
+        key: message.key().map(text),
+        value: message.payload().map(text),
+        headers: message.headers()
+            .map(|headers| headers.iter().map(|h| (h.key.to_string(), h.value.map(text))).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// The first offset to read, within the watermarks, timestamps are resolved to offsets by the caller.
+fn start_offset(start: StartPosition, low: i64, high: i64, count: usize) -> i64 {
+    match start {
+        StartPosition::Beginning => low,
+        StartPosition::End => (high - count as i64).max(low),
+        StartPosition::Offset(offset) => offset.clamp(low, high),
+        StartPosition::Timestamp(_) => unreachable!("timestamps are resolved to offsets before reading"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_start_offset_to_watermarks() {
+        assert_eq!(start_offset(StartPosition::Beginning, 10, 100, 20), 10);
+        assert_eq!(start_offset(StartPosition::End, 10, 100, 20), 80);
+        assert_eq!(start_offset(StartPosition::End, 10, 15, 20), 10);
+        assert_eq!(start_offset(StartPosition::Offset(5), 10, 100, 20), 10);
+        assert_eq!(start_offset(StartPosition::Offset(50), 10, 100, 20), 50);
+        assert_eq!(start_offset(StartPosition::Offset(500), 10, 100, 20), 100);
+    }
+}
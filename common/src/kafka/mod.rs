@@ -3,4 +3,13 @@ pub mod topic;
 pub mod client;
 pub mod types;
 pub mod broker;
-pub mod group;
\ No newline at end of file
+pub mod group;
+pub mod diagnostics;
+pub mod health;
+pub mod election;
+pub mod acl;
+pub mod quota;
+pub mod protocol;
+pub mod reassign;
+pub mod message;
+mod native;
\ No newline at end of file
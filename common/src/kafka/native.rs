@@ -0,0 +1,207 @@
+//! Thin wrappers over the librdkafka admin API for operations rdkafka does not expose.
+
+use crate::kafka::client::{create_admin_client, Config, IamClientContext, WriteError};
+use rdkafka::admin::AdminClient;
+use rdkafka::bindings as rdsys;
+use rdkafka::error::KafkaError;
+use rdkafka::types::{RDKafkaErrorCode, RDKafkaRespErr};
+use std::ffi::{c_char, CStr, CString};
+
+/// Runs a single admin operation on a dedicated queue and waits for its result event.
+///
+/// `request` is handed the native client, the admin options and the queue to post the result to,
+/// `extract` reads the result out of the event before it is destroyed.
+pub(crate) fn admin_request<R>(
+    config: &Config,
+    op: rdsys::rd_kafka_admin_op_t,
+    request: impl FnOnce(*mut rdsys::rd_kafka_t, *mut rdsys::rd_kafka_AdminOptions_t, *mut rdsys::rd_kafka_queue_t),
+    extract: impl FnOnce(*mut rdsys::rd_kafka_event_t) -> Result<R, KafkaError>,
+) -> Result<R, KafkaError> {
+    admin_request_on(&create_admin_client(config), config, op, request, extract)
+}
+
+/// As [`admin_request`], on a client shared by many requests.
+pub(crate) fn admin_request_on<R>(
+    client: &AdminClient<IamClientContext>,
+    config: &Config,
+    op: rdsys::rd_kafka_admin_op_t,
+    request: impl FnOnce(*mut rdsys::rd_kafka_t, *mut rdsys::rd_kafka_AdminOptions_t, *mut rdsys::rd_kafka_queue_t),
+    extract: impl FnOnce(*mut rdsys::rd_kafka_event_t) -> Result<R, KafkaError>,
+) -> Result<R, KafkaError> {
+    let timeout_ms = config.timeout.as_millis() as i32;
+    unsafe {
+        let rk = client.inner().native_ptr();
+        let queue = rdsys::rd_kafka_queue_new(rk);
+        let options = rdsys::rd_kafka_AdminOptions_new(rk, op);
+        let mut errstr = [0 as c_char; 512];
+        rdsys::rd_kafka_AdminOptions_set_request_timeout(options, timeout_ms, errstr.as_mut_ptr(), errstr.len());
+
+        request(rk, options, queue);
+
+        // the request timeout already bounds the operation, leave some room for the result to be delivered.
+        let event = rdsys::rd_kafka_queue_poll(queue, timeout_ms.saturating_add(1000));
+        rdsys::rd_kafka_AdminOptions_destroy(options);
+
+        let result = if event.is_null() {
+            Err(KafkaError::AdminOp(RDKafkaErrorCode::OperationTimedOut))
+        } else {
+            let err = rdsys::rd_kafka_event_error(event);
+            let result = if err != RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR {
+                Err(KafkaError::AdminOp(err.into()))
+            } else {
+                extract(event)
+            };
+            rdsys::rd_kafka_event_destroy(event);
+            result
+        };
+        rdsys::rd_kafka_queue_destroy(queue);
+        result
+    }
+}
+
+/// Copies a nullable C string into an owned string.
+///
+/// # Safety
+/// `ptr` must be null or point to a valid nul terminated string.
+pub(crate) unsafe fn to_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+}
+
+/// Builds a slice over a librdkafka result array, tolerating null for empty results.
+///
+/// # Safety
+/// `ptr` must be null or point to `len` valid elements that outlive the returned slice.
+pub(crate) unsafe fn as_slice<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// A broker node returned by the admin API.
+pub(crate) struct Node {
+    pub(crate) id: i32,
+    pub(crate) host: String,
+    pub(crate) port: i32,
+    pub(crate) rack: Option<String>,
+}
+
+/// Reads a broker node returned by the admin API.
+///
+/// # Safety
+/// `node` must be null or point to a valid node.
+pub(crate) unsafe fn node(node: *const rdsys::rd_kafka_Node_t) -> Option<Node> {
+    if node.is_null() {
+        return None;
+    }
+    Some(Node {
+        id: rdsys::rd_kafka_Node_id(node),
+        host: to_string(rdsys::rd_kafka_Node_host(node)).unwrap_or_default(),
+        port: rdsys::rd_kafka_Node_port(node) as i32,
+        rack: to_string(rdsys::rd_kafka_Node_rack(node)),
+    })
+}
+
+/// Change applied to a config entry by [`incremental_alter_configs`].
+pub(crate) enum AlterConfigOp {
+    Set(String),
+    Delete,
+}
+
+/// Config entries to change on a single broker or topic.
+pub(crate) struct ConfigAlteration {
+    pub(crate) resource_type: rdsys::rd_kafka_ResourceType_t,
+    pub(crate) name: String,
+    pub(crate) entries: Vec<(String, AlterConfigOp)>,
+}
+
+/// Sets or deletes config entries without touching the rest of each resource's config.
+///
+/// Fails with the error of the first resource that could not be altered.
+pub(crate) fn incremental_alter_configs(config: &Config, alterations: &[ConfigAlteration]) -> Result<(), WriteError> {
+    config.check_writable("alter configs")?;
+    if alterations.is_empty() {
+        return Ok(());
+    }
+    let to_c = |value: &str| CString::new(value)
+        .map_err(|e| KafkaError::AdminOpCreation(e.to_string()));
+
+    let mut resources = Vec::with_capacity(alterations.len());
+    let built = (|| {
+        for alteration in alterations {
+            let resource = unsafe { rdsys::rd_kafka_ConfigResource_new(alteration.resource_type, to_c(&alteration.name)?.as_ptr()) };
+            resources.push(resource);
+            for (name, op) in &alteration.entries {
+                let name = to_c(name)?;
+                let error = match op {
+                    AlterConfigOp::Set(value) => unsafe {
+                        rdsys::rd_kafka_ConfigResource_add_incremental_config(
+                            resource,
+                            name.as_ptr(),
+                            rdsys::rd_kafka_AlterConfigOpType_t::RD_KAFKA_ALTER_CONFIG_OP_TYPE_SET,
+                            to_c(value)?.as_ptr(),
+                        )
+                    },
+                    AlterConfigOp::Delete => unsafe {
+                        rdsys::rd_kafka_ConfigResource_add_incremental_config(
+                            resource,
+                            name.as_ptr(),
+                            rdsys::rd_kafka_AlterConfigOpType_t::RD_KAFKA_ALTER_CONFIG_OP_TYPE_DELETE,
+                            std::ptr::null(),
+                        )
+                    },
+                };
+                if !error.is_null() {
+                    let code = unsafe { rdsys::rd_kafka_error_code(error) };
+                    unsafe { rdsys::rd_kafka_error_destroy(error) };
+                    return Err(KafkaError::AdminOp(code.into()));
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    // the request copies the resources, they are ours to destroy either way.
+    let result = built.and_then(|_| admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_INCREMENTALALTERCONFIGS,
+        |rk, options, queue| unsafe {
+            rdsys::rd_kafka_IncrementalAlterConfigs(rk, resources.as_mut_ptr(), resources.len(), options, queue)
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_IncrementalAlterConfigs_result(event);
+            let mut count = 0;
+            let altered = rdsys::rd_kafka_IncrementalAlterConfigs_result_resources(result, &mut count);
+            for resource in as_slice(altered, count) {
+                let err = rdsys::rd_kafka_ConfigResource_error(*resource);
+                if err != RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR {
+                    return Err(KafkaError::AdminOp(err.into()));
+                }
+            }
+            Ok(())
+        },
+    ));
+    unsafe {
+        for resource in resources {
+            rdsys::rd_kafka_ConfigResource_destroy(resource);
+        }
+    }
+    Ok(result?)
+}
+
+/// Reads the message of a nullable librdkafka error object, which stays owned by the caller.
+///
+/// # Safety
+/// `error` must be null or point to a valid error.
+pub(crate) unsafe fn error_message(error: *const rdsys::rd_kafka_error_t) -> Option<String> {
+    if error.is_null() {
+        None
+    } else {
+        to_string(rdsys::rd_kafka_error_string(error))
+    }
+}
@@ -0,0 +1,531 @@
+//! Minimal Kafka protocol client for the admin APIs librdkafka does not implement.
+//!
+//! Only what those APIs need is supported: plaintext connections, or TLS with SASL/OAUTHBEARER
+//! using the IAM auth token when IAM authentication is enabled.
+
+use crate::kafka::client::{create_base_client, Config};
+use crate::kafka::iam::SignerError;
+use openssl::ssl::{SslConnector, SslMethod};
+use rdkafka::bindings as rdsys;
+use rdkafka::consumer::Consumer;
+use rdkafka::error::KafkaError;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use thiserror::Error;
+
+const CLIENT_ID: &str = "kafka-utils";
+/// The largest response read, a bigger size is taken for a corrupt or non-Kafka stream.
+const MAX_RESPONSE_SIZE: usize = 100 * 1024 * 1024;
+
+const SASL_HANDSHAKE: i16 = 17;
+pub(crate) const DESCRIBE_LOG_DIRS: i16 = 35;
+const SASL_AUTHENTICATE: i16 = 36;
+pub(crate) const ELECT_LEADERS: i16 = 43;
+pub(crate) const ALTER_PARTITION_REASSIGNMENTS: i16 = 45;
+pub(crate) const LIST_PARTITION_REASSIGNMENTS: i16 = 46;
+pub(crate) const DESCRIBE_CLIENT_QUOTAS: i16 = 48;
+pub(crate) const ALTER_CLIENT_QUOTAS: i16 = 49;
+
+#[derive(Error, Debug)]
+pub enum ProtocolError {
+    #[error("connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("tls handshake failed: {0}")]
+    Tls(String),
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error("authentication failed: {0}")]
+    Authentication(String),
+    #[error("{}", error_message(*.code, .message))]
+    Broker { code: i16, message: Option<String> },
+    #[error("malformed response: {0}")]
+    Decode(String),
+}
+
+type ProtocolResult<T> = Result<T, ProtocolError>;
+
+trait Stream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Stream for T {}
+
+/// A connection to a single broker, authenticated the same way the librdkafka clients are.
+///
+/// Every call blocks until the broker answers or `config.timeout` elapses, async callers run them
+/// on the blocking pool.
+pub(crate) struct BrokerConnection {
+    stream: Box<dyn Stream>,
+    correlation_id: i32,
+}
+
+impl BrokerConnection {
+    /// Opens an authenticated connection to the controller, falling back to any broker when unknown.
+    pub(crate) fn controller(config: &Config) -> ProtocolResult<Self> {
+        let client = create_base_client(config);
+        let metadata = client.fetch_metadata(None, config.timeout)?;
+        let controller_id = unsafe {
+            rdsys::rd_kafka_controllerid(client.client().native_ptr(), config.timeout.as_millis() as i32)
+        };
+        let broker = metadata.brokers()
+            .iter()
+            .find(|b| b.id() == controller_id)
+            .or(metadata.brokers().first())
+            .ok_or(KafkaError::MetadataFetch(rdkafka::types::RDKafkaErrorCode::BrokerNotAvailable))?;
+        Self::open(config, broker.host(), broker.port() as u16)
+    }
+
+    pub(crate) fn open(config: &Config, host: &str, port: u16) -> ProtocolResult<Self> {
+        let tcp = connect(host, port, config)?;
+        tcp.set_read_timeout(Some(config.timeout))?;
+        tcp.set_write_timeout(Some(config.timeout))?;
+
+        if !config.iam_auth() {
+            return Ok(Self { stream: Box::new(tcp), correlation_id: 0 });
+        }
+
+        let tls = SslConnector::builder(SslMethod::tls())
+            .map_err(|e| ProtocolError::Tls(e.to_string()))?
+            .build()
+            .connect(host, tcp)
+            .map_err(|e| ProtocolError::Tls(e.to_string()))?;
+        let mut connection = Self { stream: Box::new(tls), correlation_id: 0 };
+        connection.authenticate(config)?;
+        Ok(connection)
+    }
+
+    fn authenticate(&mut self, config: &Config) -> ProtocolResult<()> {
+        let mut handshake = Encoder::new();
+        handshake.string("OAUTHBEARER");
+        let response = self.request(SASL_HANDSHAKE, 1, false, handshake)?;
+        let mut decoder = Decoder::new(&response);
+        let error_code = decoder.i16()?;
+        if error_code != 0 {
+            return Err(ProtocolError::Authentication(error_message(error_code, &None)));
+        }
+
+        let token = config.auth_token()?;
+        let mut authenticate = Encoder::new();
+        authenticate.bytes(format!("n,,\x01auth=Bearer {}\x01\x01", token.token).as_bytes());
+        let response = self.request(SASL_AUTHENTICATE, 1, false, authenticate)?;
+        let mut decoder = Decoder::new(&response);
+        let error_code = decoder.i16()?;
+        let error_message = decoder.nullable_string()?;
+        if error_code != 0 {
+            return Err(ProtocolError::Authentication(
+                error_message.unwrap_or_else(|| self::error_message(error_code, &None)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sends a request and returns the response body, after the response header.
+    pub(crate) fn request(&mut self, api_key: i16, api_version: i16, flexible: bool, body: Encoder) -> ProtocolResult<Vec<u8>> {
+        self.correlation_id += 1;
+
+        let mut header = Encoder::new();
+        header.i16(api_key);
+        header.i16(api_version);
+        header.i32(self.correlation_id);
+        header.string(CLIENT_ID);
+        if flexible {
+            header.tagged_fields();
+        }
+
+        let size = (header.buf.len() + body.buf.len()) as i32;
+        self.stream.write_all(&size.to_be_bytes())?;
+        self.stream.write_all(&header.buf)?;
+        self.stream.write_all(&body.buf)?;
+        self.stream.flush()?;
+
+        let mut size = [0u8; 4];
+        self.stream.read_exact(&mut size)?;
+        let size = i32::from_be_bytes(size);
+        let size = usize::try_from(size)
+            .ok()
+            .filter(|size| *size <= MAX_RESPONSE_SIZE)
+            .ok_or_else(|| ProtocolError::Decode(format!("response size {size} out of range")))?;
+        let mut response = vec![0u8; size];
+        self.stream.read_exact(&mut response)?;
+
+        let mut decoder = Decoder::new(&response);
+        let correlation_id = decoder.i32()?;
+        if correlation_id != self.correlation_id {
+            return Err(ProtocolError::Decode(format!(
+                "expected correlation id {}, got {correlation_id}",
+                self.correlation_id,
+            )));
+        }
+        if flexible {
+            decoder.skip_tagged_fields()?;
+        }
+        Ok(response[decoder.pos..].to_vec())
+    }
+}
+
+// Tries every address of the host in turn, each within the timeout.
+fn connect(host: &str, port: u16, config: &Config) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, config.timeout) {
+            Ok(tcp) => return Ok(tcp),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{host} has no address"))))
+}
+
+/// Writes request fields in the Kafka wire format.
+pub(crate) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    pub(crate) fn i8(&mut self, value: i8) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn i16(&mut self, value: i16) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.buf.push(value as u8);
+    }
+
+    pub(crate) fn uvarint(&mut self, mut value: u32) {
+        while value >= 0x80 {
+            self.buf.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    pub(crate) fn string(&mut self, value: &str) {
+        self.i16(value.len() as i16);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    pub(crate) fn nullable_string(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => self.string(value),
+            None => self.i16(-1),
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, value: &[u8]) {
+        self.i32(value.len() as i32);
+        self.buf.extend_from_slice(value);
+    }
+
+    pub(crate) fn compact_string(&mut self, value: &str) {
+        self.uvarint(value.len() as u32 + 1);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    /// Writes the length prefix of a compact array, `None` encodes a null array.
+    pub(crate) fn compact_array_len(&mut self, len: Option<usize>) {
+        match len {
+            Some(len) => self.uvarint(len as u32 + 1),
+            None => self.uvarint(0),
+        }
+    }
+
+    /// Writes the length prefix of an array, `None` encodes a null array.
+    pub(crate) fn array_len(&mut self, len: Option<usize>) {
+        match len {
+            Some(len) => self.i32(len as i32),
+            None => self.i32(-1),
+        }
+    }
+
+    /// Writes an empty tagged fields section.
+    pub(crate) fn tagged_fields(&mut self) {
+        self.uvarint(0);
+    }
+}
+
+/// Reads response fields in the Kafka wire format.
+pub(crate) struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> ProtocolResult<&'a [u8]> {
+        if self.pos + len > self.buf.len() {
+            return Err(ProtocolError::Decode(format!(
+                "expected {len} bytes at offset {}, only {} left",
+                self.pos,
+                self.buf.len() - self.pos,
+            )));
+        }
+        let value = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(value)
+    }
+
+    pub(crate) fn i16(&mut self) -> ProtocolResult<i16> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn i32(&mut self) -> ProtocolResult<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn i64(&mut self) -> ProtocolResult<i64> {
+        Ok(i64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn bool(&mut self) -> ProtocolResult<bool> {
+        Ok(self.take(1)?[0] != 0)
+    }
+
+    pub(crate) fn f64(&mut self) -> ProtocolResult<f64> {
+        Ok(f64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn uvarint(&mut self) -> ProtocolResult<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ProtocolError::Decode("varint is too long".to_string()))
+    }
+
+    fn utf8(&mut self, len: usize) -> ProtocolResult<String> {
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| ProtocolError::Decode(e.to_string()))
+    }
+
+    pub(crate) fn nullable_string(&mut self) -> ProtocolResult<Option<String>> {
+        match self.i16()? {
+            len if len < 0 => Ok(None),
+            len => Ok(Some(self.utf8(len as usize)?)),
+        }
+    }
+
+    pub(crate) fn string(&mut self) -> ProtocolResult<String> {
+        Ok(self.nullable_string()?.unwrap_or_default())
+    }
+
+    pub(crate) fn compact_nullable_string(&mut self) -> ProtocolResult<Option<String>> {
+        match self.uvarint()? {
+            0 => Ok(None),
+            len => Ok(Some(self.utf8(len as usize - 1)?)),
+        }
+    }
+
+    pub(crate) fn compact_string(&mut self) -> ProtocolResult<String> {
+        Ok(self.compact_nullable_string()?.unwrap_or_default())
+    }
+
+    /// Reads the length prefix of a compact array, a null array is read as empty.
+    pub(crate) fn compact_array_len(&mut self) -> ProtocolResult<usize> {
+        Ok(self.uvarint()?.saturating_sub(1) as usize)
+    }
+
+    /// Reads the length prefix of an array, a null array is read as empty.
+    pub(crate) fn array_len(&mut self) -> ProtocolResult<usize> {
+        Ok(self.i32()?.max(0) as usize)
+    }
+
+    pub(crate) fn compact_i32_array(&mut self) -> ProtocolResult<Vec<i32>> {
+        (0..self.compact_array_len()?).map(|_| self.i32()).collect()
+    }
+
+    pub(crate) fn skip_tagged_fields(&mut self) -> ProtocolResult<()> {
+        for _ in 0..self.uvarint()? {
+            self.uvarint()?;
+            let size = self.uvarint()? as usize;
+            self.take(size)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fails with the broker error when the code is not zero.
+pub(crate) fn check_error(code: i16, message: Option<String>) -> ProtocolResult<()> {
+    if code == 0 {
+        Ok(())
+    } else {
+        Err(ProtocolError::Broker { code, message })
+    }
+}
+
+/// Describes a Kafka error code, preferring the message sent by the broker.
+pub(crate) fn error_message(code: i16, message: &Option<String>) -> String {
+    if let Some(message) = message.as_ref().filter(|m| !m.is_empty()) {
+        return message.clone();
+    }
+    let name = match code {
+        0 => "no error",
+        3 => "unknown topic or partition",
+        7 => "request timed out",
+        29 => "topic authorization failed",
+        31 => "cluster authorization failed",
+        33 => "unsupported sasl mechanism",
+        35 => "unsupported version",
+        36 => "topic already exists",
+        37 => "invalid partitions",
+        39 => "invalid replica assignment",
+        40 => "invalid config",
+        41 => "not controller",
+        42 => "invalid request",
+        58 => "sasl authentication failed",
+        84 => "election not needed",
+        85 => "no reassignment in progress",
+        83 => "eligible leaders not available",
+        _ => "kafka error",
+    };
+    format!("{name} (error code {code})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_uvarint() {
+        let mut encoder = Encoder::new();
+        encoder.uvarint(0);
+        encoder.uvarint(127);
+        encoder.uvarint(300);
+
+        assert_eq!(encoder.buf, vec![0x00, 0x7f, 0xac, 0x02]);
+        let mut decoder = Decoder::new(&encoder.buf);
+        assert_eq!(decoder.uvarint().unwrap(), 0);
+        assert_eq!(decoder.uvarint().unwrap(), 127);
+        assert_eq!(decoder.uvarint().unwrap(), 300);
+    }
+
+    #[test]
+    fn encodes_compact_and_legacy_strings() {
+        let mut encoder = Encoder::new();
+        encoder.compact_string("ab");
+        encoder.string("ab");
+
+        assert_eq!(encoder.buf, vec![0x03, b'a', b'b', 0x00, 0x02, b'a', b'b']);
+        let mut decoder = Decoder::new(&encoder.buf);
+        assert_eq!(decoder.compact_string().unwrap(), "ab");
+        assert_eq!(decoder.nullable_string().unwrap(), Some("ab".to_string()));
+        let mut decoder = Decoder::new(&[0x00, 0xff, 0xff]);
+        assert_eq!(decoder.compact_nullable_string().unwrap(), None);
+        assert_eq!(decoder.nullable_string().unwrap(), None);
+    }
+
+    #[test]
+    fn skips_tagged_fields() {
+        let buf = vec![0x01, 0x00, 0x02, 0xaa, 0xbb, 0x00, 0x00, 0x00, 0x07];
+        let mut decoder = Decoder::new(&buf);
+
+        decoder.skip_tagged_fields().unwrap();
+
+        assert_eq!(decoder.i32().unwrap(), 7);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let mut decoder = Decoder::new(&[0x00, 0x01]);
+
+        assert!(matches!(decoder.i32(), Err(ProtocolError::Decode(_))));
+    }
+
+    #[tokio::test]
+    async fn round_trips_a_request() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broker = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut size = [0u8; 4];
+            stream.read_exact(&mut size).unwrap();
+            let mut request = vec![0u8; i32::from_be_bytes(size) as usize];
+            stream.read_exact(&mut request).unwrap();
+
+            // correlation id, empty tagged fields and a single i32 body.
+            let response = [&request[4..8], &[0x00], &7i32.to_be_bytes()].concat();
+            stream.write_all(&(response.len() as i32).to_be_bytes()).unwrap();
+            stream.write_all(&response).unwrap();
+            request
+        });
+        let config = crate::kafka::client::create_config(
+            format!("127.0.0.1:{port}"),
+            false,
+            "eu-west-1".to_string(),
+            Default::default(),
+            std::time::Duration::from_secs(5),
+        );
+
+        let mut connection = BrokerConnection::open(&config, "127.0.0.1", port).unwrap();
+        let mut body = Encoder::new();
+        body.i32(42);
+        let response = connection.request(ALTER_PARTITION_REASSIGNMENTS, 0, true, body).unwrap();
+
+        assert_eq!(Decoder::new(&response).i32().unwrap(), 7);
+        let request = broker.join().unwrap();
+        let mut decoder = Decoder::new(&request);
+        assert_eq!(decoder.i16().unwrap(), ALTER_PARTITION_REASSIGNMENTS);
+        assert_eq!(decoder.i16().unwrap(), 0);
+        assert_eq!(decoder.i32().unwrap(), 1);
+        assert_eq!(decoder.nullable_string().unwrap().as_deref(), Some(CLIENT_ID));
+        decoder.skip_tagged_fields().unwrap();
+        assert_eq!(decoder.i32().unwrap(), 42);
+    }
+
+    // A broker answering with the given bytes, whatever the request.
+    struct Replay(io::Cursor<Vec<u8>>);
+
+    impl Read for Replay {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Replay {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rejects_response_sizes_out_of_range() {
+        for size in [MAX_RESPONSE_SIZE as i32 + 1, i32::MAX, -1] {
+            let response = [&size.to_be_bytes()[..], &[0u8; 8]].concat();
+            let mut connection = BrokerConnection { stream: Box::new(Replay(io::Cursor::new(response))), correlation_id: 0 };
+
+            let result = connection.request(ALTER_PARTITION_REASSIGNMENTS, 0, true, Encoder::new());
+
+            assert!(matches!(result, Err(ProtocolError::Decode(_))), "size {size}");
+        }
+    }
+
+    #[test]
+    fn describes_error_codes() {
+        assert_eq!(error_message(41, &None), "not controller (error code 41)");
+        assert_eq!(error_message(41, &Some("moved".to_string())), "moved");
+        assert_eq!(error_message(-5, &None), "kafka error (error code -5)");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::kafka::client::{Config, ReadOnlyError};
+use crate::kafka::protocol::{self, BrokerConnection, Decoder, Encoder, ProtocolError};
+use crate::kafka::types::{ClientQuotaEntry, QuotaEntityEntry, QuotaEntityType, QuotaKey};
+use rdkafka::error::KafkaError;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+const MATCH_EXACT: i8 = 0;
+const MATCH_DEFAULT: i8 = 1;
+
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error("invalid quota: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityName {
+    Named(String),
+    /// The default entity, applying to every user or client id without a quota of its own.
+    Default,
+}
+
+/// The user and client id a quota applies to, a missing component matches any when describing.
+#[derive(Debug, Clone, Default)]
+pub struct QuotaEntity {
+    pub user: Option<EntityName>,
+    pub client_id: Option<EntityName>,
+}
+
+impl QuotaEntity {
+    fn components(&self) -> Vec<(QuotaEntityType, &EntityName)> {
+        [(QuotaEntityType::User, &self.user), (QuotaEntityType::ClientId, &self.client_id)]
+            .into_iter()
+            .filter_map(|(entity_type, name)| name.as_ref().map(|name| (entity_type, name)))
+            .collect()
+    }
+}
+
+/// Describes the quotas of every entity matching the filter, all quotas for an empty filter.
+pub fn describe_quotas(config: &Config, filter: &QuotaEntity) -> Result<Vec<ClientQuotaEntry>, QuotaError> {
+    let components = filter.components();
+    let mut body = Encoder::new();
+    body.array_len(Some(components.len()));
+    for (entity_type, name) in &components {
+        body.string(entity_type.name());
+        match name {
+            EntityName::Named(name) => {
+                body.i8(MATCH_EXACT);
+                body.nullable_string(Some(name));
+            }
+            EntityName::Default => {
+                body.i8(MATCH_DEFAULT);
+                body.nullable_string(None);
+            }
+        }
+    }
+    body.bool(false);
+
+    let mut connection = BrokerConnection::controller(config)?;
+    let response = connection.request(protocol::DESCRIBE_CLIENT_QUOTAS, 0, false, body)?;
+    let mut decoder = Decoder::new(&response);
+    decoder.i32()?;
+    let error_code = decoder.i16()?;
+    protocol::check_error(error_code, decoder.nullable_string()?)?;
+
+    let mut quotas = Vec::new();
+    for _ in 0..decoder.array_len()? {
+        let mut entity = Vec::new();
+        for _ in 0..decoder.array_len()? {
+            let entity_type = decoder.string()?;
+            let name = decoder.nullable_string()?;
+            entity.push(QuotaEntityEntry { entity_type: entity_type_from(&entity_type)?, name });
+        }
+        entity.sort_by_key(|e| e.entity_type);
+        let mut values = BTreeMap::new();
+        for _ in 0..decoder.array_len()? {
+            let key = decoder.string()?;
+            values.insert(key, decoder.f64()?);
+        }
+        quotas.push(ClientQuotaEntry { entity, quotas: values });
+    }
+    quotas.sort_by_key(|q| entity_label(&q.entity));
+    Ok(quotas)
+}
+
+/// Sets and removes quotas of a single entity, leaving its other quotas untouched.
+pub fn alter_quotas(
+    config: &Config,
+    entity: &QuotaEntity,
+    set: &[(QuotaKey, f64)],
+    remove: &[QuotaKey],
+) -> Result<(), QuotaError> {
+    config.check_writable("alter quotas")?;
+    let components = entity.components();
+    if components.is_empty() {
+        return Err(QuotaError::Invalid("a user or client id is required".to_string()));
+    }
+    if set.is_empty() && remove.is_empty() {
+        return Err(QuotaError::Invalid("no quota to set or remove".to_string()));
+    }
+
+    let mut body = Encoder::new();
+    body.array_len(Some(1));
+    body.array_len(Some(components.len()));
+    for (entity_type, name) in &components {
+        body.string(entity_type.name());
+        body.nullable_string(match name {
+            EntityName::Named(name) => Some(name),
+            EntityName::Default => None,
+        });
+    }
+    body.array_len(Some(set.len() + remove.len()));
+    for (key, value) in set {
+        body.string(key.name());
+        body.f64(*value);
+        body.bool(false);
+    }
+    for key in remove {
+        body.string(key.name());
+        body.f64(0.0);
+        body.bool(true);
+    }
+    body.bool(false);
+
+    let mut connection = BrokerConnection::controller(config)?;
+    let response = connection.request(protocol::ALTER_CLIENT_QUOTAS, 0, false, body)?;
+    let mut decoder = Decoder::new(&response);
+    decoder.i32()?;
+    for _ in 0..decoder.array_len()? {
+        let error_code = decoder.i16()?;
+        let error_message = decoder.nullable_string()?;
+        for _ in 0..decoder.array_len()? {
+            decoder.string()?;
+            decoder.nullable_string()?;
+        }
+        protocol::check_error(error_code, error_message)?;
+    }
+    Ok(())
+}
+
+/// Formats an entity the way `kafka-configs` does, as in `user=alice, client-id=<default>`.
+pub fn entity_label(entity: &[QuotaEntityEntry]) -> String {
+    entity.iter()
+        .map(|e| format!("{}={}", e.entity_type.name(), e.name.as_deref().unwrap_or("<default>")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn entity_type_from(name: &str) -> Result<QuotaEntityType, QuotaError> {
+    match name {
+        "user" => Ok(QuotaEntityType::User),
+        "client-id" => Ok(QuotaEntityType::ClientId),
+        "ip" => Ok(QuotaEntityType::Ip),
+        other => Err(QuotaError::Invalid(format!("unsupported entity type {other}"))),
+    }
+}
@@ -0,0 +1,565 @@
+use crate::kafka::broker::describe_cluster;
+use crate::kafka::client::{create_base_client, Config, ReadOnlyError, WriteError};
+use crate::kafka::health::{partition_placements, PartitionPlacement};
+use crate::kafka::native::{self, AlterConfigOp, ConfigAlteration};
+use crate::kafka::protocol::{self, BrokerConnection, Decoder, Encoder, ProtocolError};
+use crate::kafka::types::{
+    PartitionReplicasEntry, PartitionResultEntry, ReassignmentPlan, ReassignmentProposalEntry, ReassignmentStatus,
+    ReassignmentStatusEntry,
+};
+use itertools::Itertools;
+use rdkafka::bindings as rdsys;
+use rdkafka::consumer::Consumer;
+use rdkafka::error::KafkaError;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use thiserror::Error;
+
+const PLAN_VERSION: i32 = 1;
+const ANY_LOG_DIR: &str = "any";
+
+const LEADER_THROTTLED_RATE: &str = "leader.replication.throttled.rate";
+const FOLLOWER_THROTTLED_RATE: &str = "follower.replication.throttled.rate";
+const LEADER_THROTTLED_REPLICAS: &str = "leader.replication.throttled.replicas";
+const FOLLOWER_THROTTLED_REPLICAS: &str = "follower.replication.throttled.replicas";
+
+// Target replicas of a partition, `None` cancels its ongoing reassignment.
+type PartitionTarget<'a> = (i32, Option<&'a [i32]>);
+
+#[derive(Error, Debug)]
+pub enum ReassignError {
+    #[error(transparent)]
+    Kafka(#[from] KafkaError),
+    #[error(transparent)]
+    ReadOnly(#[from] ReadOnlyError),
+    #[error(transparent)]
+    Protocol(#[from] ProtocolError),
+    #[error("invalid plan: {0}")]
+    InvalidPlan(String),
+    #[error("failed to change replication throttle: {0}")]
+    Throttle(WriteError),
+}
+
+/// A broker the plan may place replicas on.
+#[derive(Debug, Clone)]
+pub(crate) struct TargetBroker {
+    pub(crate) id: i32,
+    pub(crate) rack: Option<String>,
+}
+
+/// Generates a plan spreading the replicas of the topics evenly over the given brokers, all brokers when empty.
+///
+/// Only partitions whose assignment changes are part of the plan, the proposal also carries their
+/// current assignment so the plan can be rolled back.
+pub fn plan(config: &Config, topics: &[String], brokers: &[i32]) -> Result<ReassignmentProposalEntry, ReassignError> {
+    let metadata = create_base_client(config)
+        .fetch_metadata(None, config.timeout)?;
+
+    if let Some(topic) = topics.iter().find(|t| !metadata.topics().iter().any(|m| m.name() == t.as_str())) {
+        return Err(ReassignError::InvalidPlan(format!("unknown topic {topic}")));
+    }
+    let cluster_brokers = metadata.brokers().iter().map(|b| b.id()).collect::<Vec<_>>();
+    if let Some(broker) = brokers.iter().find(|b| !cluster_brokers.contains(b)) {
+        return Err(ReassignError::InvalidPlan(format!("unknown broker {broker}")));
+    }
+
+    let racks = broker_racks(config);
+    let targets = if brokers.is_empty() { &cluster_brokers } else { brokers }
+        .iter()
+        .unique()
+        .map(|id| TargetBroker { id: *id, rack: racks.get(id).cloned().flatten() })
+        .collect::<Vec<_>>();
+    let partitions = partition_placements(&metadata)
+        .into_iter()
+        .filter(|p| topics.contains(&p.topic))
+        .collect::<Vec<_>>();
+
+    let proposed = generate_plan(&partitions, &targets)?;
+    let current = partitions.iter()
+        .filter(|p| proposed.partitions.iter().any(|m| m.topic == p.topic && m.partition == p.partition))
+        .sorted_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)))
+        .map(|p| replicas_entry(&p.topic, p.partition, p.replicas.clone()))
+        .collect();
+
+    Ok(ReassignmentProposalEntry {
+        current: ReassignmentPlan { version: PLAN_VERSION, partitions: current },
+        proposed,
+    })
+}
+
+// Without the racks the plan is still balanced, only not rack aware.
+fn broker_racks(config: &Config) -> HashMap<i32, Option<String>> {
+    match describe_cluster(config) {
+        Ok(cluster) => cluster.brokers.into_iter().map(|b| (b.id, b.rack)).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to describe broker racks, planning without them: {e}");
+            HashMap::new()
+        }
+    }
+}
+
+/// Assigns the replicas of every partition to the target brokers, keeping the replication factor.
+///
+/// Replicas stay where they are as long as their broker is a target, holds no more than its share
+/// of replicas and the partition is not concentrated in its rack. Missing replicas go to the least
+/// loaded broker, preferring racks the partition is not in yet. Preferred leaders are then spread
+/// the same way.
+pub(crate) fn generate_plan(partitions: &[PartitionPlacement], brokers: &[TargetBroker]) -> Result<ReassignmentPlan, ReassignError> {
+    if brokers.is_empty() {
+        return Err(ReassignError::InvalidPlan("no target brokers".to_string()));
+    }
+    let racks = brokers.iter().map(|b| (b.id, b.rack.as_deref())).collect::<HashMap<_, _>>();
+    let rack_count = racks.values().collect::<HashSet<_>>().len();
+    let rack_usage = |replicas: &[i32], rack: Option<&str>| replicas.iter()
+        .filter(|r| racks.get(r) == Some(&rack))
+        .count();
+
+    let partitions = partitions.iter()
+        .sorted_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)))
+        .collect::<Vec<_>>();
+    let total_replicas = partitions.iter().map(|p| p.replicas.len()).sum::<usize>();
+    let capacity = total_replicas.div_ceil(brokers.len());
+    let mut load = brokers.iter().map(|b| (b.id, 0)).collect::<HashMap<_, _>>();
+
+    let mut assignments = Vec::with_capacity(partitions.len());
+    for p in &partitions {
+        let replication_factor = p.replicas.len();
+        if replication_factor > brokers.len() {
+            return Err(ReassignError::InvalidPlan(format!(
+                "{}-{} has {replication_factor} replicas but only {} target brokers",
+                p.topic, p.partition, brokers.len(),
+            )));
+        }
+        let per_rack = replication_factor.div_ceil(rack_count);
+
+        let mut replicas = Vec::with_capacity(replication_factor);
+        for replica in &p.replicas {
+            let Some(rack) = racks.get(replica) else { continue };
+            if load[replica] < capacity && rack_usage(&replicas, *rack) < per_rack {
+                replicas.push(*replica);
+                *load.get_mut(replica).unwrap() += 1;
+            }
+        }
+        while replicas.len() < replication_factor {
+            let next = brokers.iter()
+                .filter(|b| !replicas.contains(&b.id))
+                .min_by_key(|b| (
+                    rack_usage(&replicas, b.rack.as_deref()) >= per_rack,
+                    load[&b.id] >= capacity,
+                    load[&b.id],
+                    b.id,
+                ))
+                .expect("fewer replicas than target brokers")
+                .id;
+            replicas.push(next);
+            *load.get_mut(&next).unwrap() += 1;
+        }
+        assignments.push((p, replicas));
+    }
+
+    let leader_capacity = partitions.len().div_ceil(brokers.len());
+    let mut leaders = brokers.iter().map(|b| (b.id, 0)).collect::<HashMap<_, _>>();
+    for (_, replicas) in assignments.iter_mut() {
+        let Some(preferred) = replicas.first() else { continue };
+        if leaders[preferred] >= leader_capacity {
+            let (index, _) = replicas.iter()
+                .enumerate()
+                .min_by_key(|(_, r)| leaders[*r])
+                .expect("replicas are not empty");
+            let leader = replicas.remove(index);
+            replicas.insert(0, leader);
+        }
+        *leaders.get_mut(&replicas[0]).unwrap() += 1;
+    }
+
+    Ok(ReassignmentPlan {
+        version: PLAN_VERSION,
+        partitions: assignments.into_iter()
+            .filter(|(p, replicas)| p.replicas != *replicas)
+            .map(|(p, replicas)| replicas_entry(&p.topic, p.partition, replicas))
+            .collect(),
+    })
+}
+
+/// Starts the reassignments of the plan, throttling replication to `throttle` bytes per second when given.
+pub fn execute(config: &Config, plan: &ReassignmentPlan, throttle: Option<u64>) -> Result<Vec<PartitionResultEntry>, ReassignError> {
+    config.check_writable("reassign partitions")?;
+    validate(plan)?;
+    if let Some(rate) = throttle {
+        let metadata = create_base_client(config)
+            .fetch_metadata(None, config.timeout)?;
+        set_throttle(config, plan, &partition_placements(&metadata), rate)?;
+    }
+
+    let mut connection = BrokerConnection::controller(config)?;
+    let partitions = plan.partitions.iter()
+        .map(|p| (p.topic.as_str(), p.partition, Some(p.replicas.as_slice())))
+        .collect::<Vec<_>>();
+    alter_reassignments(&mut connection, config, &partitions)
+}
+
+/// Compares the plan with the ongoing reassignments and the current assignment of its partitions.
+pub fn status(config: &Config, plan: &ReassignmentPlan) -> Result<Vec<ReassignmentStatusEntry>, ReassignError> {
+    let mut connection = BrokerConnection::controller(config)?;
+    let mut topics: BTreeMap<&str, Vec<i32>> = BTreeMap::new();
+    plan.partitions.iter().for_each(|p| topics.entry(&p.topic).or_default().push(p.partition));
+    let mut ongoing = list_reassignments(&mut connection, config, Some(&topics))?
+        .into_iter()
+        .map(|e| ((e.topic.clone(), e.partition), e))
+        .collect::<HashMap<_, _>>();
+
+    let metadata = create_base_client(config)
+        .fetch_metadata(None, config.timeout)?;
+    let current = partition_placements(&metadata)
+        .into_iter()
+        .map(|p| ((p.topic, p.partition), p.replicas))
+        .collect::<HashMap<_, _>>();
+
+    Ok(plan.partitions.iter()
+        .map(|p| {
+            let key = (p.topic.clone(), p.partition);
+            if let Some(entry) = ongoing.remove(&key) {
+                return entry;
+            }
+            let replicas = current.get(&key).cloned().unwrap_or_default();
+            ReassignmentStatusEntry {
+                topic: p.topic.clone(),
+                partition: p.partition,
+                status: if replicas == p.replicas { ReassignmentStatus::Completed } else { ReassignmentStatus::NotApplied },
+                replicas,
+                adding: vec![],
+                removing: vec![],
+            }
+        })
+        .collect())
+}
+
+/// Cancels the ongoing reassignments of the plan's partitions, or every ongoing reassignment without a plan.
+pub fn cancel(config: &Config, plan: Option<&ReassignmentPlan>) -> Result<Vec<PartitionResultEntry>, ReassignError> {
+    config.check_writable("cancel reassignments")?;
+    let mut connection = BrokerConnection::controller(config)?;
+    let partitions = match plan {
+        Some(plan) => plan.partitions.iter()
+            .map(|p| (p.topic.clone(), p.partition))
+            .collect::<Vec<_>>(),
+        None => list_reassignments(&mut connection, config, None)?
+            .into_iter()
+            .map(|e| (e.topic, e.partition))
+            .collect(),
+    };
+    if partitions.is_empty() {
+        return Ok(vec![]);
+    }
+    let partitions = partitions.iter()
+        .map(|(topic, partition)| (topic.as_str(), *partition, None))
+        .collect::<Vec<_>>();
+    alter_reassignments(&mut connection, config, &partitions)
+}
+
+/// Removes the replication throttles set by [`execute`] from every broker and the plan's topics.
+pub fn remove_throttle(config: &Config, plan: &ReassignmentPlan) -> Result<(), ReassignError> {
+    config.check_writable("remove replication throttles")?;
+    let metadata = create_base_client(config)
+        .fetch_metadata(None, config.timeout)?;
+    let mut alterations = metadata.brokers()
+        .iter()
+        .map(|b| ConfigAlteration {
+            resource_type: rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+            name: b.id().to_string(),
+            entries: vec![
+                (LEADER_THROTTLED_RATE.to_string(), AlterConfigOp::Delete),
+                (FOLLOWER_THROTTLED_RATE.to_string(), AlterConfigOp::Delete),
+            ],
+        })
+        .collect::<Vec<_>>();
+    alterations.extend(plan.partitions.iter().map(|p| &p.topic).unique().map(|topic| ConfigAlteration {
+        resource_type: rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+        name: topic.clone(),
+        entries: vec![
+            (LEADER_THROTTLED_REPLICAS.to_string(), AlterConfigOp::Delete),
+            (FOLLOWER_THROTTLED_REPLICAS.to_string(), AlterConfigOp::Delete),
+        ],
+    }));
+    native::incremental_alter_configs(config, &alterations).map_err(ReassignError::Throttle)
+}
+
+fn validate(plan: &ReassignmentPlan) -> Result<(), ReassignError> {
+    if plan.partitions.is_empty() {
+        return Err(ReassignError::InvalidPlan("no partitions to reassign".to_string()));
+    }
+    let mut seen = HashSet::new();
+    for p in &plan.partitions {
+        let name = format!("{}-{}", p.topic, p.partition);
+        if !seen.insert((&p.topic, p.partition)) {
+            return Err(ReassignError::InvalidPlan(format!("{name} is listed more than once")));
+        }
+        if p.replicas.is_empty() {
+            return Err(ReassignError::InvalidPlan(format!("{name} has no replicas")));
+        }
+        if p.replicas.iter().unique().count() != p.replicas.len() {
+            return Err(ReassignError::InvalidPlan(format!("{name} has duplicate replicas {:?}", p.replicas)));
+        }
+        if p.log_dirs.iter().any(|d| d != ANY_LOG_DIR) {
+            return Err(ReassignError::InvalidPlan(format!("{name} sets log dirs, moving replicas between log dirs is not supported")));
+        }
+    }
+    Ok(())
+}
+
+// Throttles the current replicas as leaders and the new ones as followers, like `kafka-reassign-partitions` does.
+fn set_throttle(config: &Config, plan: &ReassignmentPlan, current: &[PartitionPlacement], rate: u64) -> Result<(), ReassignError> {
+    let current = current.iter()
+        .map(|p| ((p.topic.as_str(), p.partition), p.replicas.as_slice()))
+        .collect::<HashMap<_, _>>();
+
+    let mut brokers = BTreeSet::new();
+    let mut topics: BTreeMap<&str, (Vec<String>, Vec<String>)> = BTreeMap::new();
+    for p in &plan.partitions {
+        let existing = current.get(&(p.topic.as_str(), p.partition)).copied().unwrap_or_default();
+        brokers.extend(existing.iter().chain(p.replicas.iter()).copied());
+        let (leaders, followers) = topics.entry(&p.topic).or_default();
+        leaders.extend(existing.iter().map(|b| format!("{}:{b}", p.partition)));
+        followers.extend(p.replicas.iter().filter(|b| !existing.contains(b)).map(|b| format!("{}:{b}", p.partition)));
+    }
+
+    let mut alterations = brokers.into_iter()
+        .map(|broker| ConfigAlteration {
+            resource_type: rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+            name: broker.to_string(),
+            entries: vec![
+                (LEADER_THROTTLED_RATE.to_string(), AlterConfigOp::Set(rate.to_string())),
+                (FOLLOWER_THROTTLED_RATE.to_string(), AlterConfigOp::Set(rate.to_string())),
+            ],
+        })
+        .collect::<Vec<_>>();
+    alterations.extend(topics.into_iter().map(|(topic, (leaders, followers))| ConfigAlteration {
+        resource_type: rdsys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+        name: topic.to_string(),
+        entries: [(LEADER_THROTTLED_REPLICAS, leaders), (FOLLOWER_THROTTLED_REPLICAS, followers)]
+            .into_iter()
+            .filter(|(_, replicas)| !replicas.is_empty())
+            .map(|(name, replicas)| (name.to_string(), AlterConfigOp::Set(replicas.join(","))))
+            .collect(),
+    }));
+    native::incremental_alter_configs(config, &alterations).map_err(ReassignError::Throttle)
+}
+
+// Sends AlterPartitionReassignments, partitions without replicas have their reassignment cancelled.
+fn alter_reassignments(
+    connection: &mut BrokerConnection,
+    config: &Config,
+    partitions: &[(&str, i32, Option<&[i32]>)],
+) -> Result<Vec<PartitionResultEntry>, ReassignError> {
+    let mut topics: BTreeMap<&str, Vec<PartitionTarget>> = BTreeMap::new();
+    partitions.iter().for_each(|(topic, partition, replicas)| topics.entry(topic).or_default().push((*partition, *replicas)));
+
+    let mut body = Encoder::new();
+    body.i32(config.timeout.as_millis() as i32);
+    body.compact_array_len(Some(topics.len()));
+    for (topic, partitions) in &topics {
+        body.compact_string(topic);
+        body.compact_array_len(Some(partitions.len()));
+        for (partition, replicas) in partitions {
+            body.i32(*partition);
+            body.compact_array_len(replicas.map(|r| r.len()));
+            replicas.unwrap_or_default().iter().for_each(|r| body.i32(*r));
+            body.tagged_fields();
+        }
+        body.tagged_fields();
+    }
+    body.tagged_fields();
+
+    let response = connection.request(protocol::ALTER_PARTITION_REASSIGNMENTS, 0, true, body)?;
+    let mut decoder = Decoder::new(&response);
+    decoder.i32()?;
+    let error_code = decoder.i16()?;
+    protocol::check_error(error_code, decoder.compact_nullable_string()?)?;
+
+    let mut results = Vec::new();
+    for _ in 0..decoder.compact_array_len()? {
+        let topic = decoder.compact_string()?;
+        for _ in 0..decoder.compact_array_len()? {
+            let partition = decoder.i32()?;
+            let error_code = decoder.i16()?;
+            let error_message = decoder.compact_nullable_string()?;
+            decoder.skip_tagged_fields()?;
+            results.push(PartitionResultEntry {
+                topic: topic.clone(),
+                partition,
+                error: (error_code != 0).then(|| protocol::error_message(error_code, &error_message)),
+            });
+        }
+        decoder.skip_tagged_fields()?;
+    }
+    Ok(results)
+}
+
+// Sends ListPartitionReassignments for the given partitions, or for all partitions when `None`.
+fn list_reassignments(
+    connection: &mut BrokerConnection,
+    config: &Config,
+    topics: Option<&BTreeMap<&str, Vec<i32>>>,
+) -> Result<Vec<ReassignmentStatusEntry>, ReassignError> {
+    let mut body = Encoder::new();
+    body.i32(config.timeout.as_millis() as i32);
+    body.compact_array_len(topics.map(|t| t.len()));
+    for (topic, partitions) in topics.into_iter().flatten() {
+        body.compact_string(topic);
+        body.compact_array_len(Some(partitions.len()));
+        partitions.iter().for_each(|p| body.i32(*p));
+        body.tagged_fields();
+    }
+    body.tagged_fields();
+
+    let response = connection.request(protocol::LIST_PARTITION_REASSIGNMENTS, 0, true, body)?;
+    let mut decoder = Decoder::new(&response);
+    decoder.i32()?;
+    let error_code = decoder.i16()?;
+    protocol::check_error(error_code, decoder.compact_nullable_string()?)?;
+
+    let mut ongoing = Vec::new();
+    for _ in 0..decoder.compact_array_len()? {
+        let topic = decoder.compact_string()?;
+        for _ in 0..decoder.compact_array_len()? {
+            let partition = decoder.i32()?;
+            let replicas = decoder.compact_i32_array()?;
+            let adding = decoder.compact_i32_array()?;
+            let removing = decoder.compact_i32_array()?;
+            decoder.skip_tagged_fields()?;
+            ongoing.push(ReassignmentStatusEntry {
+                topic: topic.clone(),
+                partition,
+                status: ReassignmentStatus::InProgress,
+                replicas,
+                adding,
+                removing,
+            });
+        }
+        decoder.skip_tagged_fields()?;
+    }
+    Ok(ongoing)
+}
+
+fn replicas_entry(topic: &str, partition: i32, replicas: Vec<i32>) -> PartitionReplicasEntry {
+    PartitionReplicasEntry {
+        topic: topic.to_string(),
+        partition,
+        replicas,
+        log_dirs: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placement(topic: &str, partition: i32, replicas: &[i32]) -> PartitionPlacement {
+        PartitionPlacement {
+            topic: topic.to_string(),
+            partition,
+            leader: replicas[0],
+            replicas: replicas.to_vec(),
+            isr: replicas.to_vec(),
+        }
+    }
+
+    fn brokers(racks: &[(i32, Option<&str>)]) -> Vec<TargetBroker> {
+        racks.iter().map(|(id, rack)| TargetBroker { id: *id, rack: rack.map(str::to_string) }).collect()
+    }
+
+    fn replica_counts(partitions: &[PartitionPlacement], plan: &ReassignmentPlan) -> BTreeMap<i32, usize> {
+        let mut counts = BTreeMap::new();
+        for p in partitions {
+            let replicas = plan.partitions.iter()
+                .find(|m| m.topic == p.topic && m.partition == p.partition)
+                .map(|m| &m.replicas)
+                .unwrap_or(&p.replicas);
+            replicas.iter().for_each(|r| *counts.entry(*r).or_default() += 1);
+        }
+        counts
+    }
+
+    #[test]
+    fn balanced_topic_is_left_alone() {
+        let partitions = vec![
+            placement("orders", 0, &[1, 2]),
+            placement("orders", 1, &[2, 3]),
+            placement("orders", 2, &[3, 1]),
+        ];
+
+        let plan = generate_plan(&partitions, &brokers(&[(1, None), (2, None), (3, None)])).unwrap();
+
+        assert!(plan.partitions.is_empty());
+    }
+
+    #[test]
+    fn moves_replicas_onto_new_brokers() {
+        let partitions = vec![
+            placement("orders", 0, &[1, 2]),
+            placement("orders", 1, &[2, 1]),
+            placement("orders", 2, &[1, 2]),
+            placement("orders", 3, &[2, 1]),
+        ];
+
+        let plan = generate_plan(&partitions, &brokers(&[(1, None), (2, None), (3, None), (4, None)])).unwrap();
+
+        assert_eq!(replica_counts(&partitions, &plan), BTreeMap::from([(1, 2), (2, 2), (3, 2), (4, 2)]));
+        assert_eq!(plan.partitions[0].topic, "orders");
+        assert_eq!(plan.version, 1);
+    }
+
+    #[test]
+    fn moves_replicas_off_removed_brokers() {
+        let partitions = vec![
+            placement("orders", 0, &[1, 3]),
+            placement("orders", 1, &[3, 2]),
+        ];
+
+        let plan = generate_plan(&partitions, &brokers(&[(1, None), (2, None)])).unwrap();
+
+        assert!(plan.partitions.iter().all(|p| !p.replicas.contains(&3)));
+        assert_eq!(replica_counts(&partitions, &plan), BTreeMap::from([(1, 2), (2, 2)]));
+        let leaders = plan.partitions.iter().map(|p| p.replicas[0]).collect::<BTreeSet<_>>();
+        assert_eq!(leaders, BTreeSet::from([1, 2]));
+    }
+
+    #[test]
+    fn spreads_replicas_across_racks() {
+        let partitions = vec![placement("orders", 0, &[1, 2])];
+
+        let plan = generate_plan(
+            &partitions,
+            &brokers(&[(1, Some("a")), (2, Some("a")), (3, Some("b")), (4, Some("b"))]),
+        ).unwrap();
+
+        assert_eq!(plan.partitions.len(), 1);
+        assert_eq!(plan.partitions[0].replicas[0], 1);
+        assert!([3, 4].contains(&plan.partitions[0].replicas[1]));
+    }
+
+    #[test]
+    fn rejects_replication_factor_above_broker_count() {
+        let partitions = vec![placement("orders", 0, &[1, 2, 3])];
+
+        let result = generate_plan(&partitions, &brokers(&[(1, None), (2, None)]));
+
+        assert!(matches!(result, Err(ReassignError::InvalidPlan(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_plans() {
+        let plan = |replicas: Vec<i32>, log_dirs: Vec<&str>| ReassignmentPlan {
+            version: 1,
+            partitions: vec![PartitionReplicasEntry {
+                topic: "orders".to_string(),
+                partition: 0,
+                replicas,
+                log_dirs: log_dirs.into_iter().map(str::to_string).collect(),
+            }],
+        };
+
+        assert!(validate(&plan(vec![1, 2], vec!["any", "any"])).is_ok());
+        assert!(validate(&plan(vec![], vec![])).is_err());
+        assert!(validate(&plan(vec![1, 1], vec![])).is_err());
+        assert!(validate(&plan(vec![1, 2], vec!["/data", "any"])).is_err());
+    }
+}
@@ -1,19 +1,22 @@
 use crate::kafka;
-use crate::kafka::client::{create_base_client, Config, IamClientContext};
-use crate::kafka::types::ListTopicEntry;
+use crate::kafka::client::{create_base_client, Config, IamClientContext, WriteError};
+use crate::kafka::native;
+use crate::kafka::broker;
+use crate::kafka::types::{DeleteRecordsEntry, DescribeConfigEntry, ListTopicEntry, TopicPartitionEntry};
 use itertools::Itertools;
-use rdkafka::admin::{AdminOptions, TopicResult};
+use rdkafka::admin::{AdminOptions, ResourceSpecifier, TopicResult};
+use rdkafka::bindings as rdsys;
 use rdkafka::consumer::{BaseConsumer, Consumer};
 use rdkafka::error::KafkaError;
 use rdkafka::metadata::MetadataTopic;
+use rdkafka::types::{RDKafkaErrorCode, RDKafkaRespErr};
+use rdkafka::{Offset, TopicPartitionList};
 use std::collections::HashMap;
 use std::time::Duration;
 
-pub fn list_topics(config: &Config) -> Vec<ListTopicEntry> {
+pub fn list_topics(config: &Config) -> Result<Vec<ListTopicEntry>, KafkaError> {
     let client = create_base_client(config);
-    let metadata = client
-        .fetch_metadata(None, config.timeout)
-        .expect("Failed to fetch metadata");
+    let metadata = client.fetch_metadata(None, config.timeout)?;
 
     let topics_metadata = metadata.topics();
     let topic_offsets = fetch_topics_offsets(client, config.timeout, topics_metadata);
@@ -34,18 +37,20 @@ pub fn list_topics(config: &Config) -> Vec<ListTopicEntry> {
             replication_factor: topic.partitions().iter()
                 .flat_map(|partition| partition.replicas().iter())
                 .max()
-                .map(|v| *v)
-                .unwrap_or_else(|| 0),
+                .copied()
+                .unwrap_or(0),
             message_count,
             size: 0,
         }
     })
         .collect::<Vec<_>>();
     topics.sort_by(|a, b| a.name.cmp(&b.name));
-    topics
+    Ok(topics)
 }
 
-fn fetch_topics_offsets(client: BaseConsumer<IamClientContext>, timeout: Duration, topics_metadata: &[MetadataTopic]) -> HashMap<&str, Vec<(i32, (i64, i64))>> {
+type PartitionOffsets = Vec<(i32, (i64, i64))>;
+
+fn fetch_topics_offsets(client: BaseConsumer<IamClientContext>, timeout: Duration, topics_metadata: &[MetadataTopic]) -> HashMap<&str, PartitionOffsets> {
     topics_metadata.iter()
         .flat_map(|topic|
             topic.partitions()
@@ -62,21 +67,161 @@ fn fetch_topics_offsets(client: BaseConsumer<IamClientContext>, timeout: Duratio
     ).into_group_map()
 }
 
-pub fn list_topics_names(config: &Config) -> Vec<String> {
-    let result = create_base_client(config)
-        .fetch_metadata(None, config.timeout);
+pub fn list_topics_names(config: &Config) -> Result<Vec<String>, KafkaError> {
+    let metadata = create_base_client(config)
+        .fetch_metadata(None, config.timeout)?;
 
-    let mut topics = result.expect("Failed to fetch metadata").topics()
+    let mut topics = metadata.topics()
         .iter().map(|topic| topic.name().to_string())
         .collect::<Vec<_>>();
     topics.sort_by_key(|t| t.to_string());
-    topics
+    Ok(topics)
+}
+
+/// The placement of every partition of the topic, with its watermarks.
+pub fn describe_partitions(config: &Config, topic: &str) -> Result<Vec<TopicPartitionEntry>, KafkaError> {
+    let client = create_base_client(config);
+    let metadata = client.fetch_metadata(Some(topic), config.timeout)?;
+    let metadata_topic = metadata.topics()
+        .iter()
+        .find(|t| t.name() == topic && t.error().is_none())
+        .ok_or(KafkaError::MetadataFetch(RDKafkaErrorCode::UnknownTopicOrPartition))?;
+
+    let mut partitions = metadata_topic.partitions()
+        .iter()
+        .map(|p| {
+            let watermarks = client.fetch_watermarks(topic, p.id(), config.timeout).ok();
+            TopicPartitionEntry {
+                partition: p.id(),
+                leader: Some(p.leader()).filter(|leader| *leader >= 0),
+                replicas: p.replicas().to_vec(),
+                isr: p.isr().to_vec(),
+                low_watermark: watermarks.map(|(low, _)| low),
+                high_watermark: watermarks.map(|(_, high)| high),
+            }
+        })
+        .collect::<Vec<_>>();
+    partitions.sort_by_key(|p| p.partition);
+    Ok(partitions)
 }
 
-pub async fn delete_topics(config: &Config, topics: Vec<String>) -> Result<Vec<TopicResult>, KafkaError> {
+/// The config of the topic, every entry with where its value comes from.
+pub async fn describe_configs(config: &Config, topic: &str) -> Result<Vec<DescribeConfigEntry>, KafkaError> {
+    broker::describe_configs(config, ResourceSpecifier::Topic(topic)).await
+}
+
+pub async fn delete_topics(config: &Config, topics: Vec<String>) -> Result<Vec<TopicResult>, WriteError> {
+    config.check_writable("delete topics")?;
     let delete_topics:Vec<&str> = topics.iter()
         .map(|t| t.as_str())
         .collect();
     let admin_options = AdminOptions::new();
-    kafka::client::create_admin_client(config).delete_topics(&delete_topics, &admin_options).await
-}
\ No newline at end of file
+    Ok(kafka::client::create_admin_client(config).delete_topics(&delete_topics, &admin_options).await?)
+}
+
+/// Where to truncate partitions, every record before the resolved offset is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncateTarget {
+    Offset(i64),
+    /// The first offset with a timestamp at or after this one, in milliseconds since the epoch.
+    Timestamp(i64),
+    HighWatermark,
+}
+
+/// Resolves the truncation of the partitions of the topics, all of them when `partitions` is empty,
+/// without deleting anything.
+///
+/// Offsets are clamped to the watermarks, a timestamp after the last record resolves to the high watermark.
+pub fn plan_delete_records(config: &Config, topics: &[String], partitions: &[i32], target: TruncateTarget) -> Result<Vec<DeleteRecordsEntry>, KafkaError> {
+    let client = create_base_client(config);
+    let metadata = client.fetch_metadata(None, config.timeout)?;
+
+    let mut entries = vec![];
+    for topic in topics {
+        let topic_metadata = metadata.topics().iter()
+            .find(|t| t.name() == topic)
+            .ok_or(KafkaError::MetadataFetch(RDKafkaErrorCode::UnknownTopicOrPartition))?;
+        for partition in topic_metadata.partitions().iter().filter(|p| partitions.is_empty() || partitions.contains(&p.id())) {
+            let (low, high) = client.fetch_watermarks(topic, partition.id(), config.timeout)?;
+            entries.push(DeleteRecordsEntry {
+                topic: topic.clone(),
+                partition: partition.id(),
+                low_watermark: low,
+                high_watermark: high,
+                offset: high,
+                error: None,
+            });
+        }
+    }
+
+    match target {
+        TruncateTarget::HighWatermark => {}
+        TruncateTarget::Offset(offset) => entries.iter_mut()
+            .for_each(|e| e.offset = offset.clamp(e.low_watermark, e.high_watermark)),
+        TruncateTarget::Timestamp(timestamp) => {
+            let mut timestamps = TopicPartitionList::new();
+            for entry in &entries {
+                timestamps.add_partition_offset(&entry.topic, entry.partition, Offset::Offset(timestamp))?;
+            }
+            let offsets = client.offsets_for_times(timestamps, config.timeout)?;
+            for entry in entries.iter_mut() {
+                if let Some(Offset::Offset(offset)) = offsets.find_partition(&entry.topic, entry.partition).map(|e| e.offset()) {
+                    entry.offset = offset.clamp(entry.low_watermark, entry.high_watermark);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Deletes the records of a plan from [`plan_delete_records`], partitions with nothing to delete are left alone.
+///
+/// Returns the plan with the new low watermarks as offsets, and the errors of the partitions that failed.
+pub fn delete_records(config: &Config, plan: &[DeleteRecordsEntry]) -> Result<Vec<DeleteRecordsEntry>, WriteError> {
+    config.check_writable("delete records")?;
+    let mut offsets = TopicPartitionList::new();
+    for entry in plan.iter().filter(|e| e.records() > 0) {
+        offsets.add_partition_offset(&entry.topic, entry.partition, Offset::Offset(entry.offset))?;
+    }
+    if offsets.count() == 0 {
+        return Ok(plan.to_vec());
+    }
+
+    let results = native::admin_request(
+        config,
+        rdsys::rd_kafka_admin_op_t::RD_KAFKA_ADMIN_OP_DELETERECORDS,
+        |rk, options, queue| unsafe {
+            let mut delete = [rdsys::rd_kafka_DeleteRecords_new(offsets.ptr())];
+            rdsys::rd_kafka_DeleteRecords(rk, delete.as_mut_ptr(), delete.len(), options, queue);
+            rdsys::rd_kafka_DeleteRecords_destroy(delete[0]);
+        },
+        |event| unsafe {
+            let result = rdsys::rd_kafka_event_DeleteRecords_result(event);
+            let list = rdsys::rd_kafka_DeleteRecords_result_offsets(result);
+            let elements = if list.is_null() { &[] } else { native::as_slice((*list).elems, (*list).cnt as usize) };
+            Ok(elements.iter()
+                .map(|e| {
+                    let topic = native::to_string(e.topic).unwrap_or_default();
+                    let result = if e.err == RDKafkaRespErr::RD_KAFKA_RESP_ERR_NO_ERROR {
+                        Ok(e.offset)
+                    } else {
+                        Err(RDKafkaErrorCode::from(e.err).to_string())
+                    };
+                    ((topic, e.partition), result)
+                })
+                .collect::<HashMap<_, _>>())
+        },
+    )?;
+
+    Ok(plan.iter()
+        .map(|entry| {
+            let mut entry = entry.clone();
+            match results.get(&(entry.topic.clone(), entry.partition)) {
+                Some(Ok(offset)) => entry.offset = *offset,
+                Some(Err(error)) => entry.error = Some(error.clone()),
+                None => {}
+            }
+            entry
+        })
+        .collect())
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ListTopicEntry {
     pub name: String,
     pub partitions: i32,
@@ -7,15 +9,505 @@ pub struct ListTopicEntry {
     pub size: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ListConsumerGroupEntry {
     pub name: String,
     pub state: String,
+    pub members: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ListBrokerEntry {
     pub id: i32,
     pub host: String,
     pub port: i32,
-}
\ No newline at end of file
+    pub rack: Option<String>,
+    pub controller: bool,
+    /// Partitions the broker leads.
+    pub leaders: usize,
+    /// Partition replicas the broker hosts, the ones it leads included.
+    pub replicas: usize,
+    /// Bytes stored in the broker's log dirs, `None` when they could not be described.
+    pub log_dir_size: Option<i64>,
+}
+
+/// Formats a size in bytes with binary units, as in `1.5 GiB`.
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckStepEntry {
+    pub step: String,
+    pub target: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteConsumerGroupEntry {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DescribeBrokerEntry {
+    pub id: i32,
+    pub host: String,
+    pub port: i32,
+    pub rack: Option<String>,
+    pub controller: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DescribeClusterEntry {
+    pub cluster_id: Option<String>,
+    pub controller_id: Option<i32>,
+    pub brokers: Vec<DescribeBrokerEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DescribeConfigEntry {
+    pub name: String,
+    pub value: Option<String>,
+    pub source: String,
+    pub is_default: bool,
+    pub is_read_only: bool,
+    pub is_sensitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HealthIssueKind {
+    Offline,
+    UnderReplicated,
+    BelowMinIsr,
+    NonPreferredLeader,
+    BrokerWithoutLeadership,
+    SkewedTopic,
+}
+
+impl HealthIssueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HealthIssueKind::Offline => "offline",
+            HealthIssueKind::UnderReplicated => "under-replicated",
+            HealthIssueKind::BelowMinIsr => "below-min-isr",
+            HealthIssueKind::NonPreferredLeader => "non-preferred-leader",
+            HealthIssueKind::BrokerWithoutLeadership => "broker-without-leadership",
+            HealthIssueKind::SkewedTopic => "skewed-topic",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthIssueEntry {
+    pub kind: HealthIssueKind,
+    pub topic: Option<String>,
+    pub partition: Option<i32>,
+    pub broker: Option<i32>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReportEntry {
+    pub brokers: usize,
+    pub topics: usize,
+    pub partitions: usize,
+    pub issues: Vec<HealthIssueEntry>,
+}
+
+/// A partition reassignment plan, in the format read and written by `kafka-reassign-partitions`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReassignmentPlan {
+    pub version: i32,
+    pub partitions: Vec<PartitionReplicasEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionReplicasEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub replicas: Vec<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_dirs: Vec<String>,
+}
+
+/// A generated plan along with the current assignment of the partitions it moves, to roll back with.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReassignmentProposalEntry {
+    pub current: ReassignmentPlan,
+    pub proposed: ReassignmentPlan,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReassignmentStatus {
+    Completed,
+    InProgress,
+    /// Neither in progress nor matching the plan, the reassignment failed or was cancelled.
+    NotApplied,
+}
+
+impl ReassignmentStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReassignmentStatus::Completed => "completed",
+            ReassignmentStatus::InProgress => "in-progress",
+            ReassignmentStatus::NotApplied => "not-applied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReassignmentStatusEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub status: ReassignmentStatus,
+    pub replicas: Vec<i32>,
+    pub adding: Vec<i32>,
+    pub removing: Vec<i32>,
+}
+
+/// Outcome of an operation applied to a single partition.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionResultEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ElectionStatus {
+    Elected,
+    /// The partition already had the leader the election would pick.
+    NotNeeded,
+    Failed,
+}
+
+impl ElectionStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ElectionStatus::Elected => "elected",
+            ElectionStatus::NotNeeded => "not-needed",
+            ElectionStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ElectLeaderEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub status: ElectionStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AclResourceType {
+    Any,
+    Topic,
+    Group,
+    Cluster,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AclPatternType {
+    Any,
+    /// Matches literal, wildcard and prefixed patterns that apply to the name, only valid in filters.
+    Match,
+    Literal,
+    Prefixed,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AclOperation {
+    Any,
+    All,
+    Read,
+    Write,
+    Create,
+    Delete,
+    Alter,
+    Describe,
+    ClusterAction,
+    DescribeConfigs,
+    AlterConfigs,
+    IdempotentWrite,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AclPermission {
+    Any,
+    Allow,
+    Deny,
+    Unknown,
+}
+
+macro_rules! acl_names {
+    ($type:ident { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl $type {
+            pub const VARIANTS: &'static [$type] = &[$($type::$variant),+];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($type::$variant => $name),+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $type {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $type::VARIANTS.iter()
+                    .find(|v| v.name().eq_ignore_ascii_case(s) && **v != $type::Unknown)
+                    .copied()
+                    .ok_or_else(|| format!(
+                        "expected one of {}",
+                        $type::VARIANTS.iter()
+                            .filter(|v| **v != $type::Unknown)
+                            .map(|v| v.name())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ))
+            }
+        }
+    };
+}
+
+acl_names!(AclResourceType {
+    Any => "any",
+    Topic => "topic",
+    Group => "group",
+    Cluster => "cluster",
+    Unknown => "unknown",
+});
+
+acl_names!(AclPatternType {
+    Any => "any",
+    Match => "match",
+    Literal => "literal",
+    Prefixed => "prefixed",
+    Unknown => "unknown",
+});
+
+acl_names!(AclOperation {
+    Any => "any",
+    All => "all",
+    Read => "read",
+    Write => "write",
+    Create => "create",
+    Delete => "delete",
+    Alter => "alter",
+    Describe => "describe",
+    ClusterAction => "cluster-action",
+    DescribeConfigs => "describe-configs",
+    AlterConfigs => "alter-configs",
+    IdempotentWrite => "idempotent-write",
+    Unknown => "unknown",
+});
+
+acl_names!(AclPermission {
+    Any => "any",
+    Allow => "allow",
+    Deny => "deny",
+    Unknown => "unknown",
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AclBindingEntry {
+    pub resource_type: AclResourceType,
+    pub resource_name: String,
+    pub pattern_type: AclPatternType,
+    pub principal: String,
+    pub host: String,
+    pub operation: AclOperation,
+    pub permission: AclPermission,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AclResultEntry {
+    #[serde(flatten)]
+    pub binding: AclBindingEntry,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuotaEntityType {
+    User,
+    ClientId,
+    Ip,
+}
+
+impl QuotaEntityType {
+    /// The entity type as named by the Kafka protocol.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaEntityType::User => "user",
+            QuotaEntityType::ClientId => "client-id",
+            QuotaEntityType::Ip => "ip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKey {
+    ProducerByteRate,
+    ConsumerByteRate,
+    RequestPercentage,
+    ControllerMutationRate,
+}
+
+impl QuotaKey {
+    pub const VARIANTS: &'static [QuotaKey] = &[
+        QuotaKey::ProducerByteRate,
+        QuotaKey::ConsumerByteRate,
+        QuotaKey::RequestPercentage,
+        QuotaKey::ControllerMutationRate,
+    ];
+
+    /// The quota as named by the Kafka protocol.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuotaKey::ProducerByteRate => "producer_byte_rate",
+            QuotaKey::ConsumerByteRate => "consumer_byte_rate",
+            QuotaKey::RequestPercentage => "request_percentage",
+            QuotaKey::ControllerMutationRate => "controller_mutation_rate",
+        }
+    }
+}
+
+impl std::str::FromStr for QuotaKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QuotaKey::VARIANTS.iter()
+            .find(|k| k.name() == s.replace('-', "_"))
+            .copied()
+            .ok_or_else(|| format!(
+                "expected one of {}",
+                QuotaKey::VARIANTS.iter().map(|k| k.name()).collect::<Vec<_>>().join(", "),
+            ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaEntityEntry {
+    pub entity_type: QuotaEntityType,
+    /// `None` is the default entity, applying to every user or client id without a quota of its own.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientQuotaEntry {
+    pub entity: Vec<QuotaEntityEntry>,
+    pub quotas: std::collections::BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteRecordsEntry {
+    pub topic: String,
+    pub partition: i32,
+    /// The low watermark before the truncation.
+    pub low_watermark: i64,
+    pub high_watermark: i64,
+    /// Records before this offset are deleted, it becomes the new low watermark.
+    pub offset: i64,
+    pub error: Option<String>,
+}
+
+impl DeleteRecordsEntry {
+    /// The number of records the truncation deletes, or deleted once applied.
+    pub fn records(&self) -> i64 {
+        (self.offset - self.low_watermark).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupPartitionLagEntry {
+    pub topic: String,
+    pub partition: i32,
+    /// `None` when the group has not committed an offset for the partition.
+    pub committed: Option<i64>,
+    /// `None` when the watermarks could not be fetched, as for deleted topics.
+    pub high_watermark: Option<i64>,
+    pub lag: Option<i64>,
+    /// The member the partition is assigned to, if any.
+    pub client_id: Option<String>,
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicGroupLagEntry {
+    pub group: String,
+    pub state: String,
+    pub members: usize,
+    /// Partitions of the topic the group committed an offset for.
+    pub partitions: usize,
+    /// Summed over those partitions.
+    pub lag: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicPartitionEntry {
+    pub partition: i32,
+    /// `None` when the partition has no leader.
+    pub leader: Option<i32>,
+    pub replicas: Vec<i32>,
+    pub isr: Vec<i32>,
+    /// `None` when the watermarks could not be fetched.
+    pub low_watermark: Option<i64>,
+    pub high_watermark: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    /// Milliseconds since the epoch, `None` when the record has none.
+    pub timestamp: Option<i64>,
+    /// Keys and values are decoded as UTF-8, invalid sequences replaced.
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub headers: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagePageEntry {
+    pub topic: String,
+    pub partition: i32,
+    pub low_watermark: i64,
+    pub high_watermark: i64,
+    pub messages: Vec<MessageEntry>,
+}
@@ -1,97 +1,289 @@
 use crate::command;
+use crate::command::browse_topic::{BrowseTopicState, DetailScroll};
+use crate::command::describe_group::DescribeGroupState;
+use crate::command::describe_topic::{DescribeTopicState, Tab};
+use crate::command::health::HealthState;
+use crate::command::list_acls::ListAclsState;
+use crate::command::list_brokers::ListBrokersState;
+use crate::command::list_contexts::ListContextsState;
+use crate::command::list_groups::ListGroupsState;
+use crate::command::list_quotas::ListQuotasState;
 use crate::command::list_topics::ListTopicsState;
+use crate::command::tail::{TailBatch, TailState};
+use crate::event::{AppEvent, EventHandler};
+use crate::confirm::{Answer, Confirmation, ConfirmWidget};
+use crate::filter::Filter;
+use crate::keys::{Action, Context, KeyBindings};
+use crate::palette::{CommandLine, Completion, History, Names, SUGGESTIONS};
+use crate::settings::{Cluster, Profile};
 use crate::table::{LocalTable, TableData};
+use crate::theme::Theme;
 use color_eyre::eyre::WrapErr;
 use common::kafka;
+use common::kafka::acl::AclFilter;
 use common::kafka::client::Config;
-use crossterm::event;
+use common::kafka::election::ElectionType;
+use common::kafka::group::OffsetResetTarget;
+use common::kafka::message::StartPosition;
+use common::kafka::quota::QuotaEntity;
+use common::kafka::types::{DeleteRecordsEntry, MessageEntry};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::layout::Flex;
-use ratatui::prelude::{Alignment, Buffer, Color, Constraint, Layout, Modifier, Rect, Style, Stylize, Widget};
+use ratatui::prelude::{Alignment, Buffer, Constraint, Layout, Line, Modifier, Rect, Span, Style, Stylize, Widget};
 use ratatui::style::Styled;
-use ratatui::widgets::{Block, Borders, Cell, Clear, HighlightSpacing, Padding, Paragraph, Row, Table};
+use ratatui::widgets::{Block, Borders, Clear, HighlightSpacing, List, ListItem, Padding, Paragraph, Row, Table, Tabs, Wrap};
 use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::future::Future;
 use std::ops::Deref;
 use std::string::ToString;
-use tui_input::backend::crossterm::EventHandler;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+use tui_input::backend::crossterm::EventHandler as InputEventHandler;
 use tui_input::Input;
 
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 #[derive(Clone)]
 pub struct App<'a> {
     pub(crate) config: Config,
+    /// The cluster of the config.
+    cluster: Cluster,
+    /// Clusters to switch to, by name.
+    contexts: BTreeMap<String, Profile>,
 
     input_mode: InputMode,
     input: Input,
+    history: History,
+    /// Topic and group names for completion, fetched when the command line opens.
+    names: Names,
+    /// The completion being cycled through with Tab.
+    completion: Option<Completion>,
 
     command: Box<Command>,
 
     error: Option<String>,
 
-    pub(crate) table: LocalTable<'a>,
+    pub(crate) table: LocalTable,
     data: TableData<'a>,
+    /// How far the record details next to the table are scrolled.
+    pub(crate) detail_scroll: DetailScroll,
 
     exit: bool,
     popup_type: PopupType,
+    confirmation: Option<Confirmation>,
+
+    sender: UnboundedSender<AppEvent>,
+    /// Background tasks still running.
+    tasks: Vec<Task>,
+    next_task: u64,
+    /// Bumped on every context switch, what tasks of older generations hand back is dropped.
+    generation: u64,
+    /// The task loading the next view, views loaded by older tasks are dropped.
+    view_task: Option<u64>,
+    ticks: usize,
+
+    refresh_interval: Option<Duration>,
+    last_refresh: Instant,
+
+    /// Stops the running tail, if any.
+    tail: Option<Arc<AtomicBool>>,
+
+    keys: KeyBindings,
+    help: bool,
+    theme: Theme,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::upper_case_acronyms)]
 enum InputMode {
+    #[default]
     DEFAULT,
     COMMAND,
-}
-
-impl Default for InputMode {
-    fn default() -> Self { InputMode::DEFAULT }
+    PROMPT(Prompt),
 }
 
 #[derive(Debug, Clone)]
-enum Command {
+pub(crate) enum Command {
     None,
-    ListTopics(ListTopicsState)
+    ListTopics(ListTopicsState),
+    ListBrokers(ListBrokersState),
+    Health(HealthState),
+    ListAcls(ListAclsState),
+    ListQuotas(ListQuotasState),
+    ListGroups(ListGroupsState),
+    DescribeGroup(DescribeGroupState),
+    BrowseTopic(BrowseTopicState),
+    Tail(TailState),
+    ListContexts(ListContextsState),
+    DescribeTopic(DescribeTopicState),
 }
 
 impl Command {
 
-    const CMD_LIST_TOPICS: &'static str = "list-topics";
+    pub(crate) const CMD_LIST_TOPICS: &'static str = "list-topics";
+    pub(crate) const CMD_LIST_BROKERS: &'static str = "list-brokers";
+    pub(crate) const CMD_HEALTH: &'static str = "health";
+    pub(crate) const CMD_LIST_ACLS: &'static str = "list-acls";
+    pub(crate) const CMD_LIST_QUOTAS: &'static str = "list-quotas";
+    pub(crate) const CMD_LIST_GROUPS: &'static str = "list-groups";
+    pub(crate) const CMD_DESCRIBE_GROUP: &'static str = "describe-group";
+    pub(crate) const CMD_BROWSE_TOPIC: &'static str = "browse-topic";
+    pub(crate) const CMD_TAIL: &'static str = "tail";
+    pub(crate) const CMD_CONTEXT: &'static str = "context";
+    pub(crate) const CMD_DESCRIBE_TOPIC: &'static str = "describe-topic";
 
-    fn parse(s: String) -> Option<Command> {
-        match s.as_str() {
-            Command::CMD_LIST_TOPICS => Some(Command::ListTopics(ListTopicsState::default())),
-            _ => None
+    fn parse(line: &str) -> Result<Command, String> {
+        let line = CommandLine::parse(line)?;
+        let prefix = line.option("prefix").map(str::to_string);
+        match line.spec.name {
+            Command::CMD_LIST_TOPICS => Ok(Command::ListTopics(ListTopicsState::with_prefix(prefix))),
+            Command::CMD_LIST_BROKERS => Ok(Command::ListBrokers(ListBrokersState::default())),
+            Command::CMD_HEALTH => Ok(Command::Health(HealthState::default())),
+            Command::CMD_LIST_ACLS => Ok(Command::ListAcls(ListAclsState::default())),
+            Command::CMD_LIST_QUOTAS => Ok(Command::ListQuotas(ListQuotasState::default())),
+            Command::CMD_LIST_GROUPS => Ok(Command::ListGroups(ListGroupsState::with_prefix(prefix))),
+            Command::CMD_DESCRIBE_GROUP => Ok(Command::DescribeGroup(DescribeGroupState::new(line.argument().to_string()))),
+            Command::CMD_DESCRIBE_TOPIC => Ok(Command::DescribeTopic(DescribeTopicState::new(line.argument().to_string()))),
+            Command::CMD_BROWSE_TOPIC => {
+                let partition = line.option("partition")
+                    .map(|p| p.parse::<i32>().map_err(|_| format!("Invalid partition '{p}'")))
+                    .transpose()?
+                    .unwrap_or(0);
+                let start = line.option("from")
+                    .map(command::browse_topic::parse_start)
+                    .transpose()?
+                    .unwrap_or(StartPosition::Beginning);
+                Ok(Command::BrowseTopic(BrowseTopicState::new(line.argument().to_string()).starting_at(partition, start)))
+            }
+            Command::CMD_TAIL => {
+                let topics = line.argument().split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+                Ok(Command::Tail(TailState::new(topics)))
+            }
+            // switching to a given context is not a view, see `App::execute_command`.
+            Command::CMD_CONTEXT => Ok(Command::ListContexts(ListContextsState::default())),
+            name => Err(format!("Unknown command '{name}'")),
         }
     }
 
     fn name(self) -> String {
         match self {
-            Command::ListTopics(_) => Command::CMD_LIST_TOPICS.to_string(),
+            Command::ListTopics(state) => match state.prefix() {
+                Some(prefix) => format!("{} prefix={prefix}", Command::CMD_LIST_TOPICS),
+                None => Command::CMD_LIST_TOPICS.to_string(),
+            },
+            Command::ListBrokers(_) => Command::CMD_LIST_BROKERS.to_string(),
+            Command::Health(_) => Command::CMD_HEALTH.to_string(),
+            Command::ListAcls(_) => Command::CMD_LIST_ACLS.to_string(),
+            Command::ListQuotas(_) => Command::CMD_LIST_QUOTAS.to_string(),
+            Command::ListGroups(state) => match state.prefix() {
+                Some(prefix) => format!("{} prefix={prefix}", Command::CMD_LIST_GROUPS),
+                None => Command::CMD_LIST_GROUPS.to_string(),
+            },
+            Command::DescribeGroup(state) => format!("{} {}", Command::CMD_DESCRIBE_GROUP, state.group()),
+            Command::BrowseTopic(state) => format!("{} {}", Command::CMD_BROWSE_TOPIC, state.topic()),
+            Command::Tail(state) => format!("{} {}", Command::CMD_TAIL, state.topics().join(",")),
+            Command::ListContexts(_) => Command::CMD_CONTEXT.to_string(),
+            Command::DescribeTopic(state) => format!("{} {}", Command::CMD_DESCRIBE_TOPIC, state.topic()),
             Command::None => "none".to_string(),
         }
     }
+
+    /// The key bindings of the view, besides the table and global ones.
+    fn context(&self) -> Option<Context> {
+        match self {
+            Command::ListTopics(_) => Some(Context::Topics),
+            Command::ListGroups(_) => Some(Context::Groups),
+            Command::DescribeGroup(_) => Some(Context::Group),
+            Command::BrowseTopic(_) => Some(Context::Browse),
+            Command::Tail(_) => Some(Context::Tail),
+            Command::ListContexts(_) => Some(Context::Contexts),
+            Command::DescribeTopic(_) => Some(Context::Topic),
+            Command::ListBrokers(_) | Command::Health(_) | Command::ListAcls(_) | Command::ListQuotas(_) | Command::None => None,
+        }
+    }
+
+    /// Whether the other command shows the same data, as when reloading it, so rows can be followed.
+    fn same_view(&self, other: &Command) -> bool {
+        match (self, other) {
+            (Command::BrowseTopic(a), Command::BrowseTopic(b)) => a.same_page(b),
+            (Command::DescribeTopic(a), Command::DescribeTopic(b)) => a.same_tab(b),
+            _ => self.clone().name() == other.clone().name(),
+        }
+    }
 }
 
 impl<'a> App<'a> {
 
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, cluster: Cluster, sender: UnboundedSender<AppEvent>) -> Self {
         Self {
             config,
+            contexts: BTreeMap::from([(cluster.name.clone(), cluster.profile.clone())]),
+            cluster,
             input_mode: Default::default(),
             input: Default::default(),
+            history: History::load(),
+            names: Names::default(),
+            completion: None,
             command: Box::new(Command::None),
             error: None,
             table: LocalTable::new(),
             data: TableData::empty(),
+            detail_scroll: DetailScroll::default(),
             exit: false,
             popup_type: PopupType::SUCCESS,
+            confirmation: None,
+            sender,
+            tasks: vec![],
+            next_task: 0,
+            generation: 0,
+            view_task: None,
+            ticks: 0,
+            refresh_interval: None,
+            last_refresh: Instant::now(),
+            tail: None,
+            keys: KeyBindings::default(),
+            help: false,
+            theme: Theme::default(),
         }
     }
 
+    /// Reloads the current view every `interval`, when given.
+    pub fn refresh_interval(mut self, interval: Option<Duration>) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Remaps the default key bindings.
+    pub fn key_bindings(mut self, mut keys: KeyBindings) -> Self {
+        keys.set_read_only(self.cluster.profile.read_only);
+        self.keys = keys;
+        self
+    }
+
+    /// Draws with the colors of `theme`.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The clusters to switch to, besides the one connected to.
+    pub fn contexts(mut self, contexts: BTreeMap<String, Profile>) -> Self {
+        self.contexts.extend(contexts);
+        self.names.contexts = self.contexts.keys().cloned().collect();
+        self
+    }
+
     /// runs the application's main loop until the user quits
-    pub async fn run(&mut self, terminal: &mut DefaultTerminal) -> color_eyre::Result<()> {
+    pub async fn run(&mut self, terminal: &mut DefaultTerminal, mut events: EventHandler) -> color_eyre::Result<()> {
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()
-                .await
-                .wrap_err("handle events failed")?;
+            match events.next().await {
+                Some(event) => self.handle_event(event).wrap_err("handle events failed")?,
+                None => break,
+            }
         }
         Ok(())
     }
@@ -100,36 +292,71 @@ impl<'a> App<'a> {
         frame.render_stateful_widget(self.clone(), frame.area(), self);
     }
 
-    async fn handle_events(&mut self) -> color_eyre::Result<()> {
-        match event::read()? {
+    fn handle_event(&mut self, event: AppEvent) -> color_eyre::Result<()> {
+        match event {
+            AppEvent::Tick => {
+                self.ticks = self.ticks.wrapping_add(1);
+                if let Some(interval) = self.refresh_interval {
+                    // a failing refresh would reopen its error popup as soon as it is closed.
+                    if !self.is_open() && self.last_refresh.elapsed() >= interval {
+                        self.refresh();
+                    }
+                }
+                Ok(())
+            }
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
+            AppEvent::Input(Event::Key(key_event)) if key_event.kind == KeyEventKind::Press => self
                 .handle_key_event(key_event)
-                .await
                 .wrap_err_with(|| format!("handling key event failed:\n{key_event:#?}")),
-            _ => Ok(())
+            AppEvent::Input(_) => Ok(()),
+            AppEvent::InputError(err) => Err(err.into()),
+            AppEvent::Finished(task, outcome) => {
+                self.finish(task, outcome);
+                Ok(())
+            }
+            AppEvent::Tailed(session, batch) => {
+                self.tailed(session, batch);
+                Ok(())
+            }
         }
     }
 
-    async fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> color_eyre::Result<()> {
         match key_event.code {
             KeyCode::Char('c') if key_event.modifiers == KeyModifiers::CONTROL => self.exit(),
             _ => {
+                if self.help {
+                    if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q'))
+                        || self.keys.action(None, &key_event) == Some(Action::Help) {
+                        self.help = false;
+                    }
+                    return Ok(())
+                }
                 if self.is_open() {
-                    match key_event.code {
-                        KeyCode::Esc => {
-                            self.close()
-                        },
-                        _ => {}
+                    match self.confirmation.as_mut().map(|c| c.handle_key_event(key_event)) {
+                        Some(Answer::Confirmed) => {
+                            let confirmation = self.confirmation.take();
+                            self.close();
+                            if let Some(confirmation) = confirmation {
+                                self.run_confirmed(confirmation.action);
+                            }
+                        }
+                        Some(Answer::Cancelled) => self.close(),
+                        Some(Answer::Pending) => {}
+                        None if key_event.code == KeyCode::Esc => self.close(),
+                        None => {}
                     }
                     return Ok(())
                 }
-                match self.input_mode {
-                    InputMode::COMMAND => {
+                match self.input_mode.clone() {
+                    InputMode::COMMAND | InputMode::PROMPT(_) => {
                         match key_event.code {
                             KeyCode::Esc => {
                                 if !self.has_error() {
+                                    if let InputMode::PROMPT(Prompt::Filter) = self.input_mode {
+                                        self.table.set_filter(None, &self.data);
+                                    }
                                     self.input_mode = InputMode::DEFAULT;
                                     self.input.reset();
                                 }
@@ -137,46 +364,39 @@ impl<'a> App<'a> {
                             },
                             KeyCode::Enter => {
                                 if !self.has_error() {
-                                    self.execute_command();
+                                    match self.input_mode.clone() {
+                                        InputMode::PROMPT(prompt) => self.answer_prompt(prompt),
+                                        _ => self.execute_command(),
+                                    }
+                                }
+                            }
+                            KeyCode::Tab | KeyCode::BackTab if matches!(self.input_mode, InputMode::COMMAND) => {
+                                let line = self.input.value().to_string();
+                                let completion = self.completion.get_or_insert_with(|| Completion::of(&line, &self.names));
+                                if let Some(completed) = completion.cycle(&line, key_event.code == KeyCode::Tab) {
+                                    self.input = Input::new(completed);
                                 }
                             }
+                            KeyCode::Up | KeyCode::Down if matches!(self.input_mode, InputMode::COMMAND) => {
+                                let entry = if key_event.code == KeyCode::Up { self.history.previous() } else { self.history.next() };
+                                self.input = Input::new(entry.unwrap_or_default().to_string());
+                                self.completion = None;
+                            }
                             _ => {
                                 if !self.has_error() {
                                     self.input.handle_event(&Event::Key(key_event));
+                                    self.completion = None;
+                                    // filters apply as they are typed.
+                                    if let InputMode::PROMPT(Prompt::Filter) = self.input_mode {
+                                        self.table.set_filter(Filter::parse(self.input.value()), &self.data);
+                                    }
                                 }
                             }
                         }
                     }
                     InputMode::DEFAULT => {
-                        match key_event.code {
-                            KeyCode::Char('q') => self.exit(),
-                            KeyCode::Char(':') => self.input_mode = InputMode::COMMAND,
-                            _ => {
-                                match self.command.deref() {
-                                    Command::None => {}
-                                    _ => {
-                                        match key_event.code {
-                                            KeyCode::Up => {
-                                                self.table.state.select_previous();
-                                            }
-                                            KeyCode::Down => {
-                                                self.table.state.select_next();
-                                            }
-                                            KeyCode::Char(' ') => {
-                                                self.table.toggle_selected();
-                                            }
-                                            _ => {}
-                                        }
-                                        match self.command.deref() {
-                                            Command::ListTopics(state) => {
-                                                command::list_topics::handle_key_event(key_event, self, state.to_owned())
-                                                    .await;
-                                            }
-                                            Command::None => {}
-                                        }
-                                    }
-                                }
-                            }
+                        if let Some(action) = self.keys.action(self.command.context(), &key_event) {
+                            self.handle_action(action);
                         }
                     }
                 }
@@ -185,26 +405,356 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.exit(),
+            Action::Command => self.open_command_line(),
+            Action::Help => self.help = true,
+            Action::Refresh => self.refresh(),
+            _ if matches!(self.command.deref(), Command::None) => {}
+            Action::Filter => {
+                let text = self.table.filter.as_ref().map(|f| f.text().to_string()).unwrap_or_default();
+                self.prompt(Prompt::Filter);
+                self.input = Input::new(text);
+            }
+            Action::NextMatch if self.table.filter.is_some() => self.table.next_match(),
+            Action::PreviousMatch if self.table.filter.is_some() => self.table.previous_match(),
+            Action::Sort => self.table.next_sort(&self.data),
+            Action::ReverseSort => self.table.reverse_sort(&self.data),
+            // the filter goes first, views go back with Esc.
+            Action::Back if self.table.filter.is_some() => self.table.set_filter(None, &self.data),
+            Action::Up => self.table.state.select_previous(),
+            Action::Down => self.table.state.select_next(),
+            Action::PageUp => self.table.page_up(),
+            Action::PageDown => self.table.page_down(),
+            Action::First => self.table.state.select_first(),
+            Action::Last => self.table.select_last(),
+            Action::Select => self.table.toggle_selected(),
+            action => match self.command.deref() {
+                Command::ListTopics(state) => {
+                    command::list_topics::handle_action(action, self, state.to_owned());
+                }
+                Command::ListGroups(state) => {
+                    command::list_groups::handle_action(action, self, state.to_owned());
+                }
+                Command::DescribeGroup(state) => {
+                    command::describe_group::handle_action(action, self, state.to_owned());
+                }
+                Command::BrowseTopic(state) => {
+                    command::browse_topic::handle_action(action, self, state.to_owned());
+                }
+                Command::Tail(state) => {
+                    command::tail::handle_action(action, self, state.to_owned());
+                }
+                Command::ListContexts(state) => {
+                    command::list_contexts::handle_action(action, self, state.to_owned());
+                }
+                Command::DescribeTopic(state) => {
+                    command::describe_topic::handle_action(action, self, state.to_owned());
+                }
+                Command::ListBrokers(_) | Command::Health(_) | Command::ListAcls(_) | Command::ListQuotas(_) => {}
+                Command::None => {}
+            },
+        }
+    }
+
+    // Opens the command line, fetching the names to complete in the background.
+    fn open_command_line(&mut self) {
+        self.input_mode = InputMode::COMMAND;
+        self.completion = None;
+        self.history.reset();
+        let config = self.config.clone();
+        // completion goes without the names that cannot be listed rather than getting in the way.
+        self.spawn_blocking("Loading names".to_string(), move || {
+            let topics = kafka::topic::list_topics_names(&config).unwrap_or_default();
+            let groups = kafka::group::list(&config, None)
+                .map(|groups| groups.into_iter().map(|g| g.name).collect())
+                .unwrap_or_default();
+            Outcome::Names(Names { topics, groups, contexts: vec![] })
+        });
+    }
+
     fn execute_command(&mut self) {
-        match Command::parse(self.input.to_string()) {
-            Some(cmd) => {
-                let mut cmd_ref = Box::new(cmd);
+        // `context` lists the contexts, `context <name>` switches to it.
+        let switch = CommandLine::parse(self.input.value()).ok()
+            .filter(|line| line.spec.name == Command::CMD_CONTEXT && !line.arguments.is_empty())
+            .map(|line| line.argument().to_string());
+        let result = match switch {
+            Some(name) => self.switch_context(&name),
+            None => Command::parse(self.input.value()).map(|cmd| self.load_view(cmd)),
+        };
+        match result {
+            Ok(()) => {
+                self.history.push(self.input.value());
                 self.input.reset();
                 self.input_mode = InputMode::DEFAULT;
+                self.completion = None;
                 self.clear_error();
-                match *cmd_ref {
-                    Command::ListTopics(ref mut state) => {
-                        self.table.definition = command::list_topics::create_list_topics_table_definition();
-                        let topics = kafka::topic::list_topics(&self.config);
-                        state.set_topics(topics.clone());
-                        self.data = command::list_topics::table_from(topics)
-                    }
-                    Command::None => {}
+            }
+            Err(err) => self.open(PopupType::ERROR, err),
+        }
+    }
+
+    /// Connects to the cluster of the context, listing its topics.
+    ///
+    /// Whatever is still running against the previous cluster goes on, but what it hands back is dropped.
+    pub(crate) fn switch_context(&mut self, name: &str) -> Result<(), String> {
+        let profile = self.contexts.get(name)
+            .ok_or_else(|| format!("Unknown context '{name}', expected one of {}", self.names.contexts.join(", ")))?;
+        self.generation += 1;
+        self.cluster = Cluster { name: name.to_string(), profile: profile.clone() };
+        self.config = profile.config();
+        self.keys.set_read_only(profile.read_only);
+        self.names.topics.clear();
+        self.names.groups.clear();
+        self.show(Command::None);
+        self.load_view(Command::ListTopics(ListTopicsState::default()));
+        Ok(())
+    }
+
+    /// Asks for a value in the input, answered with Enter.
+    pub(crate) fn prompt(&mut self, prompt: Prompt) {
+        self.input.reset();
+        self.input_mode = InputMode::PROMPT(prompt);
+    }
+
+    fn answer_prompt(&mut self, prompt: Prompt) {
+        let value = self.input.value().trim().to_string();
+        // filters are applied as they are typed.
+        let result = match self.command.deref().clone() {
+            Command::BrowseTopic(state) => command::browse_topic::jump(self, state, prompt, &value),
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => {
+                self.input.reset();
+                self.input_mode = InputMode::DEFAULT;
+            }
+            Err(err) => self.open(PopupType::ERROR, err),
+        }
+    }
+
+    /// Replaces the state of the current view, without reloading its data.
+    pub(crate) fn set_command(&mut self, cmd: Command) {
+        *self.command = cmd;
+    }
+
+    /// Loads the data of the command in the background, switching to it once loaded.
+    pub(crate) fn load_view(&mut self, cmd: Command) {
+        let label = format!("Loading {}", cmd.clone().name());
+        self.load_in_background(cmd, label);
+    }
+
+    /// Reloads the data of the current view in the background, unless it is already loading.
+    ///
+    /// Tails are never reloaded, they stream their records.
+    fn refresh(&mut self) {
+        if matches!(self.command.deref(), Command::None | Command::Tail(_)) || self.view_task.is_some() {
+            return;
+        }
+        let cmd = self.command.deref().clone();
+        let label = format!("Refreshing {}", cmd.clone().name());
+        self.load_in_background(cmd, label);
+    }
+
+    /// Drops the view still loading or refreshing, once the user moved on from it.
+    pub(crate) fn drop_pending_view(&mut self) {
+        self.view_task = None;
+    }
+
+    fn load_in_background(&mut self, cmd: Command, label: String) {
+        let config = self.config.clone();
+        let task = self.spawn(label, async move { Outcome::Loaded(load(config, cmd).await) });
+        self.view_task = Some(task);
+    }
+
+    /// Switches to a command once its data is loaded, a reloaded view keeps its cursor and selection.
+    pub(crate) fn show(&mut self, mut cmd: Command) {
+        if !self.command.same_view(&cmd) {
+            self.stop_tail();
+            if let Command::Tail(state) = &mut cmd {
+                self.start_tail(state);
+            }
+        }
+        if let Command::ListContexts(state) = &mut cmd {
+            state.set_contexts(&self.contexts, &self.cluster);
+        }
+        let data = match &cmd {
+            Command::ListTopics(state) => {
+                self.table.definition = command::list_topics::create_list_topics_table_definition();
+                command::list_topics::table_from(state.topics().to_vec(), &self.theme)
+            }
+            Command::ListBrokers(state) => {
+                self.table.definition = command::list_brokers::create_list_brokers_table_definition();
+                command::list_brokers::table_from(state.brokers().to_vec())
+            }
+            Command::Health(state) => {
+                self.table.definition = command::health::create_health_table_definition();
+                state.report().map(|report| command::health::table_from(report, &self.theme)).unwrap_or_else(TableData::empty)
+            }
+            Command::ListAcls(state) => {
+                self.table.definition = command::list_acls::create_list_acls_table_definition();
+                command::list_acls::table_from(state.acls().to_vec(), &self.theme)
+            }
+            Command::ListQuotas(state) => {
+                self.table.definition = command::list_quotas::create_list_quotas_table_definition();
+                command::list_quotas::table_from(state.quotas().to_vec())
+            }
+            Command::ListGroups(state) => {
+                self.table.definition = command::list_groups::create_list_groups_table_definition();
+                command::list_groups::table_from(state.groups().to_vec(), &self.theme)
+            }
+            Command::DescribeGroup(state) => {
+                self.table.definition = command::describe_group::create_describe_group_table_definition();
+                command::describe_group::table_from(state.partitions().to_vec())
+            }
+            Command::BrowseTopic(state) => {
+                self.table.definition = command::browse_topic::create_browse_topic_table_definition();
+                command::browse_topic::table_from(state.messages().to_vec())
+            }
+            Command::Tail(state) => {
+                self.table.definition = command::tail::create_tail_table_definition();
+                command::tail::table_from(state.messages())
+            }
+            Command::ListContexts(state) => {
+                self.table.definition = command::list_contexts::create_list_contexts_table_definition();
+                command::list_contexts::table_from(state.contexts().to_vec(), &self.theme)
+            }
+            Command::DescribeTopic(state) => {
+                self.table.definition = command::describe_topic::create_describe_topic_table_definition(state.tab());
+                command::describe_topic::table_from(state, &self.theme)
+            }
+            Command::None => TableData::empty(),
+        };
+        if self.command.same_view(&cmd) {
+            self.table.refresh(&self.data, &data);
+        } else {
+            self.table.reset(&data);
+        }
+        self.data = data;
+        *self.command = cmd;
+    }
+
+    /// Reads the topics of the tail on its own thread, until another view is shown.
+    fn start_tail(&mut self, state: &mut TailState) {
+        let session = self.next_task;
+        self.next_task += 1;
+        state.set_session(session);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.tail = Some(stop.clone());
+        let (config, topics, sender) = (self.config.clone(), state.topics().to_vec(), self.sender.clone());
+        std::thread::spawn(move || command::tail::run(config, topics, session, stop, sender));
+    }
+
+    fn stop_tail(&mut self) {
+        if let Some(stop) = self.tail.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn tailed(&mut self, session: u64, batch: Result<TailBatch, String>) {
+        let Command::Tail(state) = &mut *self.command else {
+            return;
+        };
+        if state.session() != Some(session) {
+            return;
+        }
+        match batch {
+            Ok(batch) => {
+                state.push(batch);
+                if !state.paused() {
+                    self.update_tail();
                 }
-                self.command = cmd_ref;
             }
-            _ => {
-                self.set_error_message(format!("Unknown command '{}'", self.input));
+            Err(err) => {
+                state.stop();
+                self.tail = None;
+                self.open(PopupType::ERROR, format!("Tail stopped: {}", err));
+            }
+        }
+    }
+
+    /// Shows the records of the tail, the cursor follows new records unless moved up.
+    pub(crate) fn update_tail(&mut self) {
+        let Command::Tail(state) = self.command.deref() else {
+            return;
+        };
+        let data = command::tail::table_from(state.messages());
+        let follow = self.table.state.selected().is_none_or(|i| i + 1 >= self.table.visible.len());
+        self.table.refresh(&self.data, &data);
+        if follow && !self.table.visible.is_empty() {
+            self.table.state.select(Some(self.table.visible.len() - 1));
+        }
+        self.data = data;
+    }
+
+    /// Runs the task in the background, showing the label next to a spinner until it finishes.
+    pub(crate) fn spawn<F>(&mut self, label: String, task: F) -> u64
+    where
+        F: Future<Output = Outcome> + Send + 'static,
+    {
+        let id = self.next_task;
+        self.next_task += 1;
+        self.tasks.push(Task { id, generation: self.generation, label });
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            let outcome = tokio::spawn(task).await
+                .unwrap_or_else(|err| Outcome::Popup(PopupType::ERROR, err.to_string()));
+            // the receiver is only gone once the app exited.
+            let _ = sender.send(AppEvent::Finished(id, outcome));
+        });
+        id
+    }
+
+    /// Like [`App::spawn`], for blocking Kafka calls.
+    pub(crate) fn spawn_blocking<F>(&mut self, label: String, task: F) -> u64
+    where
+        F: FnOnce() -> Outcome + Send + 'static,
+    {
+        self.spawn(label, async move {
+            tokio::task::spawn_blocking(task).await
+                .unwrap_or_else(|err| Outcome::Popup(PopupType::ERROR, err.to_string()))
+        })
+    }
+
+    fn finish(&mut self, task: u64, outcome: Outcome) {
+        let current = self.tasks.iter().any(|t| t.id == task && t.generation == self.generation);
+        self.tasks.retain(|t| t.id != task);
+        if !current {
+            return;
+        }
+        match outcome {
+            Outcome::Loaded(result) => {
+                if self.view_task != Some(task) {
+                    return;
+                }
+                self.view_task = None;
+                self.last_refresh = Instant::now();
+                match result {
+                    Ok(cmd) => self.show(cmd),
+                    Err(err) => self.open(PopupType::ERROR, err),
+                }
+            }
+            Outcome::Popup(popup_type, message) => self.open(popup_type, message),
+            Outcome::Confirm(confirmation) => self.confirm(*confirmation),
+            Outcome::Names(names) => {
+                self.names.topics = names.topics;
+                self.names.groups = names.groups;
+            }
+        }
+    }
+
+
+    fn run_confirmed(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::UncleanElection(config, topics) => {
+                command::list_topics::elect_leaders(self, config, topics, ElectionType::Unclean)
+            }
+            ConfirmAction::Truncate(config, plan) => command::list_topics::truncate(self, config, plan),
+            ConfirmAction::DeleteGroups(config, groups) => command::list_groups::delete_groups(self, config, groups),
+            ConfirmAction::DeleteTopics(config, topics) => command::list_topics::delete_topics(self, config, topics),
+            ConfirmAction::ResetOffsets(config, group, partitions, target) => {
+                command::describe_group::reset_offsets(self, config, group, partitions, target)
             }
         }
     }
@@ -224,11 +774,169 @@ impl<'a> App<'a> {
 
     fn render_command_view(&self, cmd: &Command, area: Rect, buf: &mut Buffer, state: &mut App) {
         match cmd {
-            Command::ListTopics(_) => {
+            Command::ListTopics(_) | Command::ListBrokers(_) | Command::ListAcls(_) | Command::ListQuotas(_)
+            | Command::ListGroups(_) | Command::DescribeGroup(_) | Command::ListContexts(_) => {
                 self.draw_table(area, buf, state);
             }
-            _ => {}
+            Command::Health(health) => {
+                let [summary_area, table_area] = Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(area);
+                if let Some(report) = health.report() {
+                    Paragraph::new(command::health::summary(report, &self.theme)).render(summary_area, buf);
+                }
+                self.draw_table(table_area, buf, state);
+            }
+            Command::BrowseTopic(browse) => {
+                let [summary_area, body_area] = Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(area);
+                Paragraph::new(command::browse_topic::summary(browse)).render(summary_area, buf);
+                let message = state.table.current().and_then(|i| browse.messages().get(i)).filter(|_| browse.detail());
+                self.draw_messages(message, body_area, buf, state);
+            }
+            Command::Tail(tail) => {
+                let [summary_area, body_area] = Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(area);
+                Paragraph::new(command::tail::summary(tail, &self.theme)).render(summary_area, buf);
+                let message = state.table.current().and_then(|i| tail.message(i)).filter(|_| tail.detail());
+                self.draw_messages(message, body_area, buf, state);
+            }
+            Command::DescribeTopic(topic) => {
+                let [tabs_area, summary_area, table_area] = Layout::vertical([
+                    Constraint::Length(1),
+                    Constraint::Length(2),
+                    Constraint::Min(1),
+                ]).areas(area);
+                let (titles, selected) = command::describe_topic::tabs(topic);
+                Tabs::new(titles)
+                    .select(selected)
+                    .highlight_style(self.theme.cursor)
+                    .render(tabs_area, buf);
+                Paragraph::new(command::describe_topic::summary(topic, &self.theme)).render(summary_area, buf);
+                self.draw_table(table_area, buf, state);
+            }
+            Command::None => {}
+        }
+    }
+
+    // The table of records, next to the details of one of them when given.
+    fn draw_messages(&self, message: Option<&MessageEntry>, area: Rect, buf: &mut Buffer, state: &mut App) {
+        match message {
+            Some(message) => {
+                let [table_area, detail_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(area);
+                self.draw_table(table_area, buf, state);
+                let detail = command::browse_topic::detail(message);
+                // the wrapped height, less the border and padding from the width
+                let width = detail_area.width.saturating_sub(2).max(1) as usize;
+                let height = detail.lines.iter().map(|line| line.width().div_ceil(width).max(1)).sum::<usize>();
+                let scroll = state.detail_scroll.on(message, height.saturating_sub(1).min(u16::MAX as usize) as u16);
+                Paragraph::new(detail)
+                    .wrap(Wrap { trim: false })
+                    .scroll((scroll, 0))
+                    .block(Block::new().borders(Borders::LEFT).padding(Padding::left(1)))
+                    .render(detail_area, buf);
+            }
+            None => self.draw_table(area, buf, state),
+        }
+    }
+
+    // The cluster connected to, so it is never mistaken for another one.
+    fn render_header(&self, area: Rect, buf: &mut Buffer) {
+        let profile = &self.cluster.profile;
+        let mut context = vec![Span::styled(self.cluster.name.clone(), self.theme.header.add_modifier(Modifier::BOLD))];
+        if profile.production {
+            context.push(Span::raw("  "));
+            context.push(Span::styled("PRODUCTION", self.theme.production.add_modifier(Modifier::BOLD | Modifier::REVERSED)));
+        }
+        if profile.read_only {
+            context.push(Span::raw("  "));
+            context.push(Span::styled("READ-ONLY", self.theme.success.add_modifier(Modifier::BOLD | Modifier::REVERSED)));
+        }
+        let auth = if profile.iam_auth { format!("IAM, {}", profile.aws_region) } else { "none".to_string() };
+        let lines = vec![
+            Line::from([vec![Span::raw("Context:  ")], context].concat()),
+            Line::from(format!("Cluster:  {}", profile.bootstrap_servers)),
+            Line::from(format!("Auth:     {auth}")),
+            Line::from(match self.contexts.len() {
+                1 => "Name clusters in ~/.kafka-utils/config.yaml to switch between them".to_string(),
+                count => format!("{}{} to switch between the {count} contexts", self.keys.key(Context::Global, Action::Command), Command::CMD_CONTEXT),
+            }).style(self.theme.dimmed),
+        ];
+        Paragraph::new(lines).block(Block::new().padding(Padding::horizontal(1))).render(area, buf);
+    }
+
+    // The keys of the view, and how to get the others.
+    fn render_footer(&self, area: Rect, buf: &mut Buffer) {
+        let view = self.command.context()
+            .map(|context| self.keys.of(context).map(|b| (b.keys.first().map(|k| k.to_string()), b.description)).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let global = |action| self.keys.of(Context::Global)
+            .find(|b| b.action == action)
+            .map(|b| (b.keys.first().map(|k| k.to_string()), b.description));
+        // help first, the line is cut on narrow terminals.
+        let hints = global(Action::Help).into_iter()
+            .chain(view)
+            .chain([Action::Command, Action::Filter, Action::Quit].into_iter().filter_map(global))
+            .filter_map(|(key, description)| key.map(|key| format!("{key} {description}")))
+            .collect::<Vec<_>>();
+        Paragraph::new(Line::from(hints.join("  ")).style(self.theme.dimmed)).render(area, buf);
+    }
+
+    // Every key of the view, the table and the app, with what it does.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let section = |context: Context| {
+            let mut lines = vec![Line::from(context.to_string()).bold()];
+            lines.extend(self.keys.of(context).map(|b| Line::from(format!("{:>14}  {}", b.keys(), b.description))));
+            lines.push(Line::from(""));
+            lines
+        };
+        let mut left = section(Context::Global);
+        left.extend(section(Context::Table));
+        let right = self.command.context().map(section).unwrap_or_default();
+
+        let pop_area = popup_area(area, 80, 80);
+        Clear.render(pop_area, buf);
+        let block = Block::bordered()
+            .title("keys")
+            .title_alignment(Alignment::Center)
+            .title_bottom(Line::from(format!(
+                "{} or Esc to close, remap in ~/.kafka-utils/keys.yaml",
+                self.keys.key(Context::Global, Action::Help),
+            )).centered())
+            .padding(Padding::horizontal(1))
+            .style(self.theme.popup);
+        let [left_area, right_area] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .areas(block.inner(pop_area));
+        block.render(pop_area, buf);
+        Paragraph::new(left).render(left_area, buf);
+        Paragraph::new(right).render(right_area, buf);
+    }
+
+    // The completions of the word being typed, in a dropdown under the command line.
+    fn render_suggestions(&self, input_area: Rect, area: Rect, buf: &mut Buffer) {
+        let line = self.input.value();
+        let completion = self.completion.clone().unwrap_or_else(|| Completion::of(line, &self.names));
+        // nothing left to suggest once the word is typed in full.
+        if completion.candidates.is_empty() || (completion.selected.is_none() && line.ends_with(&completion.candidates[0]) && completion.candidates.len() == 1) {
+            return;
         }
+        let selected = completion.selected.unwrap_or(0);
+        let skip = (selected + 1).saturating_sub(SUGGESTIONS);
+        let width = completion.candidates.iter().map(|c| c.len()).max().unwrap_or(0) as u16 + 4;
+        let height = completion.candidates.len().min(SUGGESTIONS) as u16 + 2;
+        let x = (input_area.x + 1 + completion.start() as u16).min(area.right().saturating_sub(width));
+        let dropdown = Rect::new(x, input_area.bottom(), width, height).intersection(area);
+        let items = completion.candidates.iter().enumerate().skip(skip).take(SUGGESTIONS).map(|(i, candidate)| {
+            let item = ListItem::new(candidate.as_str());
+            if completion.selected == Some(i) { item.style(self.theme.cursor) } else { item }
+        });
+        Clear.render(dropdown, buf);
+        let title = if completion.candidates.len() > SUGGESTIONS {
+            format!("{} of {}", selected + 1, completion.candidates.len())
+        } else {
+            String::new()
+        };
+        List::new(items)
+            .style(self.theme.popup)
+            .block(Block::bordered().title_bottom(Line::from(title).right_aligned()))
+            .render(dropdown, buf);
     }
 
     fn draw_table(&self, area: Rect, buf: &mut Buffer, state: &mut App) {
@@ -238,38 +946,35 @@ impl<'a> App<'a> {
         self.render_table(rects[0], buf, state);
     }
     fn render_table(&self, area: Rect, buf: &mut Buffer, state: &mut App) {
-        let table = self.clone().table;
         let table_data = state.clone().data;
-        let header_style = Style::default()
-            .fg(table.colors.header_fg);
-        let selected_style = Style::default()
-            .add_modifier(Modifier::REVERSED);
+        let theme = &self.theme;
 
         let table_definition = state.clone().table.definition;
 
         let header = table_definition
-            .header
+            .header(state.table.sort)
             .into_iter()
-            .map(Cell::from)
             .collect::<Row>()
-            .style(header_style)
+            .style(theme.header)
             .height(1);
 
-        let rows: Vec<Row> = if table_definition.selectable {
-            table_data.rows.iter().enumerate().map(|(i, row)| {
-                if state.table.selected.contains(&i) {
-                    let style = Style::from(row.style())
-                        .add_modifier(Modifier::BOLD);
-                    row.clone().set_style(style)
-                } else {
-                    row.clone()
-                }
-            }).collect::<Vec<_>>()
-        } else { table_data.rows };
+        let rows: Vec<Row> = state.table.visible.iter().map(|i| {
+            let row = &table_data.rows[*i];
+            let mut style = row.style();
+            if table_definition.selectable && state.table.selected.contains(i) {
+                style = style.patch(theme.selected);
+            }
+            if state.table.changed.contains(i) {
+                style = style.patch(theme.changed);
+            }
+            row.clone().set_style(style)
+        }).collect::<Vec<_>>();
 
+        // the header takes a line.
+        state.table.page = area.height.saturating_sub(1).max(1);
         let t = Table::new(rows, table_data.widths)
             .header(header)
-            .row_highlight_style(selected_style)
+            .row_highlight_style(theme.cursor)
             .highlight_spacing(HighlightSpacing::Always);
 
         ratatui::prelude::StatefulWidget::render(t, area, buf, &mut state.table.state)
@@ -281,7 +986,7 @@ impl<'a> ratatui::widgets::StatefulWidget for App<'a> {
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let input_size = match self.input_mode {
-            InputMode::COMMAND => 3,
+            InputMode::COMMAND | InputMode::PROMPT(_) => 3,
             _ => 0
         };
 
@@ -289,20 +994,37 @@ impl<'a> ratatui::widgets::StatefulWidget for App<'a> {
             Constraint::Length(5),
             Constraint::Length(input_size),
             Constraint::Min(1),
+            Constraint::Length(1),
         ]);
 
-        let [_, input_area, main_area] = vertical.areas(area);
+        let [header_area, input_area, main_area, footer_area] = vertical.areas(area);
+        self.render_header(header_area, buf);
+        self.render_footer(footer_area, buf);
 
+        let input_title = match &self.input_mode {
+            InputMode::PROMPT(prompt) => prompt.title(),
+            _ => "Input",
+        };
+        let mut input_block = Block::default().title(input_title).borders(Borders::ALL);
+        if let InputMode::COMMAND = self.input_mode {
+            if let Ok(line) = CommandLine::parse(self.input.value()).or_else(|_| {
+                // the usage of the command being typed, once its name is.
+                let name = self.input.value().split_whitespace().next().unwrap_or_default();
+                CommandLine::parse(name)
+            }) {
+                input_block = input_block.title_bottom(Line::from(format!("{} {}", line.spec.name, line.spec.usage)).style(self.theme.dimmed));
+            }
+        }
         Paragraph::new(self.input.value())
             .style(match self.input_mode {
                 InputMode::DEFAULT => Style::default(),
-                InputMode::COMMAND => Style::default().fg(Color::Yellow),
+                InputMode::COMMAND | InputMode::PROMPT(_) => self.theme.input,
             })
-            .block(Block::default().title("Input").borders(Borders::ALL))
+            .block(input_block)
             .render(input_area, buf);
 
-        let main_block = Block::bordered()
-            .style(Style::new().fg(self.table.colors.border))
+        let mut main_block = Block::bordered()
+            .style(if self.cluster.profile.production { self.theme.production } else { self.theme.border })
             .padding(Padding {
                 left: 1,
                 right: 1,
@@ -310,6 +1032,21 @@ impl<'a> ratatui::widgets::StatefulWidget for App<'a> {
             })
             .title(self.command.clone().name())
             .title_alignment(Alignment::Center);
+        if let Some(interval) = self.refresh_interval {
+            main_block = main_block.title_bottom(Line::from(format!("refresh every {}s", interval.as_secs())).right_aligned());
+        }
+        if let Some(filter) = &self.table.filter {
+            main_block = main_block.title_bottom(Line::from(format!(
+                "/{} {} of {}",
+                filter.text(),
+                self.table.visible.len(),
+                self.data.rows.len(),
+            )).left_aligned());
+        }
+        if let Some(Task { label, .. }) = self.tasks.last() {
+            let spinner = SPINNER[self.ticks % SPINNER.len()];
+            main_block = main_block.title_top(Line::from(format!("{spinner} {label}")).right_aligned());
+        }
 
         main_block
             .clone()
@@ -317,22 +1054,35 @@ impl<'a> ratatui::widgets::StatefulWidget for App<'a> {
 
         self.render_command_view(&self.command, main_block.inner(main_area), buf, state);
 
-        if self.has_error() {
+        if let InputMode::COMMAND = self.input_mode {
+            self.render_suggestions(input_area, area, buf);
+        }
+
+        if self.help {
+            self.render_help(area, buf);
+        }
+
+        if let Some(confirmation) = self.confirmation() {
+            let pop_area = popup_area(area, 60, 40);
+            Clear.render(pop_area, buf);
+            confirmation.render(pop_area, buf, &self.theme);
+        } else if self.has_error() {
             let message = self.error.clone().unwrap();
 
             let pop_area = popup_area(area, 60, 20);
             Clear.render(pop_area, buf);
-            let (title, color) = match self.popup_type {
-                PopupType::ERROR => ("error", Color::Red),
-                PopupType::SUCCESS => ("success", Color::Green),
+            let (title, style) = match self.popup_type {
+                PopupType::ERROR => ("error", self.theme.error),
+                PopupType::SUCCESS => ("success", self.theme.success),
+                PopupType::CONFIRM => ("confirm", self.theme.warning),
             };
             let block = Block::bordered()
                 .title(title)
                 .title_alignment(Alignment::Center)
                 .title_style(Style::default())
-                .bg(Color::Black);
+                .style(self.theme.popup);
             Paragraph::new(message)
-                .style(color)
+                .style(style)
                 .block(block)
                 .render(pop_area, buf);
         }
@@ -360,6 +1110,7 @@ impl PopupWidget for App<'_> {
     }
 
     fn close(&mut self) {
+        self.confirmation = None;
         self.clear_error();
     }
 
@@ -368,8 +1119,183 @@ impl PopupWidget for App<'_> {
     }
 }
 
+impl ConfirmWidget for App<'_> {
+    /// Asks the user to confirm the action before running it.
+    fn confirm(&mut self, confirmation: Confirmation) {
+        let message = confirmation.message().to_string();
+        self.open(PopupType::CONFIRM, message);
+        self.confirmation = Some(confirmation);
+    }
+
+    fn confirmation(&self) -> Option<&Confirmation> {
+        self.confirmation.as_ref()
+    }
+}
+
+/// A background task still running, with what it is doing.
+#[derive(Debug, Clone)]
+struct Task {
+    id: u64,
+    /// The cluster generation it was spawned in.
+    generation: u64,
+    label: String,
+}
+
 #[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum PopupType {
     ERROR,
-    SUCCESS
+    SUCCESS,
+    CONFIRM,
+}
+
+/// An action waiting for the user's confirmation, run on the cluster it was asked on.
+#[derive(Clone)]
+pub(crate) enum ConfirmAction {
+    UncleanElection(Config, Vec<String>),
+    Truncate(Config, Vec<DeleteRecordsEntry>),
+    DeleteGroups(Config, Vec<String>),
+    DeleteTopics(Config, Vec<String>),
+    /// Partitions of the group to reset.
+    ResetOffsets(Config, String, Vec<(String, i32)>, OffsetResetTarget),
+}
+
+/// A value asked in the input, in place of a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Prompt {
+    JumpToOffset,
+    JumpToTimestamp,
+    Filter,
+}
+
+impl Prompt {
+    fn title(&self) -> &'static str {
+        match self {
+            Prompt::JumpToOffset => "Jump to offset",
+            Prompt::JumpToTimestamp => "Jump to timestamp, in milliseconds since the epoch or RFC 3339",
+            Prompt::Filter => "Filter, by regex or field as in partitions>12",
+        }
+    }
+}
+
+/// What a background task hands back to the event loop.
+pub(crate) enum Outcome {
+    /// The command with its data, to switch the view to.
+    Loaded(Result<Command, String>),
+    Popup(PopupType, String),
+    /// Boxed, as it carries the config of the cluster.
+    Confirm(Box<Confirmation>),
+    /// Names to complete in the command line.
+    Names(Names),
+}
+
+/// Fetches the data of the command, blocking Kafka calls run on the blocking pool.
+async fn load(config: Config, cmd: Command) -> Result<Command, String> {
+    match cmd {
+        Command::ListTopics(mut state) => {
+            let mut topics = tokio::task::spawn_blocking(move || kafka::topic::list_topics(&config)).await
+                .map_err(|err| err.to_string())
+                .and_then(|topics| topics.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to list topics: {}", err))?;
+            if let Some(prefix) = state.prefix() {
+                topics.retain(|topic| topic.name.starts_with(prefix));
+            }
+            state.set_topics(topics);
+            Ok(Command::ListTopics(state))
+        }
+        Command::ListBrokers(mut state) => {
+            let brokers = tokio::task::spawn_blocking(move || kafka::broker::list_brokers(&config)).await
+                .map_err(|err| err.to_string())
+                .and_then(|brokers| brokers.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to list brokers: {}", err))?;
+            state.set_brokers(brokers);
+            Ok(Command::ListBrokers(state))
+        }
+        Command::Health(mut state) => {
+            let report = kafka::health::check_health(&config).await
+                .map_err(|err| format!("Failed to check cluster health: {}", err))?;
+            state.set_report(report);
+            Ok(Command::Health(state))
+        }
+        Command::ListAcls(mut state) => {
+            let acls = tokio::task::spawn_blocking(move || kafka::acl::describe_acls(&config, &AclFilter::default())).await
+                .map_err(|err| err.to_string())
+                .and_then(|acls| acls.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to describe acls: {}", err))?;
+            state.set_acls(acls);
+            Ok(Command::ListAcls(state))
+        }
+        Command::ListQuotas(mut state) => {
+            let quotas = tokio::task::spawn_blocking(move || kafka::quota::describe_quotas(&config, &QuotaEntity::default())).await
+                .map_err(|err| err.to_string())
+                .and_then(|quotas| quotas.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to describe quotas: {}", err))?;
+            state.set_quotas(quotas);
+            Ok(Command::ListQuotas(state))
+        }
+        Command::ListGroups(mut state) => {
+            let prefix = state.prefix().map(str::to_string);
+            let groups = tokio::task::spawn_blocking(move || command::list_groups::fetch_groups(&config, prefix)).await
+                .map_err(|err| err.to_string())
+                .and_then(|groups| groups.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to list consumer groups: {}", err))?;
+            state.set_groups(groups);
+            Ok(Command::ListGroups(state))
+        }
+        Command::DescribeGroup(mut state) => {
+            let group = state.group().to_string();
+            let partitions = tokio::task::spawn_blocking(move || kafka::group::describe_lag(&config, &group)).await
+                .map_err(|err| err.to_string())
+                .and_then(|partitions| partitions.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to describe consumer group {}: {}", state.group(), err))?;
+            state.set_partitions(partitions);
+            Ok(Command::DescribeGroup(state))
+        }
+        Command::BrowseTopic(mut state) => {
+            let topic = state.topic().to_string();
+            let (partition, start) = (state.partition(), state.start());
+            let (partitions, page) = tokio::task::spawn_blocking(move || {
+                let partitions = kafka::message::partition_count(&config, &topic)?;
+                let page = kafka::message::fetch_page(&config, &topic, partition, start, command::browse_topic::PAGE_SIZE)?;
+                Ok::<_, rdkafka::error::KafkaError>((partitions, page))
+            }).await
+                .map_err(|err| err.to_string())
+                .and_then(|page| page.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to browse topic {}: {}", state.topic(), err))?;
+            state.set_page(partitions, page);
+            Ok(Command::BrowseTopic(state))
+        }
+        Command::Tail(state) => {
+            let topics = state.topics().to_vec();
+            tokio::task::spawn_blocking(move || {
+                topics.iter().try_for_each(|topic| kafka::message::partition_count(&config, topic).map(|_| ()))
+            }).await
+                .map_err(|err| err.to_string())
+                .and_then(|checked| checked.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to tail {}: {}", state.topics().join(", "), err))?;
+            Ok(Command::Tail(state))
+        }
+        Command::ListContexts(state) => Ok(Command::ListContexts(state)),
+        Command::DescribeTopic(mut state) => {
+            // neither configs nor groups may be readable without the permission to, the other tabs still show.
+            let configs = kafka::topic::describe_configs(&config, state.topic()).await.map_err(|err| err.to_string());
+            let topic = state.topic().to_string();
+            let partition = state.partition();
+            let with_groups = state.tab() == Tab::Groups;
+            let (partitions, groups, page) = tokio::task::spawn_blocking(move || {
+                let partitions = kafka::topic::describe_partitions(&config, &topic)?;
+                // the lag takes a request per group, only worth it when shown.
+                let groups = with_groups.then(|| kafka::group::topic_lag(&config, &topic).map_err(|err| err.to_string()));
+                let page = kafka::message::fetch_page(&config, &topic, partition, StartPosition::End, command::browse_topic::PAGE_SIZE)
+                    .map_err(|err| err.to_string());
+                Ok::<_, rdkafka::error::KafkaError>((partitions, groups, page))
+            }).await
+                .map_err(|err| err.to_string())
+                .and_then(|details| details.map_err(|err| err.to_string()))
+                .map_err(|err| format!("Failed to describe topic {}: {}", state.topic(), err))?;
+            state.set_details(partitions, configs, groups, page);
+            Ok(Command::DescribeTopic(state))
+        }
+        Command::None => Ok(Command::None),
+    }
 }
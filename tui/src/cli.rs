@@ -1,27 +1,69 @@
-use std::time::Duration;
 use clap::Parser;
-use common::kafka;
-use common::kafka::client::Config;
+use crate::settings::{Cluster, Profile, Settings};
+use crate::theme::ThemeName;
+use common::kafka::iam;
 
 #[derive(Debug, Parser)] // requires `derive` feature
 #[command(name = "kafka-utils")]
 #[command(about = "A fictional versioning CLI", long_about = None)]
 pub struct Cli {
-    #[arg(short, long, required = true)]
-    bootstrap_servers: String,
+    #[arg(short, long, required_unless_present = "context", conflicts_with = "context")]
+    bootstrap_servers: Option<String>,
     #[arg(short, long)]
     iam_auth: bool,
     #[arg(short, long, default_value = "10000")]
     timeout: u64,
     #[arg(short, long, default_value = "eu-west-1")]
     aws_region: String,
+    /// Lifetime in seconds of the IAM auth token, at most 900
+    #[arg(long, default_value = "900", value_parser = clap::value_parser!(u64).range(1..=iam::MAX_EXPIRY_SECONDS))]
+    iam_token_lifetime: u64,
+    /// Sign the IAM auth token for the FIPS endpoint
+    #[arg(long)]
+    iam_fips: bool,
+    /// Sign the IAM auth token for a custom endpoint url
+    #[arg(long, conflicts_with = "iam_fips")]
+    iam_endpoint: Option<String>,
+    /// Connect to a cluster of the settings file, its settings go in place of the connection flags
+    #[arg(short, long)]
+    context: Option<String>,
+    /// Refuse every operation changing a cluster, whatever the contexts say
+    #[arg(long)]
+    pub(crate) read_only: bool,
+    /// Reload the current view every this many seconds, `r` reloads it on demand
+    #[arg(short, long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub(crate) refresh: Option<u64>,
+    /// Colors of the app, over the one of the settings file, `NO_COLOR` makes it monochrome
+    #[arg(long, value_enum)]
+    pub(crate) theme: Option<ThemeName>,
 }
 
-pub fn get_config(cli: Cli) -> Config {
-    kafka::client::create_config(
-        cli.bootstrap_servers,
-        cli.iam_auth,
-        cli.aws_region,
-        Duration::from_millis(cli.timeout.into()),
-    )
-}
\ No newline at end of file
+/// The cluster to connect to, the context given or the one of the flags, named after its bootstrap servers.
+pub fn get_cluster(cli: Cli, settings: &Settings) -> Result<Cluster, String> {
+    if let Some(name) = cli.context {
+        let profile = settings.contexts.get(&name).cloned().ok_or_else(|| {
+            let names = settings.contexts.keys().cloned().collect::<Vec<_>>();
+            if names.is_empty() {
+                format!("Unknown context '{name}', there are none in ~/.kafka-utils/config.yaml")
+            } else {
+                format!("Unknown context '{name}', expected one of {}", names.join(", "))
+            }
+        })?;
+        return Ok(Cluster { name, profile });
+    }
+    let bootstrap_servers = cli.bootstrap_servers.unwrap_or_default();
+    Ok(Cluster {
+        name: bootstrap_servers.clone(),
+        profile: Profile {
+            bootstrap_servers,
+            iam_auth: cli.iam_auth,
+            aws_region: cli.aws_region,
+            timeout: cli.timeout,
+            iam_token_lifetime: cli.iam_token_lifetime,
+            iam_fips: cli.iam_fips,
+            iam_endpoint: cli.iam_endpoint,
+            production: false,
+            read_only: cli.read_only,
+        },
+    })
+}
@@ -0,0 +1,364 @@
+use crate::app::{App, Command, Prompt};
+use crate::command::list_topics::ListTopicsState;
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use chrono::{DateTime, SecondsFormat};
+use common::kafka::message::StartPosition;
+use common::kafka::types::{MessageEntry, MessagePageEntry};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Line, Modifier, Stylize, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+/// Records shown per page.
+pub(crate) const PAGE_SIZE: usize = 50;
+const KEY_WIDTH: usize = 30;
+const VALUE_WIDTH: usize = 120;
+
+pub fn create_browse_topic_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Offset").right().sort_by(SortKey::Number("offset")),
+            Column::new("Timestamp").sort_by(SortKey::Number("timestamp")),
+            Column::new("Key").sort_by(SortKey::Text("key")),
+            Column::new("Value"),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<MessageEntry>) -> TableData<'a> {
+    let mut longest_offset = 0;
+    let mut longest_key = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            let key = r.key.as_deref().map(|k| truncate(k, KEY_WIDTH)).unwrap_or("-".to_string());
+            longest_offset = max(longest_offset, constraint_len_calculator(r.offset.to_string().as_str()));
+            longest_key = max(longest_key, constraint_len_calculator(key.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(Text::from(r.offset.to_string()).alignment(Alignment::Right)),
+                    Cell::from(format_timestamp(r.timestamp)),
+                    Cell::from(key),
+                    Cell::from(r.value.as_deref().map(|v| truncate(v, VALUE_WIDTH)).unwrap_or("-".to_string())),
+                ]
+            );
+            // tombstones
+            if r.value.is_none() {
+                row.add_modifier(Modifier::DIM)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Length(max(longest_offset, 6) + 1),
+            Constraint::Length(25),
+            Constraint::Length(max(longest_key, 3) + 1),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.offset.to_string(), format!("{r:?}"))).collect())
+        .with_records(&data)
+}
+
+/// Where the browser stands, the page is fetched from `start` of `partition`.
+#[derive(Debug, Clone)]
+pub struct BrowseTopicState {
+    topic: String,
+    partitions: i32,
+    partition: i32,
+    start: StartPosition,
+    page: Option<MessagePageEntry>,
+    detail: bool,
+}
+
+impl BrowseTopicState {
+    pub fn new(topic: String) -> Self {
+        Self {
+            topic,
+            partitions: 0,
+            partition: 0,
+            start: StartPosition::Beginning,
+            page: None,
+            detail: false,
+        }
+    }
+
+    /// Starts from elsewhere than the beginning of the first partition.
+    pub fn starting_at(self, partition: i32, start: StartPosition) -> Self {
+        self.moved_to(partition, start)
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn partition(&self) -> i32 {
+        self.partition
+    }
+
+    pub fn start(&self) -> StartPosition {
+        self.start
+    }
+
+    pub fn set_page(&mut self, partitions: i32, page: MessagePageEntry) {
+        self.partitions = partitions;
+        self.page = Some(page);
+    }
+
+    pub fn messages(&self) -> &[MessageEntry] {
+        self.page.as_ref().map(|p| p.messages.as_slice()).unwrap_or_default()
+    }
+
+    pub fn detail(&self) -> bool {
+        self.detail
+    }
+
+    /// Whether both states show the same records, a reload rather than a move to other records.
+    pub fn same_page(&self, other: &BrowseTopicState) -> bool {
+        self.topic == other.topic && self.partition == other.partition && self.start == other.start
+    }
+
+    // The same topic from elsewhere, the detail pane stays as it is.
+    fn moved_to(&self, partition: i32, start: StartPosition) -> Self {
+        Self {
+            partition,
+            start,
+            page: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Where the browser stands.
+pub(crate) fn summary(state: &BrowseTopicState) -> Vec<Line<'static>> {
+    let position = match &state.page {
+        Some(page) if page.messages.is_empty() => {
+            format!("offsets {} to {}, no records from here", page.low_watermark, page.high_watermark)
+        }
+        Some(page) => format!(
+            "offsets {} to {}, showing {} to {}",
+            page.low_watermark,
+            page.high_watermark,
+            page.messages[0].offset,
+            page.messages[page.messages.len() - 1].offset,
+        ),
+        None => String::new(),
+    };
+    vec![Line::from(format!("Partition {} of {}, {}", state.partition, state.partitions, position))]
+}
+
+/// The record with its full value, pretty printed when it is JSON, and its headers.
+pub(crate) fn detail(message: &MessageEntry) -> Text<'static> {
+    let mut lines = vec![
+        Line::from(format!("Topic      {}", message.topic)),
+        Line::from(format!("Partition  {}", message.partition)),
+        Line::from(format!("Offset     {}", message.offset)),
+        Line::from(format!("Timestamp  {}", format_timestamp(message.timestamp))),
+        Line::from(format!("Key        {}", message.key.as_deref().unwrap_or("-"))),
+    ];
+    if !message.headers.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Headers").bold());
+        lines.extend(message.headers.iter()
+            .map(|(key, value)| Line::from(format!("{key}: {}", value.as_deref().unwrap_or("-")))));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Value").bold());
+    match message.value.as_deref() {
+        Some(value) => {
+            let value = serde_json::from_str::<serde_json::Value>(value)
+                .and_then(|json| serde_json::to_string_pretty(&json))
+                .unwrap_or(value.to_string());
+            lines.extend(value.lines().map(|line| Line::from(line.to_string())));
+        }
+        None => lines.push(Line::from("tombstone").dim()),
+    }
+    Text::from(lines)
+}
+
+/// How far the record details are scrolled, back at the top for another record.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DetailScroll {
+    record: Option<(String, i32, i64)>,
+    lines: u16,
+}
+
+impl DetailScroll {
+    /// The lines scrolled past on the record, at most `max`.
+    pub(crate) fn on(&mut self, message: &MessageEntry, max: u16) -> u16 {
+        let record = (message.topic.clone(), message.partition, message.offset);
+        if self.record.as_ref() != Some(&record) {
+            *self = Self { record: Some(record), lines: 0 };
+        }
+        self.lines = self.lines.min(max);
+        self.lines
+    }
+
+    pub(crate) fn scroll(&mut self, action: Action) {
+        self.lines = match action {
+            Action::ScrollDetailsUp => self.lines.saturating_sub(1),
+            _ => self.lines.saturating_add(1),
+        };
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: BrowseTopicState) {
+    let page = state.page.as_ref();
+    match action {
+        Action::Details => {
+            let mut state = state.clone();
+            state.detail = !state.detail;
+            app.set_command(Command::BrowseTopic(state));
+        }
+        Action::Back if state.detail => {
+            let mut state = state.clone();
+            state.detail = false;
+            app.set_command(Command::BrowseTopic(state));
+        }
+        Action::ScrollDetailsUp | Action::ScrollDetailsDown if state.detail => app.detail_scroll.scroll(action),
+        Action::Back => app.load_view(Command::ListTopics(ListTopicsState::default())),
+        Action::PreviousPartition | Action::NextPartition if state.partitions > 1 => {
+            let partition = if action == Action::PreviousPartition {
+                (state.partition + state.partitions - 1) % state.partitions
+            } else {
+                (state.partition + 1) % state.partitions
+            };
+            // offsets of one partition mean nothing in another one.
+            let start = match state.start {
+                StartPosition::Offset(_) => StartPosition::Beginning,
+                start => start,
+            };
+            app.load_view(Command::BrowseTopic(state.moved_to(partition, start)));
+        }
+        Action::NextPage => {
+            if let Some(page) = page {
+                match page.messages.last() {
+                    Some(last) if last.offset + 1 < page.high_watermark => {
+                        let start = StartPosition::Offset(last.offset + 1);
+                        app.load_view(Command::BrowseTopic(state.moved_to(state.partition, start)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Action::PreviousPage => {
+            if let Some(page) = page {
+                match page.messages.first() {
+                    Some(first) if first.offset > page.low_watermark => {
+                        let start = StartPosition::Offset(first.offset - PAGE_SIZE as i64);
+                        app.load_view(Command::BrowseTopic(state.moved_to(state.partition, start)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Action::FirstPage => {
+            app.load_view(Command::BrowseTopic(state.moved_to(state.partition, StartPosition::Beginning)));
+        }
+        Action::LastPage => {
+            app.load_view(Command::BrowseTopic(state.moved_to(state.partition, StartPosition::End)));
+        }
+        Action::JumpToOffset => app.prompt(Prompt::JumpToOffset),
+        Action::JumpToTimestamp => app.prompt(Prompt::JumpToTimestamp),
+        _ => {}
+    }
+}
+
+/// Jumps to the offset or timestamp typed in the prompt.
+pub(crate) fn jump(app: &mut App<'_>, state: BrowseTopicState, prompt: Prompt, value: &str) -> Result<(), String> {
+    let start = match prompt {
+        Prompt::JumpToOffset => value.parse::<i64>()
+            .map(StartPosition::Offset)
+            .map_err(|_| format!("Invalid offset '{value}'"))?,
+        Prompt::JumpToTimestamp => parse_timestamp(value).map(StartPosition::Timestamp)?,
+        Prompt::Filter => return Ok(()),
+    };
+    app.load_view(Command::BrowseTopic(state.moved_to(state.partition, start)));
+    Ok(())
+}
+
+/// Where to start from, `earliest`, `latest`, an offset or an RFC 3339 time, as in `--from earliest`.
+pub(crate) fn parse_start(value: &str) -> Result<StartPosition, String> {
+    match value {
+        "earliest" => Ok(StartPosition::Beginning),
+        "latest" => Ok(StartPosition::End),
+        _ => value.parse::<i64>()
+            .map(StartPosition::Offset)
+            .or_else(|_| DateTime::parse_from_rfc3339(value).map(|t| StartPosition::Timestamp(t.timestamp_millis())))
+            .map_err(|_| format!("Invalid start '{value}', expected earliest, latest, an offset or an RFC 3339 time")),
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<i64, String> {
+    value.parse::<i64>()
+        .or_else(|_| DateTime::parse_from_rfc3339(value).map(|t| t.timestamp_millis()))
+        .map_err(|_| format!("Invalid timestamp '{value}', expected milliseconds since the epoch or an RFC 3339 time"))
+}
+
+pub(crate) fn format_timestamp(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or("-".to_string())
+}
+
+// The first non-empty line, cut to `width` characters.
+pub(crate) fn truncate(value: &str, width: usize) -> String {
+    let line = value.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+    if line.chars().count() > width || line.trim().len() < value.trim().len() {
+        format!("{}…", line.chars().take(width).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_start_reads_positions_offsets_and_times() {
+        assert_eq!(parse_start("earliest"), Ok(StartPosition::Beginning));
+        assert_eq!(parse_start("latest"), Ok(StartPosition::End));
+        assert_eq!(parse_start("42"), Ok(StartPosition::Offset(42)));
+        assert_eq!(parse_start("2024-01-01T00:00:00Z"), Ok(StartPosition::Timestamp(1_704_067_200_000)));
+        assert_eq!(parse_start("2024-01-01T01:00:00+01:00"), Ok(StartPosition::Timestamp(1_704_067_200_000)));
+    }
+
+    #[test]
+    fn parse_start_rejects_anything_else() {
+        assert!(parse_start("yesterday").is_err());
+        assert!(parse_start("Earliest").is_err());
+        assert!(parse_start("").is_err());
+    }
+
+    #[test]
+    fn parse_timestamp_reads_millis_and_rfc3339() {
+        assert_eq!(parse_timestamp("1704067200000"), Ok(1_704_067_200_000));
+        assert_eq!(parse_timestamp("2024-01-01T00:00:00.5Z"), Ok(1_704_067_200_500));
+        assert!(parse_timestamp("2024-01-01").is_err());
+        assert!(parse_timestamp("earliest").is_err());
+    }
+
+    #[test]
+    fn truncate_cuts_long_lines() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate("a longer value", 8), "a longer…");
+        assert_eq!(truncate("", 8), "");
+    }
+
+    #[test]
+    fn truncate_marks_further_lines() {
+        assert_eq!(truncate("{\n  \"id\": 1\n}", 20), "{…");
+        assert_eq!(truncate("single line\n", 20), "single line");
+    }
+
+    #[test]
+    fn truncate_skips_leading_empty_lines() {
+        assert_eq!(truncate("\n{\"id\": 1}", 20), "{\"id\": 1}");
+        assert_eq!(truncate("\r\n  \n{\"id\": 1}\n{\"id\": 2}", 20), "{\"id\": 1}…");
+    }
+}
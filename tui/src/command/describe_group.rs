@@ -0,0 +1,141 @@
+use crate::app::{App, Command, ConfirmAction, Outcome, PopupType};
+use crate::command::list_groups::ListGroupsState;
+use crate::confirm::{Confirmation, ConfirmWidget};
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::group::OffsetResetTarget;
+use common::kafka::types::GroupPartitionLagEntry;
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Modifier, Stylize, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+pub fn create_describe_group_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Topic").sort_by(SortKey::Text("topic")),
+            Column::new("Partition").right().sort_by(SortKey::Number("partition")),
+            Column::new("Committed").right().sort_by(SortKey::Number("committed")),
+            Column::new("End Offset").right().sort_by(SortKey::Number("high_watermark")),
+            Column::new("Lag").right().sort_by(SortKey::Number("lag")),
+            Column::new("Client Id").sort_by(SortKey::Text("client_id")),
+            Column::new("Host").sort_by(SortKey::Text("host")),
+        ]
+    ).selectable(true)
+}
+
+pub fn table_from<'a>(data: Vec<GroupPartitionLagEntry>) -> TableData<'a> {
+    let mut longest_topic = 0;
+    let mut longest_client_id = 0;
+    let mut longest_host = 0;
+    let optional = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or("-".to_string());
+
+    TableData::new(
+        data.iter().map(|r| {
+            let client_id = r.client_id.clone().unwrap_or("-".to_string());
+            let host = r.host.clone().unwrap_or("-".to_string());
+            longest_topic = max(longest_topic, constraint_len_calculator(r.topic.as_str()));
+            longest_client_id = max(longest_client_id, constraint_len_calculator(client_id.as_str()));
+            longest_host = max(longest_host, constraint_len_calculator(host.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(r.topic.clone()),
+                    Cell::from(Text::from(r.partition.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(optional(r.committed)).alignment(Alignment::Right)),
+                    Cell::from(Text::from(optional(r.high_watermark)).alignment(Alignment::Right)),
+                    Cell::from(Text::from(optional(r.lag)).alignment(Alignment::Right)),
+                    Cell::from(client_id),
+                    Cell::from(host),
+                ]
+            );
+            if r.client_id.is_none() {
+                row.add_modifier(Modifier::DIM)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(max(longest_topic, 5) + 1),
+            Constraint::Min(10),
+            Constraint::Min(12),
+            Constraint::Min(12),
+            Constraint::Min(10),
+            Constraint::Min(max(longest_client_id, 9) + 1),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(format!("{}-{}", r.topic, r.partition), format!("{r:?}"))).collect())
+        .with_records(&data)
+}
+
+#[derive(Debug, Clone)]
+pub struct DescribeGroupState {
+    group: String,
+    partitions: Vec<GroupPartitionLagEntry>,
+}
+
+impl DescribeGroupState {
+    pub fn new(group: String) -> Self {
+        Self { group, partitions: vec![] }
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn set_partitions(&mut self, partitions: Vec<GroupPartitionLagEntry>) {
+        self.partitions = partitions;
+    }
+
+    pub fn partitions(&self) -> &[GroupPartitionLagEntry] {
+        &self.partitions
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: DescribeGroupState) {
+    match action {
+        Action::Back => app.load_view(Command::ListGroups(ListGroupsState::default())),
+        Action::ResetEarliest | Action::ResetLatest => {
+            let partitions = app.table.selected_or_current().iter()
+                .filter_map(|i| state.partitions.get(*i))
+                .map(|p| (p.topic.clone(), p.partition))
+                .collect::<Vec<_>>();
+            if partitions.is_empty() {
+                return;
+            }
+            let (target, message) = if action == Action::ResetEarliest {
+                (OffsetResetTarget::Earliest, format!("Reset the offsets of {} to the earliest? The group reads these partitions again.", state.group))
+            } else {
+                (OffsetResetTarget::Latest, format!("Reset the offsets of {} to the latest? The group skips the records it has not read.", state.group))
+            };
+            let affected = partitions.iter().map(|(topic, partition)| format!("{topic}-{partition}")).collect();
+            let action = ConfirmAction::ResetOffsets(app.config.clone(), state.group.clone(), partitions, target);
+            app.confirm(Confirmation::new(message, action).affecting(affected).expecting(state.group.clone()));
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn reset_offsets(app: &mut App<'_>, config: Config, group: String, partitions: Vec<(String, i32)>, target: OffsetResetTarget) {
+    app.spawn_blocking("Resetting offsets".to_string(), move || {
+        match kafka::group::reset_offsets(&config, &group, &partitions, target) {
+            Ok(results) => {
+                let errors = results.iter()
+                    .filter_map(|r| r.error.as_ref().map(|e| format!("{}-{}, {}", r.topic, r.partition, e)))
+                    .collect::<Vec<_>>();
+
+                if errors.is_empty() {
+                    Outcome::Popup(PopupType::SUCCESS, format!("Reset the offsets of {} partitions of {group}", results.len()))
+                } else {
+                    Outcome::Popup(PopupType::ERROR, format!("Failed to reset offsets:\n{}", errors.join("\n ")))
+                }
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
+        }
+    });
+}
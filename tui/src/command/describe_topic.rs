@@ -0,0 +1,351 @@
+use crate::app::{App, Command};
+use crate::command::browse_topic::{self, BrowseTopicState};
+use crate::command::describe_group::DescribeGroupState;
+use crate::command::list_topics::ListTopicsState;
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use common::kafka::message::StartPosition;
+use common::kafka::types::{DescribeConfigEntry, MessageEntry, MessagePageEntry, TopicGroupLagEntry, TopicPartitionEntry};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Line, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+/// A tab of the topic detail screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tab {
+    #[default]
+    Partitions,
+    Configuration,
+    Groups,
+    Messages,
+}
+
+pub(crate) const TABS: [Tab; 4] = [Tab::Partitions, Tab::Configuration, Tab::Groups, Tab::Messages];
+
+impl Tab {
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Tab::Partitions => "partitions",
+            Tab::Configuration => "configuration",
+            Tab::Groups => "consumer groups",
+            Tab::Messages => "messages",
+        }
+    }
+
+    fn index(self) -> usize {
+        TABS.iter().position(|tab| *tab == self).unwrap_or_default()
+    }
+}
+
+pub fn create_describe_topic_table_definition(tab: Tab) -> TableDefinition {
+    match tab {
+        Tab::Partitions => TableDefinition::new(
+            vec![
+                Column::new("Partition").right().sort_by(SortKey::Number("partition")),
+                Column::new("Leader").right().sort_by(SortKey::Number("leader")),
+                Column::new("Replicas"),
+                Column::new("ISR"),
+                Column::new("Low Watermark").right().sort_by(SortKey::Number("low_watermark")),
+                Column::new("High Watermark").right().sort_by(SortKey::Number("high_watermark")),
+            ]
+        ),
+        Tab::Configuration => TableDefinition::new(
+            vec![
+                Column::new("Name").sort_by(SortKey::Text("name")),
+                Column::new("Value").sort_by(SortKey::Text("value")),
+                Column::new("Source").sort_by(SortKey::Text("source")),
+            ]
+        ),
+        Tab::Groups => TableDefinition::new(
+            vec![
+                Column::new("Group").sort_by(SortKey::Text("group")),
+                Column::new("State").sort_by(SortKey::Text("state")),
+                Column::new("Members").right().sort_by(SortKey::Number("members")),
+                Column::new("Partitions").right().sort_by(SortKey::Number("partitions")),
+                Column::new("Lag").right().sort_by(SortKey::Number("lag")),
+            ]
+        ),
+        Tab::Messages => browse_topic::create_browse_topic_table_definition(),
+    }
+}
+
+/// The rows of the tab shown.
+pub fn table_from<'a>(state: &DescribeTopicState, theme: &Theme) -> TableData<'a> {
+    match state.tab {
+        Tab::Partitions => partitions_table(&state.partitions),
+        Tab::Configuration => configs_table(state.configs.as_deref().unwrap_or_default(), theme),
+        Tab::Groups => groups_table(state.groups()),
+        Tab::Messages => browse_topic::table_from(state.messages().to_vec()),
+    }
+}
+
+fn partitions_table<'a>(data: &[TopicPartitionEntry]) -> TableData<'a> {
+    let mut longest_replicas = 0;
+    let mut longest_isr = 0;
+    let brokers = |ids: &[i32]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    let optional = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or("-".to_string());
+
+    TableData::new(
+        data.iter().map(|r| {
+            let replicas = brokers(&r.replicas);
+            let isr = brokers(&r.isr);
+            longest_replicas = max(longest_replicas, constraint_len_calculator(replicas.as_str()));
+            longest_isr = max(longest_isr, constraint_len_calculator(isr.as_str()));
+            Row::new(
+                vec![
+                    Cell::from(Text::from(r.partition.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.leader.map(|l| l.to_string()).unwrap_or("-".to_string())).alignment(Alignment::Right)),
+                    Cell::from(replicas),
+                    Cell::from(isr),
+                    Cell::from(Text::from(optional(r.low_watermark)).alignment(Alignment::Right)),
+                    Cell::from(Text::from(optional(r.high_watermark)).alignment(Alignment::Right)),
+                ]
+            )
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(10),
+            Constraint::Min(7),
+            Constraint::Min(max(longest_replicas, 8) + 1),
+            Constraint::Min(max(longest_isr, 3) + 1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.partition.to_string(), format!("{r:?}"))).collect())
+        .with_records(data)
+}
+
+fn configs_table<'a>(data: &[DescribeConfigEntry], theme: &Theme) -> TableData<'a> {
+    let mut longest_name = 0;
+    let mut longest_source = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            longest_name = max(longest_name, constraint_len_calculator(r.name.as_str()));
+            longest_source = max(longest_source, constraint_len_calculator(r.source.as_str()));
+            let value = if r.is_sensitive { "(sensitive)".to_string() } else { r.value.clone().unwrap_or_default() };
+            let row = Row::new(
+                vec![
+                    Cell::from(r.name.clone()),
+                    Cell::from(value),
+                    Cell::from(r.source.clone()),
+                ]
+            );
+            // values set on purpose stand out from the defaults.
+            if r.is_default {
+                row
+            } else {
+                row.style(theme.highlight)
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(longest_name + 1),
+            Constraint::Fill(1),
+            Constraint::Min(max(longest_source, 6)),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.name.clone(), format!("{r:?}"))).collect())
+        .with_records(data)
+}
+
+fn groups_table<'a>(data: &[TopicGroupLagEntry]) -> TableData<'a> {
+    let mut longest_group = 0;
+    let mut longest_state = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            longest_group = max(longest_group, constraint_len_calculator(r.group.as_str()));
+            longest_state = max(longest_state, constraint_len_calculator(r.state.as_str()));
+            Row::new(
+                vec![
+                    Cell::from(r.group.clone()),
+                    Cell::from(r.state.clone()),
+                    Cell::from(Text::from(r.members.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.partitions.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.lag.to_string()).alignment(Alignment::Right)),
+                ]
+            )
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(max(longest_group, 5) + 1),
+            Constraint::Min(max(longest_state, 5) + 1),
+            Constraint::Min(8),
+            Constraint::Min(11),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.group.clone(), format!("{r:?}"))).collect())
+        .with_records(data)
+}
+
+/// The details of a topic, the tabs are loaded at once so switching tabs is immediate.
+///
+/// The consumer groups are the exception, their lag takes a request per group so they are only loaded
+/// with the groups tab shown.
+#[derive(Debug, Clone)]
+pub struct DescribeTopicState {
+    topic: String,
+    tab: Tab,
+    /// The partition of the messages tab.
+    partition: i32,
+    partitions: Vec<TopicPartitionEntry>,
+    /// The config of the topic, or why it could not be described, the other tabs show either way.
+    configs: Result<Vec<DescribeConfigEntry>, String>,
+    /// The groups reading the topic, or why they could not be listed, `None` until the groups tab is shown.
+    groups: Option<Result<Vec<TopicGroupLagEntry>, String>>,
+    /// The last records of the partition, or why they could not be read.
+    messages: Result<Option<MessagePageEntry>, String>,
+}
+
+impl DescribeTopicState {
+    pub fn new(topic: String) -> Self {
+        Self {
+            topic,
+            tab: Tab::default(),
+            partition: 0,
+            partitions: vec![],
+            configs: Ok(vec![]),
+            groups: None,
+            messages: Ok(None),
+        }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn tab(&self) -> Tab {
+        self.tab
+    }
+
+    pub fn partition(&self) -> i32 {
+        self.partition
+    }
+
+    /// Sets what was loaded, the groups are kept as they were when not loaded.
+    pub fn set_details(
+        &mut self,
+        partitions: Vec<TopicPartitionEntry>,
+        configs: Result<Vec<DescribeConfigEntry>, String>,
+        groups: Option<Result<Vec<TopicGroupLagEntry>, String>>,
+        messages: Result<MessagePageEntry, String>,
+    ) {
+        self.partitions = partitions;
+        self.configs = configs;
+        if groups.is_some() {
+            self.groups = groups;
+        }
+        self.messages = messages.map(Some);
+    }
+
+    fn groups(&self) -> &[TopicGroupLagEntry] {
+        self.groups.as_ref().and_then(|groups| groups.as_deref().ok()).unwrap_or_default()
+    }
+
+    pub fn messages(&self) -> &[MessageEntry] {
+        self.messages.as_ref().ok().and_then(Option::as_ref).map(|p| p.messages.as_slice()).unwrap_or_default()
+    }
+
+    /// Whether both states show the same tab of the same topic, a reload rather than a switch.
+    pub fn same_tab(&self, other: &DescribeTopicState) -> bool {
+        self.topic == other.topic && self.tab == other.tab && self.partition == other.partition
+    }
+
+    fn with_tab(&self, tab: Tab) -> Self {
+        Self { tab, ..self.clone() }
+    }
+
+    // The last records of the partition, in the messages tab.
+    fn with_partition(&self, partition: i32) -> Self {
+        Self { tab: Tab::Messages, partition, messages: Ok(None), ..self.clone() }
+    }
+}
+
+/// The titles of the tabs, with the index of the one shown.
+pub(crate) fn tabs(state: &DescribeTopicState) -> (Vec<String>, usize) {
+    let titles = TABS.iter()
+        .map(|tab| match tab {
+            Tab::Messages => format!("{} of partition {}", tab.title(), state.partition),
+            tab => tab.title().to_string(),
+        })
+        .collect();
+    (titles, state.tab.index())
+}
+
+/// What the tab shows, above its table.
+pub(crate) fn summary(state: &DescribeTopicState, theme: &Theme) -> Line<'static> {
+    match state.tab {
+        Tab::Partitions => {
+            let under_replicated = state.partitions.iter().filter(|p| p.isr.len() < p.replicas.len()).count();
+            let offline = state.partitions.iter().filter(|p| p.leader.is_none()).count();
+            Line::from(format!("{} partitions, {under_replicated} under replicated, {offline} offline", state.partitions.len()))
+        }
+        Tab::Configuration => match &state.configs {
+            Ok(configs) => {
+                let overridden = configs.iter().filter(|c| !c.is_default).count();
+                Line::from(format!("{} entries, {overridden} set on the topic or its brokers are highlighted", configs.len()))
+            }
+            Err(err) => Line::from(format!("Failed to describe the config: {err}")).style(theme.error),
+        },
+        Tab::Groups => match &state.groups {
+            Some(Ok(groups)) => Line::from(format!("{} consumer groups committed offsets on the topic", groups.len())),
+            Some(Err(err)) => Line::from(format!("Failed to list the consumer groups: {err}")).style(theme.error),
+            None => Line::from("Loading the consumer groups and their lag"),
+        },
+        Tab::Messages => match &state.messages {
+            Ok(Some(page)) => Line::from(format!(
+                "last {} records of partition {}, offsets {} to {}",
+                page.messages.len(), page.partition, page.low_watermark, page.high_watermark,
+            )),
+            Ok(None) => Line::from(""),
+            Err(err) => Line::from(format!("Failed to read the records: {err}")).style(theme.error),
+        },
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: DescribeTopicState) {
+    let current = app.table.current();
+    match action {
+        Action::Back => app.load_view(Command::ListTopics(ListTopicsState::default())),
+        Action::NextTab => show_tab(app, state.with_tab(TABS[(state.tab.index() + 1) % TABS.len()])),
+        Action::PreviousTab => show_tab(app, state.with_tab(TABS[(state.tab.index() + TABS.len() - 1) % TABS.len()])),
+        Action::Open => match state.tab {
+            Tab::Partitions => {
+                if let Some(partition) = current.and_then(|i| state.partitions.get(i)) {
+                    app.load_view(Command::DescribeTopic(state.with_partition(partition.partition)));
+                }
+            }
+            Tab::Groups => {
+                if let Some(group) = current.and_then(|i| state.groups().get(i)) {
+                    app.load_view(Command::DescribeGroup(DescribeGroupState::new(group.group.clone())));
+                }
+            }
+            Tab::Messages => {
+                if let Some(message) = current.and_then(|i| state.messages().get(i)) {
+                    let browse = BrowseTopicState::new(state.topic.clone())
+                        .starting_at(message.partition, StartPosition::Offset(message.offset));
+                    app.load_view(Command::BrowseTopic(browse));
+                }
+            }
+            Tab::Configuration => {}
+        },
+        _ => {}
+    }
+}
+
+// Switches tabs right away, the groups tab loads the groups behind it.
+//
+// A load of the previous tab would switch back to it once done, it is dropped.
+fn show_tab(app: &mut App<'_>, state: DescribeTopicState) {
+    let load = state.tab == Tab::Groups;
+    app.drop_pending_view();
+    app.show(Command::DescribeTopic(state.clone()));
+    if load {
+        app.load_view(Command::DescribeTopic(state));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use common::kafka::types::{HealthIssueKind, HealthReportEntry};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Line, Modifier, Span, Style, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+const ISSUE_KINDS: [HealthIssueKind; 6] = [
+    HealthIssueKind::Offline,
+    HealthIssueKind::UnderReplicated,
+    HealthIssueKind::BelowMinIsr,
+    HealthIssueKind::NonPreferredLeader,
+    HealthIssueKind::BrokerWithoutLeadership,
+    HealthIssueKind::SkewedTopic,
+];
+
+pub fn create_health_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Issue").sort_by(SortKey::Text("kind")),
+            Column::new("Topic").sort_by(SortKey::Text("topic")),
+            Column::new("Partition").right().sort_by(SortKey::Number("partition")),
+            Column::new("Broker").right().sort_by(SortKey::Number("broker")),
+            Column::new("Detail"),
+        ]
+    )
+}
+
+pub fn table_from<'a>(report: &HealthReportEntry, theme: &Theme) -> TableData<'a> {
+    let mut longest_kind = 0;
+    let mut longest_topic = 0;
+
+    TableData::new(
+        report.issues.iter().map(|r| {
+            longest_kind = max(longest_kind, constraint_len_calculator(r.kind.name()));
+            longest_topic = max(longest_topic, constraint_len_calculator(r.topic.as_deref().unwrap_or("")));
+            Row::new(
+                vec![
+                    Cell::from(r.kind.name()).style(issue_style(&r.kind, theme)),
+                    Cell::from(r.topic.clone().unwrap_or_default()),
+                    Cell::from(Text::from(r.partition.map(|p| p.to_string()).unwrap_or_default()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.broker.map(|b| b.to_string()).unwrap_or_default()).alignment(Alignment::Right)),
+                    Cell::from(r.detail.clone()),
+                ]
+            )
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(longest_kind + 1),
+            Constraint::Min(longest_topic + 1),
+            Constraint::Min(10),
+            Constraint::Min(7),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(report.issues.iter().map(|r| {
+            let id = format!("{} {:?} {:?} {:?}", r.kind.name(), r.topic, r.partition, r.broker);
+            RowKey::new(id, r.detail.clone())
+        }).collect())
+        .with_records(&report.issues)
+}
+
+/// Summary lines shown above the issues table, one count per kind of issue.
+pub fn summary<'a>(report: &HealthReportEntry, theme: &Theme) -> Text<'a> {
+    let totals = Line::from(format!(
+        "{} brokers, {} topics, {} partitions",
+        report.brokers, report.topics, report.partitions,
+    ));
+    let counts = ISSUE_KINDS.iter()
+        .flat_map(|kind| {
+            let count = report.issues.iter().filter(|i| i.kind == *kind).count();
+            let style = if count == 0 { theme.success } else { issue_style(kind, theme) };
+            vec![
+                Span::styled(format!("{}: {}", kind.name(), count), style),
+                Span::raw("  "),
+            ]
+        })
+        .collect::<Vec<_>>();
+    Text::from(vec![totals, Line::from(counts)])
+}
+
+fn issue_style(kind: &HealthIssueKind, theme: &Theme) -> Style {
+    match kind {
+        HealthIssueKind::Offline | HealthIssueKind::BelowMinIsr => theme.error,
+        HealthIssueKind::UnderReplicated => theme.error.remove_modifier(Modifier::BOLD),
+        _ => theme.warning,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthState {
+    report: Option<HealthReportEntry>,
+}
+
+impl HealthState {
+    pub fn set_report(&mut self, report: HealthReportEntry) {
+        self.report = Some(report);
+    }
+
+    pub fn report(&self) -> Option<&HealthReportEntry> {
+        self.report.as_ref()
+    }
+}
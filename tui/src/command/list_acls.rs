@@ -0,0 +1,77 @@
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use common::kafka::types::{AclBindingEntry, AclPermission};
+use ratatui::layout::Constraint;
+use ratatui::prelude::Style;
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+pub fn create_list_acls_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Resource Type").sort_by(SortKey::Text("resource_type")),
+            Column::new("Resource Name").sort_by(SortKey::Text("resource_name")),
+            Column::new("Pattern").sort_by(SortKey::Text("pattern_type")),
+            Column::new("Principal").sort_by(SortKey::Text("principal")),
+            Column::new("Host").sort_by(SortKey::Text("host")),
+            Column::new("Operation").sort_by(SortKey::Text("operation")),
+            Column::new("Permission").sort_by(SortKey::Text("permission")),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<AclBindingEntry>, theme: &Theme) -> TableData<'a> {
+    let mut longest_principal = 0;
+    let mut longest_host = 0;
+    let mut longest_operation = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            longest_principal = max(longest_principal, constraint_len_calculator(r.principal.as_str()));
+            longest_host = max(longest_host, constraint_len_calculator(r.host.as_str()));
+            longest_operation = max(longest_operation, constraint_len_calculator(r.operation.name()));
+            let permission_style = match r.permission {
+                AclPermission::Deny => theme.error,
+                _ => Style::new(),
+            };
+            Row::new(
+                vec![
+                    Cell::from(r.resource_type.name()),
+                    Cell::from(r.resource_name.clone()),
+                    Cell::from(r.pattern_type.name()),
+                    Cell::from(r.principal.clone()),
+                    Cell::from(r.host.clone()),
+                    Cell::from(r.operation.name()),
+                    Cell::from(r.permission.name()).style(permission_style),
+                ]
+            )
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(14),
+            Constraint::Fill(1),
+            Constraint::Min(9),
+            Constraint::Min(max(longest_principal, 9) + 1),
+            Constraint::Min(max(longest_host, 4) + 1),
+            Constraint::Min(max(longest_operation, 9) + 1),
+            Constraint::Min(10),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(format!("{r:?}"), "")).collect())
+        .with_records(&data)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListAclsState {
+    acls: Vec<AclBindingEntry>,
+}
+
+impl ListAclsState {
+    pub fn set_acls(&mut self, acls: Vec<AclBindingEntry>) {
+        self.acls = acls;
+    }
+
+    pub fn acls(&self) -> &[AclBindingEntry] {
+        &self.acls
+    }
+}
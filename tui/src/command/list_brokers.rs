@@ -0,0 +1,94 @@
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use common::kafka::types::{format_bytes, ListBrokerEntry};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Modifier, Stylize, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+pub fn create_list_brokers_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("ID").right().sort_by(SortKey::Number("id")),
+            Column::new("Host").sort_by(SortKey::Text("host")),
+            Column::new("Port").right().sort_by(SortKey::Number("port")),
+            Column::new("Rack").sort_by(SortKey::Text("rack")),
+            Column::new("Controller"),
+            Column::new("Leaders").right().sort_by(SortKey::Number("leaders")),
+            Column::new("Replicas").right().sort_by(SortKey::Number("replicas")),
+            Column::new("Log Dir Size").right().sort_by(SortKey::Number("log_dir_size")),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<ListBrokerEntry>) -> TableData<'a> {
+    let mut longest_id = 0;
+    let mut longest_port = 0;
+    let mut longest_rack = 0;
+    let mut longest_leaders = 0;
+    let mut longest_replicas = 0;
+    let total_leaders = data.iter().map(|b| b.leaders).sum::<usize>();
+    let total_replicas = data.iter().map(|b| b.replicas).sum::<usize>();
+    // the share of the total, to compare brokers at a glance.
+    let share = |count: usize, total: usize| {
+        let percent = (count * 100).checked_div(total).unwrap_or(0);
+        format!("{count} ({percent}%)")
+    };
+
+    TableData::new(
+        data.iter().map(|r| {
+            let rack = r.rack.clone().unwrap_or("-".to_string());
+            let leaders = share(r.leaders, total_leaders);
+            let replicas = share(r.replicas, total_replicas);
+            longest_id = max(longest_id, constraint_len_calculator(r.id.to_string().as_str()));
+            longest_port = max(longest_port, constraint_len_calculator(r.port.to_string().as_str()));
+            longest_rack = max(longest_rack, constraint_len_calculator(rack.as_str()));
+            longest_leaders = max(longest_leaders, constraint_len_calculator(leaders.as_str()));
+            longest_replicas = max(longest_replicas, constraint_len_calculator(replicas.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(Text::from(r.id.to_string()).alignment(Alignment::Right)),
+                    Cell::from(r.host.clone()),
+                    Cell::from(Text::from(r.port.to_string()).alignment(Alignment::Right)),
+                    Cell::from(rack),
+                    Cell::from(if r.controller { "yes" } else { "" }),
+                    Cell::from(Text::from(leaders).alignment(Alignment::Right)),
+                    Cell::from(Text::from(replicas).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.log_dir_size.map(format_bytes).unwrap_or("-".to_string())).alignment(Alignment::Right)),
+                ]
+            );
+            if r.controller {
+                row.add_modifier(Modifier::BOLD)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(max(longest_id, 2) + 1),
+            Constraint::Fill(1),
+            Constraint::Min(max(longest_port, 4) + 1),
+            Constraint::Min(max(longest_rack, 4) + 1),
+            Constraint::Min(11),
+            Constraint::Min(max(longest_leaders, 7) + 1),
+            Constraint::Min(max(longest_replicas, 8) + 1),
+            Constraint::Min(12),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.id.to_string(), format!("{r:?}"))).collect())
+        .with_records(&data)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListBrokersState {
+    brokers: Vec<ListBrokerEntry>,
+}
+
+impl ListBrokersState {
+    pub fn set_brokers(&mut self, brokers: Vec<ListBrokerEntry>) {
+        self.brokers = brokers;
+    }
+
+    pub fn brokers(&self) -> &[ListBrokerEntry] {
+        &self.brokers
+    }
+}
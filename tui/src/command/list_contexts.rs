@@ -0,0 +1,107 @@
+use crate::app::{App, PopupType, PopupWidget};
+use crate::keys::Action;
+use crate::settings::{Cluster, Profile};
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use ratatui::layout::Constraint;
+use ratatui::widgets::{Cell, Row};
+use serde::Serialize;
+use std::cmp::max;
+use std::collections::BTreeMap;
+
+/// A context of the settings file, as listed in the cluster picker.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextEntry {
+    pub name: String,
+    pub bootstrap_servers: String,
+    pub auth: String,
+    pub production: bool,
+    pub read_only: bool,
+    /// Whether the app is connected to it.
+    pub active: bool,
+}
+
+pub fn create_list_contexts_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new(""),
+            Column::new("Name").sort_by(SortKey::Text("name")),
+            Column::new("Bootstrap Servers").sort_by(SortKey::Text("bootstrap_servers")),
+            Column::new("Auth").sort_by(SortKey::Text("auth")),
+            Column::new("Production").sort_by(SortKey::Text("production")),
+            Column::new("Read-only").sort_by(SortKey::Text("read_only")),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<ContextEntry>, theme: &Theme) -> TableData<'a> {
+    let mut longest_name = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            longest_name = max(longest_name, constraint_len_calculator(r.name.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(if r.active { "*" } else { "" }),
+                    Cell::from(r.name.clone()),
+                    Cell::from(r.bootstrap_servers.clone()),
+                    Cell::from(r.auth.clone()),
+                    Cell::from(if r.production { "yes" } else { "" }),
+                    Cell::from(if r.read_only { "yes" } else { "" }),
+                ]
+            );
+            if r.production {
+                row.style(theme.production)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            Constraint::Length(1),
+            // + 1 is for padding.
+            Constraint::Min(max(longest_name, 4) + 1),
+            Constraint::Fill(1),
+            Constraint::Min(5),
+            Constraint::Min(10),
+            Constraint::Min(9),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.name.clone(), format!("{r:?}"))).collect())
+        .with_records(&data)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListContextsState {
+    contexts: Vec<ContextEntry>,
+}
+
+impl ListContextsState {
+    /// Lists the contexts, they come from the settings rather than from the cluster.
+    pub(crate) fn set_contexts(&mut self, contexts: &BTreeMap<String, Profile>, cluster: &Cluster) {
+        self.contexts = contexts.iter()
+            .map(|(name, profile)| ContextEntry {
+                name: name.clone(),
+                bootstrap_servers: profile.bootstrap_servers.clone(),
+                auth: if profile.iam_auth { "IAM".to_string() } else { "none".to_string() },
+                production: profile.production,
+                read_only: profile.read_only,
+                active: *name == cluster.name,
+            })
+            .collect();
+    }
+
+    pub fn contexts(&self) -> &[ContextEntry] {
+        &self.contexts
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: ListContextsState) {
+    if let Action::Connect = action {
+        let current = app.table.current().and_then(|i| state.contexts.get(i));
+        if let Some(row) = current {
+            if let Err(err) = app.switch_context(&row.name) {
+                app.open(PopupType::ERROR, err);
+            }
+        }
+    }
+}
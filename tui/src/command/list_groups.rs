@@ -0,0 +1,161 @@
+use crate::app::{App, Command, ConfirmAction, Outcome, PopupType};
+use crate::command::describe_group::DescribeGroupState;
+use crate::confirm::{Confirmation, ConfirmWidget};
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::types::ListConsumerGroupEntry;
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Modifier, Style, Stylize, Text};
+use ratatui::widgets::{Cell, Row};
+use rdkafka::error::KafkaError;
+use serde::{Serialize, Serializer};
+use std::cmp::max;
+
+pub fn create_list_groups_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Name").sort_by(SortKey::Text("name")),
+            Column::new("State").sort_by(SortKey::Text("state")),
+            Column::new("Members").right().sort_by(SortKey::Number("members")),
+            Column::new("Lag").right().sort_by(SortKey::Number("lag")),
+        ]
+    ).selectable(true)
+}
+
+pub fn table_from<'a>(data: Vec<GroupRow>, theme: &Theme) -> TableData<'a> {
+    let mut longest_state = 0;
+    let mut longest_lag = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            let (lag, lag_style) = match &r.lag {
+                Ok(lag) => (lag.to_string(), Style::default()),
+                Err(err) => (err.clone(), theme.error),
+            };
+            longest_state = max(longest_state, constraint_len_calculator(r.group.state.as_str()));
+            longest_lag = max(longest_lag, constraint_len_calculator(lag.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(r.group.name.clone()),
+                    Cell::from(r.group.state.clone()),
+                    Cell::from(Text::from(r.group.members.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(lag).alignment(Alignment::Right)).style(lag_style),
+                ]
+            );
+            if r.group.state == "Empty" || r.group.state == "Dead" {
+                row.add_modifier(Modifier::DIM)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Fill(1),
+            Constraint::Min(max(longest_state, 5) + 1),
+            Constraint::Min(8),
+            Constraint::Min(max(longest_lag, 3)),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(r.group.name.clone(), format!("{r:?}"))).collect())
+        .with_records(&data)
+}
+
+/// A consumer group with its total lag, or why it could not be fetched.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupRow {
+    #[serde(flatten)]
+    pub group: ListConsumerGroupEntry,
+    #[serde(serialize_with = "lag_or_error")]
+    pub lag: Result<i64, String>,
+}
+
+// The lag as a number for filters and sorts, the error as text.
+fn lag_or_error<S: Serializer>(lag: &Result<i64, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    match lag {
+        Ok(lag) => serializer.serialize_i64(*lag),
+        Err(err) => serializer.serialize_str(err),
+    }
+}
+
+/// Lists the groups with the lag summed over their partitions, in a single pass over all of them.
+pub(crate) fn fetch_groups(config: &Config, prefix: Option<String>) -> Result<Vec<GroupRow>, KafkaError> {
+    let groups = kafka::group::list(config, prefix)?;
+    let names = groups.iter().map(|g| g.name.clone()).collect::<Vec<_>>();
+    let lags = kafka::group::total_lag(config, &names);
+    Ok(groups.into_iter()
+        .zip(lags)
+        .map(|(group, lag)| GroupRow { group, lag: lag.map_err(|err| format!("failed: {err}")) })
+        .collect())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListGroupsState {
+    /// Only groups starting with it are listed.
+    prefix: Option<String>,
+    groups: Vec<GroupRow>,
+}
+
+impl ListGroupsState {
+    pub fn with_prefix(prefix: Option<String>) -> Self {
+        Self { prefix, ..Default::default() }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
+    pub fn set_groups(&mut self, groups: Vec<GroupRow>) {
+        self.groups = groups;
+    }
+
+    pub fn groups(&self) -> &[GroupRow] {
+        &self.groups
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: ListGroupsState) {
+    match action {
+        Action::Describe => {
+            let current = app.table.current().and_then(|i| state.groups.get(i));
+            if let Some(row) = current {
+                app.load_view(Command::DescribeGroup(DescribeGroupState::new(row.group.name.clone())));
+            }
+        }
+        Action::Delete => {
+            let groups = app.table.selected_or_current().iter()
+                .filter_map(|i| state.groups.get(*i))
+                .map(|r| r.group.name.clone())
+                .collect::<Vec<_>>();
+            if !groups.is_empty() {
+                let message = "Delete these consumer groups? Their committed offsets are lost.".to_string();
+                app.confirm(Confirmation::new(message, ConfirmAction::DeleteGroups(app.config.clone(), groups.clone())).affecting(groups).typed());
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn delete_groups(app: &mut App<'_>, config: Config, groups: Vec<String>) {
+    app.spawn("Deleting consumer groups".to_string(), async move {
+        match kafka::group::delete_groups(&config, &groups).await {
+            Ok(res) => {
+                let errors = res.iter()
+                    .filter_map(|r| r.as_ref().err())
+                    .map(|(group, error)| format!("{}, {}", group, error))
+                    .collect::<Vec<_>>();
+
+                if errors.is_empty() {
+                    Outcome::Popup(PopupType::SUCCESS, "Consumer groups deleted successfully".to_string())
+                } else {
+                    Outcome::Popup(PopupType::ERROR, format!("Failed to delete consumer groups:\n{}", errors.join("\n ")))
+                }
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
+        }
+    });
+}
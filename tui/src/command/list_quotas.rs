@@ -0,0 +1,61 @@
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use common::kafka::quota::entity_label;
+use common::kafka::types::{ClientQuotaEntry, QuotaKey};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+
+pub fn create_list_quotas_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Entity").sort_by(SortKey::Text("entity.name")),
+            Column::new("Producer Byte Rate").right().sort_by(SortKey::Number("quotas.producer_byte_rate")),
+            Column::new("Consumer Byte Rate").right().sort_by(SortKey::Number("quotas.consumer_byte_rate")),
+            Column::new("Request Percentage").right().sort_by(SortKey::Number("quotas.request_percentage")),
+            Column::new("Controller Mutation Rate").right().sort_by(SortKey::Number("quotas.controller_mutation_rate")),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<ClientQuotaEntry>) -> TableData<'a> {
+    let mut longest_entity = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            let entity = entity_label(&r.entity);
+            longest_entity = max(longest_entity, constraint_len_calculator(entity.as_str()));
+            let mut cells = vec![Cell::from(entity)];
+            cells.extend(QuotaKey::VARIANTS.iter().map(|key| {
+                let value = r.quotas.get(key.name()).map(|v| v.to_string()).unwrap_or_default();
+                Cell::from(Text::from(value).alignment(Alignment::Right))
+            }));
+            Row::new(cells)
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Min(max(longest_entity, 6) + 1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| RowKey::new(format!("{:?}", r.entity), format!("{:?}", r.quotas))).collect())
+        .with_records(&data)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListQuotasState {
+    quotas: Vec<ClientQuotaEntry>,
+}
+
+impl ListQuotasState {
+    pub fn set_quotas(&mut self, quotas: Vec<ClientQuotaEntry>) {
+        self.quotas = quotas;
+    }
+
+    pub fn quotas(&self) -> &[ClientQuotaEntry] {
+        &self.quotas
+    }
+}
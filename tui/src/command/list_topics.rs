@@ -1,26 +1,35 @@
-use crate::app::{App, PopupType, PopupWidget};
-use crate::table::{constraint_len_calculator, TableData, TableDefinition};
+use crate::app::{App, Command, ConfirmAction, Outcome, PopupType};
+use crate::command::browse_topic::BrowseTopicState;
+use crate::command::describe_topic::DescribeTopicState;
+use crate::command::tail::TailState;
+use crate::confirm::{Confirmation, ConfirmWidget};
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
 use common::kafka;
-use common::kafka::types::ListTopicEntry;
-use crossterm::event::{KeyCode, KeyEvent};
+use common::kafka::client::Config;
+use common::kafka::election::ElectionType;
+use common::kafka::topic::TruncateTarget;
+use common::kafka::types::{DeleteRecordsEntry, ElectionStatus, ListTopicEntry};
 use ratatui::layout::Constraint;
-use ratatui::prelude::{Alignment, Modifier, Style, Stylize, Text};
+use ratatui::prelude::{Alignment, Style, Text};
 use ratatui::widgets::{Cell, Row};
 use std::cmp::max;
+use std::collections::BTreeMap;
 
-pub fn create_list_topics_table_definition<'a>() -> TableDefinition<'a> {
+pub fn create_list_topics_table_definition() -> TableDefinition {
     TableDefinition::new(
         vec![
-            Cell::from("Name"),
-            Cell::from(Text::from("Partitions").alignment(Alignment::Right)),
-            Cell::from(Text::from("Replication Factor").alignment(Alignment::Right)),
-            Cell::from(Text::from("Message Count").alignment(Alignment::Right)),
-            Cell::from(Text::from("Size").alignment(Alignment::Right)),
+            Column::new("Name").sort_by(SortKey::Text("name")),
+            Column::new("Partitions").right().sort_by(SortKey::Number("partitions")),
+            Column::new("Replication Factor").right().sort_by(SortKey::Number("replication_factor")),
+            Column::new("Message Count").right().sort_by(SortKey::Number("message_count")),
+            Column::new("Size").right().sort_by(SortKey::Number("size")),
         ]
     ).selectable(true)
 }
 
-pub fn table_from<'a>(data: Vec<ListTopicEntry>) -> TableData<'a> {
+pub fn table_from<'a>(data: Vec<ListTopicEntry>, theme: &Theme) -> TableData<'a> {
     let mut longest_name = 0;
     let mut longest_partitions = 0;
     let mut longest_replication_factor = 0;
@@ -44,7 +53,7 @@ pub fn table_from<'a>(data: Vec<ListTopicEntry>) -> TableData<'a> {
                 ]
             );
             if r.name.starts_with("_") {
-                row.add_modifier(Modifier::DIM)
+                row.style(theme.dimmed)
             } else {
                 row
             }
@@ -58,62 +67,185 @@ pub fn table_from<'a>(data: Vec<ListTopicEntry>) -> TableData<'a> {
             Constraint::Min(longest_size),
         ]
     )
+        .with_keys(data.iter().map(|r| RowKey::new(r.name.clone(), format!("{r:?}"))).collect())
+        .with_records(&data)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ListTopicsState {
+    /// Only topics starting with it are listed.
+    prefix: Option<String>,
     topics: Vec<ListTopicEntry>,
 }
 
 impl ListTopicsState {
+    pub fn with_prefix(prefix: Option<String>) -> Self {
+        Self { prefix, ..Default::default() }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+
     pub fn set_topics(&mut self, topics: Vec<ListTopicEntry>) {
         self.topics = topics;
     }
+
+    pub fn topics(&self) -> &[ListTopicEntry] {
+        &self.topics
+    }
 }
 
-impl Default for ListTopicsState {
-    fn default() -> Self {
-        ListTopicsState { topics: Vec::new() }
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, state: ListTopicsState) {
+    match action {
+        Action::Elect => {
+            let topics = selected_or_current(app, &state);
+            if !topics.is_empty() {
+                elect_leaders(app, app.config.clone(), topics, ElectionType::Preferred);
+            }
+        }
+        Action::UncleanElect => {
+            let topics = selected_or_current(app, &state);
+            if !topics.is_empty() {
+                let message = "Unclean leader election can elect out of sync replicas and lose data.".to_string();
+                app.confirm(Confirmation::new(message, ConfirmAction::UncleanElection(app.config.clone(), topics.clone())).affecting(topics));
+            }
+        }
+        Action::Truncate => {
+            let topics = selected_or_current(app, &state);
+            if !topics.is_empty() {
+                plan_truncate(app, topics);
+            }
+        }
+        Action::Delete => {
+            let topics = selected_or_current(app, &state);
+            if !topics.is_empty() {
+                let message = "Delete these topics? Their records are lost.".to_string();
+                app.confirm(Confirmation::new(message, ConfirmAction::DeleteTopics(app.config.clone(), topics.clone())).affecting(topics).typed());
+            }
+        }
+        Action::Tail => {
+            let topics = selected_or_current(app, &state);
+            if !topics.is_empty() {
+                app.load_view(Command::Tail(TailState::new(topics)));
+            }
+        }
+        Action::Describe => {
+            if let Some(topic) = app.table.current().and_then(|i| state.topics.get(i)) {
+                app.load_view(Command::DescribeTopic(DescribeTopicState::new(topic.name.clone())));
+            }
+        }
+        Action::Browse => {
+            if let Some(topic) = app.table.current().and_then(|i| state.topics.get(i)) {
+                app.load_view(Command::BrowseTopic(BrowseTopicState::new(topic.name.clone())));
+            }
+        }
+        _ => {}
     }
 }
 
-pub(crate) async fn handle_key_event(key_event: KeyEvent, app: &mut App<'_>, state: ListTopicsState) {
-    match key_event.code {
-        KeyCode::Char('d') => {
-            let to_delete = app
-                .table
-                .selected
-                .iter()
-                .filter_map(|i| state.topics.get(*i))
-                .map(|t| t.name.to_string())
-                .collect::<Vec<_>>();
-
-            if to_delete.is_empty() {
-                return;
-            }
-
-            match kafka::topic::delete_topics(&app.config, to_delete).await {
-                Ok(res) => {
-                    let errors = res.iter().flat_map(|r| {
-                        if r.is_err() {
-                            Some(r.clone().unwrap_err())
-                        } else {
-                            None
-                        }
-                    }).map(|(topic, error)| format!("{}, {}", topic, error))
-                        .collect::<Vec<_>>();
-
-                    if errors.is_empty() {
-                        app.open(PopupType::SUCCESS, "Topics deleted successfully".to_string());
+pub(crate) fn delete_topics(app: &mut App<'_>, config: Config, to_delete: Vec<String>) {
+    app.spawn("Deleting topics".to_string(), async move {
+        match kafka::topic::delete_topics(&config, to_delete).await {
+            Ok(res) => {
+                let errors = res.iter().flat_map(|r| {
+                    if r.is_err() {
+                        Some(r.clone().unwrap_err())
                     } else {
-                        app.open(PopupType::ERROR, format!("Failed to delete topics:\n{}", errors.join("\n ")));
+                        None
                     }
+                }).map(|(topic, error)| format!("{}, {}", topic, error))
+                    .collect::<Vec<_>>();
+
+                if errors.is_empty() {
+                    Outcome::Popup(PopupType::SUCCESS, "Topics deleted successfully".to_string())
+                } else {
+                    Outcome::Popup(PopupType::ERROR, format!("Failed to delete topics:\n{}", errors.join("\n ")))
                 }
-                Err(err) => {
-                    app.open(PopupType::ERROR, err.to_string());
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
+        }
+    });
+}
+
+// Asks to empty the topics, showing how many records it deletes.
+fn plan_truncate(app: &mut App<'_>, topics: Vec<String>) {
+    let config = app.config.clone();
+    app.spawn_blocking("Planning truncation".to_string(), move || {
+        match kafka::topic::plan_delete_records(&config, &topics, &[], TruncateTarget::HighWatermark) {
+            Ok(plan) => {
+                let records = plan.iter().map(DeleteRecordsEntry::records).sum::<i64>();
+                if records == 0 {
+                    Outcome::Popup(PopupType::SUCCESS, format!("No records to delete in {}", topics.join(", ")))
+                } else {
+                    let message = format!("Truncating deletes {records} records.");
+                    Outcome::Confirm(Box::new(Confirmation::new(message, ConfirmAction::Truncate(config, plan)).affecting(topics).typed()))
                 }
-            };
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
         }
-        _ => {}
-    }
+    });
+}
+
+pub(crate) fn truncate(app: &mut App<'_>, config: Config, plan: Vec<DeleteRecordsEntry>) {
+    app.spawn_blocking("Deleting records".to_string(), move || {
+        match kafka::topic::delete_records(&config, &plan) {
+            Ok(results) => {
+                let errors = results.iter()
+                    .filter_map(|r| r.error.as_ref().map(|e| format!("{}-{}, {}", r.topic, r.partition, e)))
+                    .collect::<Vec<_>>();
+
+                if errors.is_empty() {
+                    let records = results.iter().map(DeleteRecordsEntry::records).sum::<i64>();
+                    Outcome::Popup(PopupType::SUCCESS, format!("Deleted {records} records"))
+                } else {
+                    Outcome::Popup(PopupType::ERROR, format!("Failed to delete records:\n{}", errors.join("\n ")))
+                }
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
+        }
+    });
+}
+
+// The selected topics, or the highlighted one when none is selected.
+fn selected_or_current(app: &App<'_>, state: &ListTopicsState) -> Vec<String> {
+    app.table.selected_or_current().iter()
+        .filter_map(|i| state.topics.get(*i))
+        .map(|t| t.name.to_string())
+        .collect()
+}
+
+pub(crate) fn elect_leaders(app: &mut App<'_>, config: Config, topics: Vec<String>, election_type: ElectionType) {
+    let topics = topics.into_iter()
+        .map(|topic| (topic, vec![]))
+        .collect::<BTreeMap<_, _>>();
+    app.spawn_blocking("Electing leaders".to_string(), move || {
+        match kafka::election::elect_leaders(&config, election_type, &topics) {
+            Ok(results) => {
+                let elected = results.iter().filter(|r| r.status == ElectionStatus::Elected).count();
+                let not_needed = results.iter().filter(|r| r.status == ElectionStatus::NotNeeded).count();
+                let errors = results.iter()
+                    .filter_map(|r| r.error.as_ref().map(|e| format!("{}-{}, {}", r.topic, r.partition, e)))
+                    .collect::<Vec<_>>();
+
+                if errors.is_empty() {
+                    Outcome::Popup(
+                        PopupType::SUCCESS,
+                        format!("Elected {elected} leaders, {not_needed} partitions already had their leader"),
+                    )
+                } else {
+                    Outcome::Popup(PopupType::ERROR, format!("Failed to elect leaders:\n{}", errors.join("\n ")))
+                }
+            }
+            Err(err) => {
+                Outcome::Popup(PopupType::ERROR, err.to_string())
+            }
+        }
+    });
 }
\ No newline at end of file
@@ -1 +1,11 @@
-pub mod list_topics;
\ No newline at end of file
+pub mod browse_topic;
+pub mod describe_group;
+pub mod describe_topic;
+pub mod health;
+pub mod list_acls;
+pub mod list_brokers;
+pub mod list_contexts;
+pub mod list_groups;
+pub mod list_quotas;
+pub mod list_topics;
+pub mod tail;
\ No newline at end of file
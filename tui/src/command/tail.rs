@@ -0,0 +1,397 @@
+use crate::app::{App, Command};
+use crate::command::browse_topic;
+use crate::command::list_topics::ListTopicsState;
+use crate::event::AppEvent;
+use crate::keys::Action;
+use crate::table::{constraint_len_calculator, Column, RowKey, SortKey, TableData, TableDefinition};
+use crate::theme::Theme;
+use common::kafka::client::Config;
+use common::kafka::message::Tail;
+use common::kafka::types::MessageEntry;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Alignment, Line, Modifier, Span, Stylize, Text};
+use ratatui::widgets::{Cell, Row};
+use std::cmp::max;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Records kept in memory, older ones are dropped as new ones arrive.
+pub(crate) const CAPACITY: usize = 1000;
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+/// How often records are handed to the view, so a busy topic does not flood the event loop.
+const BATCH_INTERVAL: Duration = Duration::from_millis(200);
+/// The rate is averaged over this window.
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+/// How long a transient error stays in the status once it stopped recurring.
+const WARNING_TIMEOUT: Duration = Duration::from_secs(10);
+const KEY_WIDTH: usize = 30;
+const VALUE_WIDTH: usize = 120;
+
+pub fn create_tail_table_definition() -> TableDefinition {
+    TableDefinition::new(
+        vec![
+            Column::new("Topic").sort_by(SortKey::Text("topic")),
+            Column::new("Partition").right().sort_by(SortKey::Number("partition")),
+            Column::new("Offset").right().sort_by(SortKey::Number("offset")),
+            Column::new("Timestamp").sort_by(SortKey::Number("timestamp")),
+            Column::new("Key").sort_by(SortKey::Text("key")),
+            Column::new("Value"),
+        ]
+    )
+}
+
+pub fn table_from<'a>(data: Vec<MessageEntry>) -> TableData<'a> {
+    let mut longest_topic = 0;
+    let mut longest_offset = 0;
+    let mut longest_key = 0;
+
+    TableData::new(
+        data.iter().map(|r| {
+            let key = r.key.as_deref().map(|k| browse_topic::truncate(k, KEY_WIDTH)).unwrap_or("-".to_string());
+            longest_topic = max(longest_topic, constraint_len_calculator(r.topic.as_str()));
+            longest_offset = max(longest_offset, constraint_len_calculator(r.offset.to_string().as_str()));
+            longest_key = max(longest_key, constraint_len_calculator(key.as_str()));
+            let row = Row::new(
+                vec![
+                    Cell::from(r.topic.clone()),
+                    Cell::from(Text::from(r.partition.to_string()).alignment(Alignment::Right)),
+                    Cell::from(Text::from(r.offset.to_string()).alignment(Alignment::Right)),
+                    Cell::from(browse_topic::format_timestamp(r.timestamp)),
+                    Cell::from(key),
+                    Cell::from(r.value.as_deref().map(|v| browse_topic::truncate(v, VALUE_WIDTH)).unwrap_or("-".to_string())),
+                ]
+            );
+            // tombstones
+            if r.value.is_none() {
+                row.add_modifier(Modifier::DIM)
+            } else {
+                row
+            }
+        }).collect(),
+        vec![
+            // + 1 is for padding.
+            Constraint::Length(max(longest_topic, 5) + 1),
+            Constraint::Length(10),
+            Constraint::Length(max(longest_offset, 6) + 1),
+            Constraint::Length(25),
+            Constraint::Length(max(longest_key, 3) + 1),
+            Constraint::Fill(1),
+        ]
+    )
+        .with_keys(data.iter().map(|r| {
+            let id = format!("{}-{}-{}", r.topic, r.partition, r.offset);
+            RowKey::new(id.clone(), id)
+        }).collect())
+        .with_records(&data)
+}
+
+/// Records read by the tail since the last batch.
+pub(crate) struct TailBatch {
+    /// Every record read, including the ones dropped to stay within [`CAPACITY`].
+    pub(crate) received: usize,
+    pub(crate) messages: VecDeque<MessageEntry>,
+    /// The last error polling recovers from, such as a broker going away.
+    pub(crate) warning: Option<String>,
+}
+
+impl TailBatch {
+    fn new() -> Self {
+        Self { received: 0, messages: VecDeque::new(), warning: None }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TailState {
+    topics: Vec<String>,
+    /// The tail feeding this view, batches of other tails are dropped.
+    session: Option<u64>,
+    messages: VecDeque<MessageEntry>,
+    /// Records received while paused, shown once resumed.
+    pending: VecDeque<MessageEntry>,
+    paused: bool,
+    stopped: bool,
+    detail: bool,
+    /// The last transient error and when it happened, until records arrive again.
+    warning: Option<(Instant, String)>,
+    received: u64,
+    started: Instant,
+    /// Records received per batch over the last [`RATE_WINDOW`].
+    recent: VecDeque<(Instant, usize)>,
+}
+
+impl TailState {
+    pub fn new(topics: Vec<String>) -> Self {
+        Self {
+            topics,
+            session: None,
+            messages: VecDeque::new(),
+            pending: VecDeque::new(),
+            paused: false,
+            stopped: false,
+            detail: false,
+            warning: None,
+            received: 0,
+            started: Instant::now(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn topics(&self) -> &[String] {
+        &self.topics
+    }
+
+    pub(crate) fn session(&self) -> Option<u64> {
+        self.session
+    }
+
+    pub(crate) fn set_session(&mut self, session: u64) {
+        self.session = Some(session);
+        self.started = Instant::now();
+    }
+
+    pub fn messages(&self) -> Vec<MessageEntry> {
+        self.messages.iter().cloned().collect()
+    }
+
+    pub fn message(&self, index: usize) -> Option<&MessageEntry> {
+        self.messages.get(index)
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn detail(&self) -> bool {
+        self.detail
+    }
+
+    pub(crate) fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Adds the records of a batch, to the pending ones while paused.
+    pub(crate) fn push(&mut self, batch: TailBatch) {
+        let now = Instant::now();
+        self.received += batch.received as u64;
+        self.recent.push_back((now, batch.received));
+        if let Some(warning) = batch.warning {
+            self.warning = Some((now, warning));
+        } else if batch.received > 0 {
+            self.warning = None;
+        }
+        while self.recent.front().is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+        let target = if self.paused { &mut self.pending } else { &mut self.messages };
+        append(target, batch.messages);
+    }
+
+    /// Records per second over the last few seconds.
+    pub fn rate(&self) -> f64 {
+        let window = self.started.elapsed().min(RATE_WINDOW).as_secs_f64();
+        let now = Instant::now();
+        let received = self.recent.iter()
+            .filter(|(at, _)| now.duration_since(*at) <= RATE_WINDOW)
+            .map(|(_, count)| count)
+            .sum::<usize>();
+        if window > 0.0 { received as f64 / window } else { 0.0 }
+    }
+
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            let pending = std::mem::take(&mut self.pending);
+            append(&mut self.messages, pending);
+        }
+    }
+}
+
+// Keeps the last `CAPACITY` records.
+fn append(buffer: &mut VecDeque<MessageEntry>, messages: impl IntoIterator<Item = MessageEntry>) {
+    buffer.extend(messages);
+    let excess = buffer.len().saturating_sub(CAPACITY);
+    buffer.drain(..excess);
+}
+
+/// What the tail is doing.
+pub(crate) fn summary(state: &TailState, theme: &Theme) -> Vec<Line<'static>> {
+    let status = if state.stopped {
+        "stopped".to_string()
+    } else if state.paused {
+        format!("paused, {} new", state.pending.len())
+    } else {
+        "following".to_string()
+    };
+    let mut line = vec![Span::raw(format!(
+        "Tailing {}, {status}, {:.1} msgs/s, {} received, {} of {CAPACITY} kept",
+        state.topics.join(", "),
+        state.rate(),
+        state.received,
+        state.messages.len(),
+    ))];
+    let warning = state.warning.as_ref().filter(|(at, _)| !state.stopped && at.elapsed() < WARNING_TIMEOUT);
+    if let Some((_, warning)) = warning {
+        line.push(Span::styled(format!(", retrying: {warning}"), theme.warning));
+    }
+    vec![Line::from(line)]
+}
+
+// Errors the consumer recovers from by itself, once the brokers are back.
+fn transient(err: &KafkaError) -> bool {
+    matches!(
+        err.rdkafka_error_code(),
+        Some(RDKafkaErrorCode::BrokerTransportFailure | RDKafkaErrorCode::AllBrokersDown)
+    )
+}
+
+/// Reads the topics until `stop` is set, sending the records to the app in batches.
+///
+/// Transient errors are sent along with the batches and polling goes on, other errors stop the tail.
+pub(crate) fn run(config: Config, topics: Vec<String>, session: u64, stop: Arc<AtomicBool>, sender: UnboundedSender<AppEvent>) {
+    let tail = match Tail::start(&config, &topics) {
+        Ok(tail) => tail,
+        Err(err) => {
+            let _ = sender.send(AppEvent::Tailed(session, Err(err.to_string())));
+            return;
+        }
+    };
+    let mut batch = TailBatch::new();
+    let mut last_batch = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        match tail.poll(POLL_TIMEOUT) {
+            Ok(Some(message)) => {
+                batch.received += 1;
+                batch.messages.push_back(message);
+                if batch.messages.len() > CAPACITY {
+                    batch.messages.pop_front();
+                }
+            }
+            Ok(None) => {}
+            Err(err) if transient(&err) => batch.warning = Some(err.to_string()),
+            Err(err) => {
+                let _ = sender.send(AppEvent::Tailed(session, Err(err.to_string())));
+                return;
+            }
+        }
+        if last_batch.elapsed() >= BATCH_INTERVAL {
+            if batch.received > 0 || batch.warning.is_some() {
+                let ready = std::mem::replace(&mut batch, TailBatch::new());
+                if sender.send(AppEvent::Tailed(session, Ok(ready))).is_err() {
+                    return;
+                }
+            }
+            last_batch = Instant::now();
+        }
+    }
+}
+
+pub(crate) fn handle_action(action: Action, app: &mut App<'_>, mut state: TailState) {
+    match action {
+        Action::Pause => {
+            state.toggle_pause();
+            let follow = !state.paused;
+            app.set_command(Command::Tail(state));
+            if follow {
+                app.update_tail();
+            }
+        }
+        Action::Details => {
+            state.detail = !state.detail;
+            app.set_command(Command::Tail(state));
+        }
+        Action::Back if state.detail => {
+            state.detail = false;
+            app.set_command(Command::Tail(state));
+        }
+        Action::ScrollDetailsUp | Action::ScrollDetailsDown if state.detail => app.detail_scroll.scroll(action),
+        Action::Back => app.load_view(Command::ListTopics(ListTopicsState::default())),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(offset: i64) -> MessageEntry {
+        MessageEntry {
+            topic: "orders".to_string(),
+            partition: 0,
+            offset,
+            timestamp: None,
+            key: None,
+            value: None,
+            headers: vec![],
+        }
+    }
+
+    fn batch(offsets: std::ops::Range<i64>) -> TailBatch {
+        let messages: VecDeque<MessageEntry> = offsets.map(entry).collect();
+        TailBatch { received: messages.len(), messages, warning: None }
+    }
+
+    fn offsets(messages: &VecDeque<MessageEntry>) -> Vec<i64> {
+        messages.iter().map(|m| m.offset).collect()
+    }
+
+    #[test]
+    fn push_keeps_the_last_records_up_to_capacity() {
+        let mut state = TailState::new(vec!["orders".to_string()]);
+
+        state.push(batch(0..800));
+        state.push(batch(800..1500));
+
+        assert_eq!(state.messages.len(), CAPACITY);
+        assert_eq!(state.messages.front().map(|m| m.offset), Some(500));
+        assert_eq!(state.messages.back().map(|m| m.offset), Some(1499));
+        assert_eq!(state.received, 1500);
+    }
+
+    #[test]
+    fn batches_received_while_paused_are_shown_on_resume() {
+        let mut state = TailState::new(vec!["orders".to_string()]);
+        state.push(batch(0..2));
+
+        state.toggle_pause();
+        state.push(batch(2..4));
+        state.push(batch(4..5));
+        assert_eq!(offsets(&state.messages), vec![0, 1]);
+        assert_eq!(offsets(&state.pending), vec![2, 3, 4]);
+
+        state.toggle_pause();
+        assert_eq!(offsets(&state.messages), vec![0, 1, 2, 3, 4]);
+        assert!(state.pending.is_empty());
+    }
+
+    #[test]
+    fn resume_keeps_the_last_records_up_to_capacity() {
+        let mut state = TailState::new(vec!["orders".to_string()]);
+        state.push(batch(0..600));
+
+        state.toggle_pause();
+        state.push(batch(600..1200));
+        state.toggle_pause();
+
+        assert_eq!(state.messages.len(), CAPACITY);
+        assert_eq!(state.messages.front().map(|m| m.offset), Some(200));
+    }
+
+    #[test]
+    fn rate_ignores_batches_older_than_the_window() {
+        let mut state = TailState::new(vec!["orders".to_string()]);
+        let now = Instant::now();
+        let Some(old) = now.checked_sub(RATE_WINDOW * 2) else { return };
+        state.started = old;
+        state.recent = VecDeque::from([(old, 1000), (now, 50)]);
+
+        assert_eq!(state.rate(), 50.0 / RATE_WINDOW.as_secs_f64());
+
+        state.push(batch(0..25));
+        assert_eq!(state.recent.len(), 2);
+        assert_eq!(state.rate(), 75.0 / RATE_WINDOW.as_secs_f64());
+    }
+}
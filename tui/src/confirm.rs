@@ -0,0 +1,205 @@
+use crate::app::{ConfirmAction, PopupWidget};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use crate::theme::Theme;
+use ratatui::prelude::{Alignment, Buffer, Line, Rect, Style, Stylize, Widget};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use tui_input::backend::crossterm::EventHandler as InputEventHandler;
+use tui_input::Input;
+
+/// Affected resources listed in the dialog, the others are counted.
+const AFFECTED_SHOWN: usize = 10;
+/// What to type to confirm an action on several resources.
+const CONFIRM_WORD: &str = "yes";
+
+/// An action waiting for the user's confirmation, with the resources it affects.
+///
+/// Destructive actions are confirmed by typing the name of the resource, or `yes` for several of them,
+/// other ones with `y`.
+#[derive(Clone)]
+pub(crate) struct Confirmation {
+    message: String,
+    affected: Vec<String>,
+    /// What to type to confirm, `None` when `y` does.
+    expected: Option<String>,
+    input: Input,
+    pub(crate) action: ConfirmAction,
+}
+
+/// What a key does to an open confirmation.
+pub(crate) enum Answer {
+    Confirmed,
+    Cancelled,
+    Pending,
+}
+
+impl Confirmation {
+    pub(crate) fn new(message: String, action: ConfirmAction) -> Self {
+        Self {
+            message,
+            affected: vec![],
+            expected: None,
+            input: Input::default(),
+            action,
+        }
+    }
+
+    /// Lists the resources the action affects.
+    pub(crate) fn affecting(mut self, affected: Vec<String>) -> Self {
+        self.affected = affected;
+        self
+    }
+
+    /// Asks to type the name of the affected resource, or `yes` when there are several, rather than `y`.
+    pub(crate) fn typed(mut self) -> Self {
+        self.expected = match self.affected.as_slice() {
+            [resource] => Some(resource.clone()),
+            _ => Some(CONFIRM_WORD.to_string()),
+        };
+        self
+    }
+
+    /// Asks to type `name`, as when the affected resources all belong to it.
+    pub(crate) fn expecting(mut self, name: String) -> Self {
+        self.expected = Some(name);
+        self
+    }
+
+    pub(crate) fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> Answer {
+        match (&self.expected, key_event.code) {
+            (_, KeyCode::Esc) => Answer::Cancelled,
+            (None, KeyCode::Char('y')) => Answer::Confirmed,
+            (None, KeyCode::Char('n')) => Answer::Cancelled,
+            (None, _) => Answer::Pending,
+            (Some(expected), KeyCode::Enter) if self.input.value() == expected => Answer::Confirmed,
+            (Some(_), _) => {
+                self.input.handle_event(&Event::Key(key_event));
+                Answer::Pending
+            }
+        }
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer, theme: &Theme) {
+        let mut lines = self.message.lines().map(|l| Line::from(l.to_string())).collect::<Vec<_>>();
+        if !self.affected.is_empty() {
+            lines.push(Line::from(""));
+            lines.extend(self.affected.iter().take(AFFECTED_SHOWN).map(|a| Line::from(format!("  {a}")).bold()));
+            if self.affected.len() > AFFECTED_SHOWN {
+                lines.push(Line::from(format!("  and {} more", self.affected.len() - AFFECTED_SHOWN)).style(theme.dimmed));
+            }
+        }
+        lines.push(Line::from(""));
+        match &self.expected {
+            Some(expected) => {
+                lines.push(Line::from(format!("Type {expected} and press Enter to confirm, Esc to cancel")));
+                let style = if self.input.value() == expected { theme.success } else { Style::default() };
+                lines.push(Line::from(format!("> {}", self.input.value())).style(style));
+            }
+            None => lines.push(Line::from("Press y to confirm, n to cancel")),
+        }
+        let block = Block::bordered()
+            .title("confirm")
+            .title_alignment(Alignment::Center)
+            .title_style(Style::default())
+            .style(theme.popup);
+        Paragraph::new(lines)
+            .style(theme.warning)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+/// A popup asking to confirm an action before it runs.
+pub(crate) trait ConfirmWidget: PopupWidget {
+    fn confirm(&mut self, confirmation: Confirmation);
+    fn confirmation(&self) -> Option<&Confirmation>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::kafka::client::create_config;
+    use common::kafka::iam::TokenOptions;
+    use crossterm::event::KeyModifiers;
+    use std::time::Duration;
+
+    // A typed deletion of `groups`, to be created within a runtime.
+    fn delete(groups: &[&str]) -> Confirmation {
+        let groups: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+        let config = create_config("127.0.0.1:1".to_string(), false, "eu-west-1".to_string(), TokenOptions::default(), Duration::from_secs(30));
+        Confirmation::new("Delete?".to_string(), ConfirmAction::DeleteGroups(config, groups.clone())).affecting(groups)
+    }
+
+    fn type_keys(confirmation: &mut Confirmation, text: &str) -> Answer {
+        let mut answer = Answer::Pending;
+        for c in text.chars() {
+            answer = confirmation.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        answer
+    }
+
+    fn press(confirmation: &mut Confirmation, code: KeyCode) -> Answer {
+        confirmation.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[tokio::test]
+    async fn typed_expects_the_name_of_a_single_resource() {
+        let mut confirmation = delete(&["orders"]).typed();
+
+        assert_eq!(confirmation.expected.as_deref(), Some("orders"));
+        assert!(matches!(type_keys(&mut confirmation, "orders"), Answer::Pending));
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Confirmed));
+    }
+
+    #[tokio::test]
+    async fn typed_expects_yes_for_several_resources() {
+        let mut confirmation = delete(&["orders", "payments"]).typed();
+
+        assert_eq!(confirmation.expected.as_deref(), Some(CONFIRM_WORD));
+        type_keys(&mut confirmation, "yes");
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Confirmed));
+    }
+
+    #[tokio::test]
+    async fn y_confirms_only_untyped_dialogs() {
+        assert!(matches!(type_keys(&mut delete(&["orders"]), "y"), Answer::Confirmed));
+        assert!(matches!(type_keys(&mut delete(&["orders"]), "n"), Answer::Cancelled));
+
+        let mut typed = delete(&["orders"]).typed();
+        assert!(matches!(type_keys(&mut typed, "y"), Answer::Pending));
+        assert_eq!(typed.input.value(), "y");
+    }
+
+    #[tokio::test]
+    async fn enter_with_a_wrong_value_stays_pending() {
+        let mut confirmation = delete(&["orders"]).typed();
+
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Pending));
+        type_keys(&mut confirmation, "order");
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Pending));
+    }
+
+    #[tokio::test]
+    async fn esc_cancels_typed_and_untyped_dialogs() {
+        assert!(matches!(press(&mut delete(&["orders"]), KeyCode::Esc), Answer::Cancelled));
+
+        let mut typed = delete(&["orders"]).typed();
+        type_keys(&mut typed, "orders");
+        assert!(matches!(press(&mut typed, KeyCode::Esc), Answer::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn expecting_overrides_the_expected_text() {
+        let mut confirmation = delete(&["orders", "payments"]).typed().expecting("billing".to_string());
+
+        type_keys(&mut confirmation, "yes");
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Pending));
+        let mut confirmation = delete(&["orders", "payments"]).typed().expecting("billing".to_string());
+        type_keys(&mut confirmation, "billing");
+        assert!(matches!(press(&mut confirmation, KeyCode::Enter), Answer::Confirmed));
+    }
+}
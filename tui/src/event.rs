@@ -0,0 +1,67 @@
+use crate::app::Outcome;
+use crate::command::tail::TailBatch;
+use crossterm::event;
+use crossterm::event::Event;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Everything the main loop reacts to, terminal input and background work alike.
+pub(crate) enum AppEvent {
+    Tick,
+    Input(Event),
+    InputError(std::io::Error),
+    /// A background task finished, identified by the id it was spawned with.
+    Finished(u64, Outcome),
+    /// Records read by a tail, identified by its session.
+    Tailed(u64, Result<TailBatch, String>),
+}
+
+/// Multiplexes terminal input, ticks and the results of background tasks onto one channel.
+pub(crate) struct EventHandler {
+    sender: UnboundedSender<AppEvent>,
+    receiver: UnboundedReceiver<AppEvent>,
+}
+
+impl EventHandler {
+    /// Starts reading the terminal on its own thread, sending a tick every `tick_rate`.
+    pub fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        let input = sender.clone();
+        std::thread::spawn(move || {
+            let mut last_tick = Instant::now();
+            loop {
+                let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+                let event = match event::poll(timeout) {
+                    Ok(true) => match event::read() {
+                        Ok(event) => Some(AppEvent::Input(event)),
+                        Err(err) => Some(AppEvent::InputError(err)),
+                    },
+                    Ok(false) => None,
+                    Err(err) => Some(AppEvent::InputError(err)),
+                };
+                let failed = matches!(event, Some(AppEvent::InputError(_)));
+                if let Some(event) = event {
+                    if input.send(event).is_err() || failed {
+                        break;
+                    }
+                }
+                if last_tick.elapsed() >= tick_rate {
+                    if input.send(AppEvent::Tick).is_err() {
+                        break;
+                    }
+                    last_tick = Instant::now();
+                }
+            }
+        });
+        Self { sender, receiver }
+    }
+
+    /// A sender for background tasks to report back on.
+    pub fn sender(&self) -> UnboundedSender<AppEvent> {
+        self.sender.clone()
+    }
+
+    pub async fn next(&mut self) -> Option<AppEvent> {
+        self.receiver.recv().await
+    }
+}
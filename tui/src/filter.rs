@@ -0,0 +1,227 @@
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// The fields of a row, by name, as used for filtering.
+pub(crate) type RowFields = Vec<(String, String)>;
+
+/// Narrows table rows to the ones matching every space separated term of the filter.
+///
+/// A term is either a predicate on a field, as in `partitions>12` or `state=stable`, or a regex matched
+/// against every field, as a plain substring when it is not a valid regex. Matching ignores case.
+#[derive(Debug, Clone)]
+pub(crate) struct Filter {
+    text: String,
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Pattern(Regex),
+    Compare(String, Operator, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+// Longest first, so `>=` is not read as `>`.
+const OPERATORS: [(&str, Operator); 6] = [
+    (">=", Operator::GreaterOrEqual),
+    ("<=", Operator::LessOrEqual),
+    ("!=", Operator::NotEqual),
+    ("=", Operator::Equal),
+    (">", Operator::Greater),
+    ("<", Operator::Less),
+];
+
+impl Filter {
+    /// `None` for a blank filter, which matches everything.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let terms = text.split_whitespace().map(Term::parse).collect::<Vec<_>>();
+        if terms.is_empty() {
+            return None;
+        }
+        Some(Self { text: text.trim().to_string(), terms })
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn matches(&self, fields: &RowFields) -> bool {
+        self.terms.iter().all(|term| term.matches(fields))
+    }
+}
+
+impl Term {
+    fn parse(term: &str) -> Self {
+        let predicate = term.find(['=', '!', '>', '<'])
+            .filter(|at| *at > 0 && term[..*at].chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+            .and_then(|at| {
+                OPERATORS.iter()
+                    .find(|(symbol, _)| term[at..].starts_with(symbol))
+                    .map(|(symbol, operator)| (at, symbol.len(), *operator))
+            });
+        match predicate {
+            Some((at, len, operator)) => {
+                Term::Compare(field_name(&term[..at]), operator, term[at + len..].to_lowercase())
+            }
+            None => {
+                let regex = RegexBuilder::new(term).case_insensitive(true).build()
+                    .unwrap_or_else(|_| RegexBuilder::new(&regex::escape(term)).case_insensitive(true).build().unwrap());
+                Term::Pattern(regex)
+            }
+        }
+    }
+
+    fn matches(&self, fields: &RowFields) -> bool {
+        match self {
+            Term::Pattern(regex) => fields.iter().any(|(_, value)| regex.is_match(value)),
+            Term::Compare(field, operator, expected) => fields.iter()
+                .filter(|(name, _)| field_name(name) == *field || name.rsplit('.').next().is_some_and(|n| field_name(n) == *field))
+                .any(|(_, value)| {
+                    let ordering = match (value.parse::<f64>(), expected.parse::<f64>()) {
+                        (Ok(value), Ok(expected)) => value.partial_cmp(&expected),
+                        _ => Some(value.to_lowercase().as_str().cmp(expected.as_str())),
+                    };
+                    match (operator, ordering) {
+                        (Operator::Equal, Some(ordering)) => ordering == Ordering::Equal,
+                        (Operator::NotEqual, Some(ordering)) => ordering != Ordering::Equal,
+                        (Operator::Greater, Some(ordering)) => ordering == Ordering::Greater,
+                        (Operator::GreaterOrEqual, Some(ordering)) => ordering != Ordering::Less,
+                        (Operator::Less, Some(ordering)) => ordering == Ordering::Less,
+                        (Operator::LessOrEqual, Some(ordering)) => ordering != Ordering::Greater,
+                        (_, None) => false,
+                    }
+                }),
+        }
+    }
+}
+
+// Field names are matched ignoring case, with `-` for `_`, as in `replication-factor`.
+fn field_name(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+/// The fields of an entry, nested ones named by their path as in `entity.name`, missing values empty.
+pub(crate) fn fields_of<T: Serialize>(entry: &T) -> RowFields {
+    let mut fields = vec![];
+    if let Ok(value) = serde_json::to_value(entry) {
+        flatten("", &value, &mut fields);
+    }
+    fields
+}
+
+fn flatten(path: &str, value: &Value, fields: &mut RowFields) {
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object {
+                let path = if path.is_empty() { name.clone() } else { format!("{path}.{name}") };
+                flatten(&path, value, fields);
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| flatten(path, value, fields)),
+        Value::String(value) => fields.push((path.to_string(), value.clone())),
+        Value::Null => fields.push((path.to_string(), String::new())),
+        value => fields.push((path.to_string(), value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn row(fields: &[(&str, &str)]) -> RowFields {
+        fields.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn compare(term: &str) -> (String, Operator, String) {
+        match Term::parse(term) {
+            Term::Compare(field, operator, expected) => (field, operator, expected),
+            Term::Pattern(regex) => panic!("'{term}' parsed as the pattern {regex}"),
+        }
+    }
+
+    #[test]
+    fn parses_the_longest_operator() {
+        assert_eq!(compare("partitions>=12"), ("partitions".to_string(), Operator::GreaterOrEqual, "12".to_string()));
+        assert_eq!(compare("partitions<=12").1, Operator::LessOrEqual);
+        assert_eq!(compare("state!=Stable"), ("state".to_string(), Operator::NotEqual, "stable".to_string()));
+        assert_eq!(compare("state=stable").1, Operator::Equal);
+        assert_eq!(compare("lag>0").1, Operator::Greater);
+        assert_eq!(compare("lag<0").1, Operator::Less);
+    }
+
+    #[test]
+    fn compares_numbers_as_numbers() {
+        let fields = row(&[("partitions", "12")]);
+        assert!(Term::parse("partitions>9").matches(&fields));
+        assert!(Term::parse("partitions>=12").matches(&fields));
+        assert!(!Term::parse("partitions<12").matches(&fields));
+        assert!(Term::parse("partitions!=9").matches(&fields));
+    }
+
+    #[test]
+    fn reads_dashes_in_field_names_as_underscores() {
+        let fields = row(&[("replication_factor", "3")]);
+        assert_eq!(compare("Replication-Factor=3").0, "replication_factor");
+        assert!(Term::parse("replication-factor=3").matches(&fields));
+        assert!(Term::parse("replication_factor=3").matches(&fields));
+        assert!(!Term::parse("replication-factor=2").matches(&fields));
+    }
+
+    #[test]
+    fn matches_nested_fields_by_their_last_name() {
+        let fields = row(&[("entity.name", "orders")]);
+        assert!(Term::parse("entity.name=orders").matches(&fields));
+        assert!(Term::parse("name=orders").matches(&fields));
+    }
+
+    #[test]
+    fn reads_terms_without_a_field_as_patterns() {
+        assert!(matches!(Term::parse("=stable"), Term::Pattern(_)));
+        assert!(matches!(Term::parse("a/b=c"), Term::Pattern(_)));
+        let fields = row(&[("name", "Orders-v2")]);
+        assert!(Term::parse("^orders").matches(&fields));
+        assert!(!Term::parse("^v2").matches(&fields));
+    }
+
+    #[test]
+    fn falls_back_to_plain_text_for_invalid_regexes() {
+        assert!(Term::parse("orders(").matches(&row(&[("name", "orders(v2)")])));
+        assert!(!Term::parse("orders(").matches(&row(&[("name", "orders")])));
+    }
+
+    #[test]
+    fn matches_every_term() {
+        let filter = Filter::parse("  orders  partitions>2 ").unwrap();
+        assert_eq!(filter.text(), "orders  partitions>2");
+        assert!(filter.matches(&row(&[("name", "orders"), ("partitions", "3")])));
+        assert!(!filter.matches(&row(&[("name", "orders"), ("partitions", "1")])));
+        assert!(Filter::parse("   ").is_none());
+    }
+
+    #[test]
+    fn flattens_nested_values_by_path() {
+        let mut fields = vec![];
+        flatten("", &json!({"name": "orders", "entity": {"kind": "user", "rack": null}, "replicas": [1, 2], "internal": false}), &mut fields);
+        fields.sort();
+        assert_eq!(fields, row(&[
+            ("entity.kind", "user"),
+            ("entity.rack", ""),
+            ("internal", "false"),
+            ("name", "orders"),
+            ("replicas", "1"),
+            ("replicas", "2"),
+        ]));
+    }
+}
@@ -0,0 +1,436 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::str::FromStr;
+use strum_macros::{Display, EnumString};
+
+/// Where a binding applies, view bindings go before the table ones, which go before the global ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum Context {
+    Global,
+    Table,
+    Topics,
+    Groups,
+    Group,
+    Topic,
+    Browse,
+    Tail,
+    Contexts,
+}
+
+/// What a key does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub(crate) enum Action {
+    Quit,
+    Command,
+    Help,
+    Refresh,
+    Filter,
+    NextMatch,
+    PreviousMatch,
+    Sort,
+    ReverseSort,
+    Back,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Select,
+    Browse,
+    Tail,
+    Elect,
+    UncleanElect,
+    Truncate,
+    Delete,
+    Describe,
+    ResetEarliest,
+    ResetLatest,
+    Details,
+    ScrollDetailsUp,
+    ScrollDetailsDown,
+    PreviousPartition,
+    NextPartition,
+    PreviousPage,
+    NextPage,
+    FirstPage,
+    LastPage,
+    JumpToOffset,
+    JumpToTimestamp,
+    Pause,
+    Connect,
+    NextTab,
+    PreviousTab,
+    Open,
+}
+
+impl Action {
+    /// Whether the action changes the cluster, these are hidden on read-only clusters.
+    pub(crate) fn mutates(self) -> bool {
+        matches!(
+            self,
+            Action::Elect | Action::UncleanElect | Action::Truncate | Action::Delete | Action::ResetEarliest | Action::ResetLatest
+        )
+    }
+}
+
+/// The bindings of every context, the keys as written in the key bindings file.
+const DEFAULTS: [(Context, Action, &[&str], &str); 47] = [
+    (Context::Global, Action::Quit, &["q"], "quit"),
+    (Context::Global, Action::Command, &[":"], "command line"),
+    (Context::Global, Action::Help, &["?"], "help"),
+    (Context::Global, Action::Refresh, &["r"], "reload the view"),
+    (Context::Global, Action::Filter, &["/"], "filter rows"),
+    (Context::Global, Action::NextMatch, &["n"], "next match"),
+    (Context::Global, Action::PreviousMatch, &["N"], "previous match"),
+    (Context::Global, Action::Sort, &["s"], "sort by the next column"),
+    (Context::Global, Action::ReverseSort, &["S"], "reverse the sort"),
+    (Context::Global, Action::Back, &["esc"], "clear the filter, or go back"),
+    (Context::Table, Action::Up, &["up", "k"], "previous row"),
+    (Context::Table, Action::Down, &["down", "j"], "next row"),
+    (Context::Table, Action::PageUp, &["pgup"], "previous page of rows"),
+    (Context::Table, Action::PageDown, &["pgdn"], "next page of rows"),
+    (Context::Table, Action::First, &["g", "home"], "first row"),
+    (Context::Table, Action::Last, &["G", "end"], "last row"),
+    (Context::Table, Action::Select, &["space"], "select the row"),
+    (Context::Topics, Action::Describe, &["enter"], "topic details"),
+    (Context::Topics, Action::Browse, &["b"], "browse records"),
+    (Context::Topics, Action::Tail, &["f"], "tail"),
+    (Context::Topics, Action::Elect, &["e"], "elect preferred leaders"),
+    (Context::Topics, Action::UncleanElect, &["U"], "unclean leader election"),
+    (Context::Topics, Action::Truncate, &["t"], "truncate"),
+    (Context::Topics, Action::Delete, &["d"], "delete"),
+    (Context::Groups, Action::Describe, &["enter"], "partitions and lag"),
+    (Context::Groups, Action::Delete, &["d"], "delete"),
+    (Context::Group, Action::ResetEarliest, &["E"], "reset offsets to earliest"),
+    (Context::Group, Action::ResetLatest, &["L"], "reset offsets to latest"),
+    (Context::Topic, Action::NextTab, &["tab", "right", "l"], "next tab"),
+    (Context::Topic, Action::PreviousTab, &["backtab", "left", "h"], "previous tab"),
+    (Context::Topic, Action::Open, &["enter"], "records of the partition, the group or the record"),
+    (Context::Browse, Action::Details, &["enter"], "record details"),
+    (Context::Browse, Action::ScrollDetailsUp, &["K", "ctrl-up"], "scroll the record details up"),
+    (Context::Browse, Action::ScrollDetailsDown, &["J", "ctrl-down"], "scroll the record details down"),
+    (Context::Browse, Action::PreviousPartition, &["left", "h"], "previous partition"),
+    (Context::Browse, Action::NextPartition, &["right", "l"], "next partition"),
+    (Context::Browse, Action::PreviousPage, &["pgup"], "previous page"),
+    (Context::Browse, Action::NextPage, &["pgdn"], "next page"),
+    (Context::Browse, Action::FirstPage, &["g", "home"], "first page"),
+    (Context::Browse, Action::LastPage, &["G", "end"], "last page"),
+    (Context::Browse, Action::JumpToOffset, &["o"], "jump to offset"),
+    (Context::Browse, Action::JumpToTimestamp, &["t"], "jump to timestamp"),
+    (Context::Tail, Action::Details, &["enter"], "record details"),
+    (Context::Tail, Action::ScrollDetailsUp, &["K", "ctrl-up"], "scroll the record details up"),
+    (Context::Tail, Action::ScrollDetailsDown, &["J", "ctrl-down"], "scroll the record details down"),
+    (Context::Tail, Action::Pause, &["p"], "pause or resume"),
+    (Context::Contexts, Action::Connect, &["enter"], "connect to the cluster"),
+];
+
+/// A key with its modifiers, as in `ctrl-d`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 15] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("pgup", KeyCode::PageUp),
+    ("pgdn", KeyCode::PageDown),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+];
+
+impl Key {
+    fn of(event: &KeyEvent) -> Self {
+        // the case of a character already tells shift, as backtab does.
+        let modifiers = match event.code {
+            KeyCode::Char(_) | KeyCode::BackTab => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        Self { code: event.code, modifiers }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = key;
+        loop {
+            if let Some(rest) = name.strip_prefix("ctrl-").filter(|r| !r.is_empty()) {
+                modifiers |= KeyModifiers::CONTROL;
+                name = rest;
+            } else if let Some(rest) = name.strip_prefix("alt-").filter(|r| !r.is_empty()) {
+                modifiers |= KeyModifiers::ALT;
+                name = rest;
+            } else {
+                break;
+            }
+        }
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => NAMED_KEYS.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, code)| *code)
+                    .ok_or_else(|| format!("Unknown key '{key}'"))?,
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            code => match NAMED_KEYS.iter().find(|(_, c)| *c == code) {
+                Some((name, _)) => write!(f, "{name}"),
+                None => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+/// A key binding, with what it does for the help.
+#[derive(Debug, Clone)]
+pub(crate) struct Binding {
+    pub(crate) context: Context,
+    pub(crate) action: Action,
+    pub(crate) keys: Vec<Key>,
+    pub(crate) description: &'static str,
+}
+
+/// The key bindings of the app, the defaults remapped by the key bindings file.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyBindings {
+    bindings: Vec<Binding>,
+    /// Hides the bindings of actions changing the cluster.
+    read_only: bool,
+}
+
+// Keys of an action in the key bindings file, one or a list of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = DEFAULTS.iter()
+            .map(|(context, action, keys, description)| Binding {
+                context: *context,
+                action: *action,
+                keys: keys.iter().map(|k| k.parse().expect("default keys are valid")).collect(),
+                description,
+            })
+            .collect();
+        Self { bindings, read_only: false }
+    }
+}
+
+impl KeyBindings {
+    /// The defaults remapped by `~/.kafka-utils/keys.yaml` when it exists, as in:
+    ///
+    /// ```yaml
+    /// table:
+    ///   first: [g, ctrl-a]
+    /// topics:
+    ///   delete: D
+    /// ```
+    pub(crate) fn load() -> Result<Self, String> {
+        let mut bindings = Self::default();
+        let Some(path) = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".kafka-utils").join("keys.yaml")) else {
+            return Ok(bindings);
+        };
+        let Ok(file) = std::fs::read_to_string(&path) else {
+            return Ok(bindings);
+        };
+        let remaps = serde_yaml::from_str::<BTreeMap<String, BTreeMap<String, Keys>>>(&file)
+            .map_err(|err| format!("Invalid key bindings in {}: {err}", path.display()))?;
+        bindings.remap(remaps).map_err(|err| format!("Invalid key bindings in {}: {err}", path.display()))?;
+        Ok(bindings)
+    }
+
+    fn remap(&mut self, remaps: BTreeMap<String, BTreeMap<String, Keys>>) -> Result<(), String> {
+        for (context, actions) in remaps {
+            let context = Context::from_str(&context).map_err(|_| format!("unknown context '{context}'"))?;
+            for (action, keys) in actions {
+                let binding = Action::from_str(&action).ok()
+                    .and_then(|action| self.bindings.iter_mut().find(|b| b.context == context && b.action == action))
+                    .ok_or_else(|| format!("unknown action '{action}' in {context}"))?;
+                let keys = match keys {
+                    Keys::One(key) => vec![key],
+                    Keys::Many(keys) => keys,
+                };
+                binding.keys = keys.iter().map(|k| k.parse()).collect::<Result<_, _>>()?;
+            }
+        }
+        self.check_duplicates()
+    }
+
+    // A key does one thing in a context, checked once every remap is done so keys can be swapped.
+    fn check_duplicates(&self) -> Result<(), String> {
+        for (i, binding) in self.bindings.iter().enumerate() {
+            for other in self.bindings[i + 1..].iter().filter(|b| b.context == binding.context) {
+                if let Some(key) = binding.keys.iter().find(|k| other.keys.contains(k)) {
+                    return Err(format!("'{key}' is bound to both {} and {} in {}", binding.action, other.action, binding.context));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// The action of the key in the view, the table or anywhere, in that order.
+    pub(crate) fn action(&self, view: Option<Context>, event: &KeyEvent) -> Option<Action> {
+        let key = Key::of(event);
+        let contexts = view.into_iter().chain([Context::Table, Context::Global]);
+        contexts.into_iter()
+            .flat_map(|context| self.of(context))
+            .find(|binding| binding.keys.contains(&key))
+            .map(|binding| binding.action)
+    }
+
+    /// The bindings of a context, in the order they are listed in the help, but the hidden ones.
+    pub(crate) fn of(&self, context: Context) -> impl Iterator<Item = &Binding> {
+        let read_only = self.read_only;
+        self.bindings.iter().filter(move |b| b.context == context && !(read_only && b.action.mutates()))
+    }
+
+    /// The first key of an action, as shown in hints.
+    pub(crate) fn key(&self, context: Context, action: Action) -> String {
+        self.bindings.iter()
+            .find(|b| b.context == context && b.action == action)
+            .and_then(|b| b.keys.first())
+            .map(|k| k.to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl Binding {
+    pub(crate) fn keys(&self) -> String {
+        self.keys.iter().map(|k| k.to_string()).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remap(yaml: &str) -> Result<KeyBindings, String> {
+        let mut bindings = KeyBindings::default();
+        bindings.remap(serde_yaml::from_str(yaml).unwrap())?;
+        Ok(bindings)
+    }
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_and_displays_keys() {
+        for key in ["q", "G", "enter", "space", "backtab", "pgdn", "f5", "ctrl-d", "ctrl-alt-x", "ctrl-up", "-"] {
+            assert_eq!(Key::from_str(key).unwrap().to_string(), key);
+        }
+        assert_eq!(Key::from_str("alt-ctrl-x").unwrap().to_string(), "ctrl-alt-x");
+        assert_eq!(Key::from_str("ctrl-space").unwrap(), Key { code: KeyCode::Char(' '), modifiers: KeyModifiers::CONTROL });
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for key in ["", "foo", "f13", "ctrl-", "ctrl-foo"] {
+            assert!(Key::from_str(key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn default_keys_do_one_thing_per_context() {
+        assert_eq!(KeyBindings::default().check_duplicates(), Ok(()));
+    }
+
+    #[test]
+    fn finds_the_view_binding_before_the_table_and_global_ones() {
+        let bindings = KeyBindings::default();
+        let g = press(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(bindings.action(Some(Context::Browse), &g), Some(Action::FirstPage));
+        assert_eq!(bindings.action(Some(Context::Topics), &g), Some(Action::First));
+        assert_eq!(bindings.action(None, &press(KeyCode::Char('q'), KeyModifiers::NONE)), Some(Action::Quit));
+        assert_eq!(bindings.action(None, &press(KeyCode::Char('x'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn ignores_shift_on_characters() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action(Some(Context::Browse), &press(KeyCode::Char('G'), KeyModifiers::SHIFT)), Some(Action::LastPage));
+        assert_eq!(bindings.action(Some(Context::Topic), &press(KeyCode::BackTab, KeyModifiers::SHIFT)), Some(Action::PreviousTab));
+    }
+
+    #[test]
+    fn hides_mutating_actions_on_read_only_clusters() {
+        let mut bindings = KeyBindings::default();
+        let d = press(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(bindings.action(Some(Context::Topics), &d), Some(Action::Delete));
+        bindings.set_read_only(true);
+        assert_eq!(bindings.action(Some(Context::Topics), &d), None);
+        assert!(bindings.of(Context::Topics).all(|b| !b.action.mutates()));
+    }
+
+    #[test]
+    fn remaps_actions_to_one_or_many_keys() {
+        let bindings = remap("table:\n  first: [g, ctrl-a]\ntopics:\n  delete: D\n").unwrap();
+        assert_eq!(bindings.key(Context::Topics, Action::Delete), "D");
+        let first = bindings.of(Context::Table).find(|b| b.action == Action::First).unwrap();
+        assert_eq!(first.keys(), "g, ctrl-a");
+        assert_eq!(bindings.action(Some(Context::Topics), &press(KeyCode::Char('d'), KeyModifiers::NONE)), None);
+    }
+
+    #[test]
+    fn swaps_keys_between_actions() {
+        let bindings = remap("topics:\n  delete: t\n  truncate: d\n").unwrap();
+        assert_eq!(bindings.key(Context::Topics, Action::Delete), "t");
+        assert_eq!(bindings.key(Context::Topics, Action::Truncate), "d");
+    }
+
+    #[test]
+    fn rejects_invalid_remaps() {
+        assert_eq!(remap("nowhere:\n  quit: x\n").unwrap_err(), "unknown context 'nowhere'");
+        assert_eq!(remap("global:\n  fly: x\n").unwrap_err(), "unknown action 'fly' in global");
+        assert_eq!(remap("global:\n  quit: foo\n").unwrap_err(), "Unknown key 'foo'");
+        assert_eq!(
+            remap("topics:\n  delete: e\n").unwrap_err(),
+            "'e' is bound to both elect and delete in topics",
+        );
+    }
+}
@@ -3,23 +3,51 @@ mod app;
 mod table;
 mod command;
 mod cli;
+mod confirm;
+mod event;
+mod filter;
+mod keys;
+mod palette;
+mod settings;
+mod theme;
 
-use crate::cli::{get_config, Cli};
+use crate::cli::{get_cluster, Cli};
+use crate::event::EventHandler;
+use crate::keys::KeyBindings;
+use crate::settings::Settings;
+use crate::theme::Theme;
 use app::App;
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+fn main() -> Result<()> {
+    init_logging();
 
     let cli = Cli::parse();
-    let config = get_config(cli);
+    let refresh_interval = cli.refresh.map(Duration::from_secs);
+    let mut settings = Settings::load().map_err(|err| eyre!(err))?;
+    if cli.read_only {
+        settings.contexts.values_mut().for_each(|profile| profile.read_only = true);
+    }
+    let theme = Theme::select(cli.theme, settings.theme);
+    let cluster = get_cluster(cli, &settings).map_err(|err| eyre!(err))?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let config = runtime.block_on(async { cluster.profile.config() });
+    let keys = KeyBindings::load().map_err(|err| eyre!(err))?;
 
     color_eyre::install().expect("color_eyre::install");
     let mut terminal = tui::init()?;
-    let app_result = App::new(config).run(&mut terminal)
-        .await;
+    let events = EventHandler::new(TICK_RATE);
+    let mut app = App::new(config, cluster, events.sender()).refresh_interval(refresh_interval).key_bindings(keys).theme(theme)
+        .contexts(settings.contexts);
+    let app_result = runtime.block_on(app.run(&mut terminal, events));
+    // tasks still waiting on Kafka must not hold up the exit.
+    runtime.shutdown_background();
     if let Err(err) = tui::restore() {
         eprintln!(
             "failed to restore terminal. Run `reset` or restart your terminal to recover: {}",
@@ -28,3 +56,19 @@ async fn main() -> Result<()> {
     }
     app_result
 }
+
+/// Logs to `~/.kafka-utils/tui.log`, anything written to the terminal would garble the screen.
+///
+/// Logging is off when the file cannot be opened.
+fn init_logging() {
+    let file = std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".kafka-utils"))
+        .and_then(|dir| fs::create_dir_all(&dir).ok().map(|_| dir.join("tui.log")))
+        .and_then(|path| OpenOptions::new().create(true).append(true).open(path).ok());
+    let mut builder = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    match file {
+        Some(file) => builder.target(env_logger::Target::Pipe(Box::new(file))),
+        None => builder.parse_filters("off"),
+    };
+    builder.init();
+}
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Commands kept in the history, older ones are dropped.
+const HISTORY_SIZE: usize = 500;
+/// Suggestions shown under the command line.
+pub(crate) const SUGGESTIONS: usize = 8;
+
+/// A command of the `:` command line, with its aliases and arguments.
+#[derive(Debug)]
+pub(crate) struct CommandSpec {
+    pub(crate) name: &'static str,
+    pub(crate) aliases: &'static [&'static str],
+    pub(crate) usage: &'static str,
+    argument: Argument,
+    options: &'static [&'static str],
+}
+
+// What the positional argument of a command names, for completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Argument {
+    None,
+    Topic,
+    /// Comma separated topics.
+    Topics,
+    Group,
+    /// A context, the command goes without it.
+    Context,
+}
+
+pub(crate) const COMMANDS: [CommandSpec; 11] = [
+    CommandSpec { name: "list-topics", aliases: &["topics"], usage: "[prefix=<prefix>]", argument: Argument::None, options: &["prefix"] },
+    CommandSpec { name: "list-brokers", aliases: &["brokers"], usage: "", argument: Argument::None, options: &[] },
+    CommandSpec { name: "describe-topic", aliases: &["topic"], usage: "<topic>", argument: Argument::Topic, options: &[] },
+    CommandSpec { name: "list-groups", aliases: &["groups"], usage: "[prefix=<prefix>]", argument: Argument::None, options: &["prefix"] },
+    CommandSpec { name: "describe-group", aliases: &["group"], usage: "<group>", argument: Argument::Group, options: &[] },
+    CommandSpec {
+        name: "browse-topic",
+        aliases: &["consume", "browse"],
+        usage: "<topic> [--partition <partition>] [--from earliest|latest|<offset>|<time>]",
+        argument: Argument::Topic,
+        options: &["partition", "from"],
+    },
+    CommandSpec { name: "tail", aliases: &[], usage: "<topic>[,<topic>...]", argument: Argument::Topics, options: &[] },
+    CommandSpec { name: "health", aliases: &[], usage: "", argument: Argument::None, options: &[] },
+    CommandSpec { name: "list-acls", aliases: &["acls"], usage: "", argument: Argument::None, options: &[] },
+    CommandSpec { name: "list-quotas", aliases: &["quotas"], usage: "", argument: Argument::None, options: &[] },
+    CommandSpec { name: "context", aliases: &["contexts", "ctx"], usage: "[<context>]", argument: Argument::Context, options: &[] },
+];
+
+/// Values of the `--from` option with a name.
+const FROM_VALUES: [&str; 2] = ["earliest", "latest"];
+
+impl CommandSpec {
+    fn find(name: &str) -> Option<&'static CommandSpec> {
+        COMMANDS.iter().find(|spec| spec.name == name || spec.aliases.contains(&name))
+    }
+}
+
+/// A parsed command line, as in `browse-topic orders --from earliest` or `list-topics prefix=orders`.
+#[derive(Debug)]
+pub(crate) struct CommandLine {
+    pub(crate) spec: &'static CommandSpec,
+    pub(crate) arguments: Vec<String>,
+    pub(crate) options: BTreeMap<String, String>,
+}
+
+impl CommandLine {
+    /// Options are given as `name=value`, `--name value` or `--name=value`.
+    pub(crate) fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("Type a command, Tab completes it")?;
+        let spec = CommandSpec::find(name).ok_or_else(|| format!("Unknown command '{name}'"))?;
+        let mut arguments = vec![];
+        let mut options = BTreeMap::new();
+        while let Some(word) = words.next() {
+            let (option, value) = match word.strip_prefix("--") {
+                Some(option) => match option.split_once('=') {
+                    Some((option, value)) => (option, value.to_string()),
+                    None => {
+                        let value = words.next().ok_or_else(|| format!("Missing value for --{option}"))?;
+                        (option, value.to_string())
+                    }
+                },
+                None => match word.split_once('=') {
+                    Some((option, value)) => (option, value.to_string()),
+                    None => {
+                        arguments.push(word.to_string());
+                        continue;
+                    }
+                },
+            };
+            if !spec.options.contains(&option) {
+                return Err(format!("Unknown option '{option}' for {}, usage: {} {}", spec.name, spec.name, spec.usage));
+            }
+            options.insert(option.to_string(), value);
+        }
+        let expected = match spec.argument {
+            Argument::None => 0..=0,
+            Argument::Context => 0..=1,
+            _ => 1..=1,
+        };
+        if !expected.contains(&arguments.len()) {
+            return Err(format!("Usage: {} {}", spec.name, spec.usage).trim_end().to_string());
+        }
+        Ok(Self { spec, arguments, options })
+    }
+
+    pub(crate) fn argument(&self) -> &str {
+        self.arguments.first().map(String::as_str).unwrap_or_default()
+    }
+
+    pub(crate) fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+}
+
+/// Topic and group names offered by completion, as last listed, with the contexts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Names {
+    pub(crate) topics: Vec<String>,
+    pub(crate) groups: Vec<String>,
+    pub(crate) contexts: Vec<String>,
+}
+
+/// Candidates for the word being typed at the end of the command line.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Completion {
+    /// Where the completed part of the line starts.
+    start: usize,
+    pub(crate) candidates: Vec<String>,
+    /// The candidate in the line, as cycled through with Tab.
+    pub(crate) selected: Option<usize>,
+}
+
+impl Completion {
+    pub(crate) fn start(&self) -> usize {
+        self.start
+    }
+
+    pub(crate) fn of(line: &str, names: &Names) -> Self {
+        let start = line.rfind(' ').map(|at| at + 1).unwrap_or(0);
+        let word = &line[start..];
+        let previous = line[..start].split_whitespace().collect::<Vec<_>>();
+        let Some((name, previous)) = previous.split_first() else {
+            let candidates = COMMANDS.iter()
+                .flat_map(|spec| std::iter::once(spec.name).chain(spec.aliases.iter().copied()))
+                .filter(|name| name.starts_with(word))
+                .map(|name| name.to_string())
+                .collect();
+            return Self { start, candidates, selected: None };
+        };
+        let Some(spec) = CommandSpec::find(name) else {
+            return Self::default();
+        };
+        if let Some(option) = word.strip_prefix("--") {
+            let candidates = spec.options.iter()
+                .filter(|o| o.starts_with(option))
+                .map(|o| format!("--{o}"))
+                .collect();
+            return Self { start, candidates, selected: None };
+        }
+        // the argument is given once, options and their values aside.
+        let given = previous.iter().enumerate()
+            .any(|(i, w)| !w.starts_with("--") && !w.contains('=') && (i == 0 || !previous[i - 1].starts_with("--") || previous[i - 1].contains('=')));
+        let (start, word, candidates) = match (previous.last().copied(), spec.argument) {
+            (Some("--from"), _) => (start, word, FROM_VALUES.iter().map(|v| v.to_string()).collect()),
+            (Some(option), _) if option.starts_with("--") && !option.contains('=') => (start, word, vec![]),
+            _ if given => (start, word, vec![]),
+            // topics after the last comma.
+            (_, Argument::Topics) => {
+                let at = word.rfind(',').map(|at| at + 1).unwrap_or(0);
+                (start + at, &word[at..], names.topics.clone())
+            }
+            (_, Argument::Topic) => (start, word, names.topics.clone()),
+            (_, Argument::Group) => (start, word, names.groups.clone()),
+            (_, Argument::Context) => (start, word, names.contexts.clone()),
+            (_, Argument::None) => (start, word, vec![]),
+        };
+        let candidates = candidates.into_iter().filter(|c| c.starts_with(word)).collect();
+        Self { start, candidates, selected: None }
+    }
+
+    /// Puts the next candidate, or the previous one, in place of the completed part of the line.
+    pub(crate) fn cycle(&mut self, line: &str, forward: bool) -> Option<String> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+        let count = self.candidates.len();
+        let selected = match (self.selected, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        };
+        self.selected = Some(selected);
+        let mut completed = format!("{}{}", &line[..self.start], self.candidates[selected]);
+        // a single candidate is final, the next word follows.
+        if count == 1 {
+            completed.push(' ');
+        }
+        Some(completed)
+    }
+}
+
+/// Commands run before, kept in a file across sessions.
+///
+/// Commands are appended to the file, so sessions running side by side keep each other's commands.
+/// The file is rewritten with the last [`HISTORY_SIZE`] commands once it holds twice as many.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+    /// The lines in the file, as read or written by this session.
+    saved: usize,
+    /// The entry shown while going through the history, `None` for the line being typed.
+    position: Option<usize>,
+}
+
+impl History {
+    /// Loads the history of `~/.kafka-utils/history`, empty when it cannot be read.
+    pub(crate) fn load() -> Self {
+        Self::from_file(std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".kafka-utils").join("history")))
+    }
+
+    fn from_file(path: Option<PathBuf>) -> Self {
+        let mut entries = path.as_deref().map(read_lines).unwrap_or_default();
+        let saved = entries.len();
+        entries.drain(..saved.saturating_sub(HISTORY_SIZE));
+        Self { path, entries, saved, position: None }
+    }
+
+    /// Adds the command, saving the history. Failing to save it does not stop the command.
+    pub(crate) fn push(&mut self, line: &str) {
+        self.position = None;
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        let excess = self.entries.len().saturating_sub(HISTORY_SIZE);
+        self.entries.drain(..excess);
+        let _ = self.save(line);
+    }
+
+    fn save(&mut self, line: &str) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        if self.saved + 1 < 2 * HISTORY_SIZE {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{line}")?;
+            self.saved += 1;
+            return Ok(());
+        }
+        // read again, other sessions may have appended to it.
+        let mut lines = read_lines(path);
+        lines.push(line.to_string());
+        lines.drain(..lines.len().saturating_sub(HISTORY_SIZE));
+        fs::write(path, lines.join("\n") + "\n")?;
+        self.saved = lines.len();
+        Ok(())
+    }
+
+    /// The entry before the one shown, the last one first.
+    pub(crate) fn previous(&mut self) -> Option<&str> {
+        let position = match self.position {
+            None => self.entries.len().checked_sub(1)?,
+            Some(position) => position.saturating_sub(1),
+        };
+        self.position = Some(position);
+        self.entries.get(position).map(String::as_str)
+    }
+
+    /// The entry after the one shown, `None` past the last one, back to an empty line.
+    pub(crate) fn next(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position >= self.entries.len() {
+            self.position = None;
+            return None;
+        }
+        self.position = Some(position);
+        self.entries.get(position).map(String::as_str)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.position = None;
+    }
+}
+
+// The commands of a history file, none when it cannot be read.
+fn read_lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|history| history.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names() -> Names {
+        Names {
+            topics: vec!["orders".to_string(), "payments".to_string(), "pay-v2".to_string()],
+            groups: vec!["billing".to_string()],
+            contexts: vec![],
+        }
+    }
+
+    // A history file of its own in the temp dir, removed first.
+    fn history_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("kafka-utils-{}-{name}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn parses_options_in_every_form() {
+        let line = CommandLine::parse("browse orders --partition 2 --from=earliest").unwrap();
+        assert_eq!(line.spec.name, "browse-topic");
+        assert_eq!(line.argument(), "orders");
+        assert_eq!(line.option("partition"), Some("2"));
+        assert_eq!(line.option("from"), Some("earliest"));
+
+        let line = CommandLine::parse("list-topics prefix=orders").unwrap();
+        assert_eq!(line.option("prefix"), Some("orders"));
+        assert_eq!(line.arguments, Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejects_unknown_options_and_missing_values() {
+        assert_eq!(CommandLine::parse("browse-topic orders --from").unwrap_err(), "Missing value for --from");
+        assert!(CommandLine::parse("list-topics limit=2").unwrap_err().starts_with("Unknown option 'limit'"));
+        assert_eq!(CommandLine::parse("nope").unwrap_err(), "Unknown command 'nope'");
+        assert!(CommandLine::parse("  ").is_err());
+    }
+
+    #[test]
+    fn checks_the_argument_count() {
+        assert_eq!(CommandLine::parse("describe-topic").unwrap_err(), "Usage: describe-topic <topic>");
+        assert!(CommandLine::parse("describe-topic orders payments").is_err());
+        assert_eq!(CommandLine::parse("brokers orders").unwrap_err(), "Usage: list-brokers");
+        assert!(CommandLine::parse("context").is_ok());
+        assert_eq!(CommandLine::parse("ctx prod").unwrap().argument(), "prod");
+        assert!(CommandLine::parse("context prod staging").is_err());
+    }
+
+    #[test]
+    fn completes_commands_and_their_aliases() {
+        assert_eq!(Completion::of("top", &names()).candidates, vec!["topics", "topic"]);
+        assert_eq!(Completion::of("browse-topic orders --", &names()).candidates, vec!["--partition", "--from"]);
+    }
+
+    #[test]
+    fn completes_the_topic_after_the_last_comma() {
+        let completion = Completion::of("tail orders,pay", &names());
+        assert_eq!(completion.start(), "tail orders,".len());
+        assert_eq!(completion.candidates, vec!["payments", "pay-v2"]);
+    }
+
+    #[test]
+    fn completes_option_values_then_nothing_once_the_argument_is_given() {
+        assert_eq!(Completion::of("browse-topic orders --from ", &names()).candidates, vec!["earliest", "latest"]);
+        assert_eq!(Completion::of("browse-topic orders --from l", &names()).candidates, vec!["latest"]);
+        assert!(Completion::of("browse-topic orders --partition ", &names()).candidates.is_empty());
+        assert!(Completion::of("browse-topic orders ", &names()).candidates.is_empty());
+        assert_eq!(Completion::of("browse-topic --from earliest o", &names()).candidates, vec!["orders"]);
+        assert_eq!(Completion::of("group b", &names()).candidates, vec!["billing"]);
+    }
+
+    #[test]
+    fn skips_repeated_commands_and_keeps_the_last_ones() {
+        let mut history = History::default();
+        for line in ["topics", " topics ", "groups", "", "topics"] {
+            history.push(line);
+        }
+        assert_eq!(history.entries, vec!["topics", "groups", "topics"]);
+
+        for i in 0..=HISTORY_SIZE {
+            history.push(&i.to_string());
+        }
+        assert_eq!(history.entries.len(), HISTORY_SIZE);
+        assert_eq!(history.entries[0], "1");
+    }
+
+    #[test]
+    fn goes_back_and_forth_through_the_history() {
+        let mut history = History::default();
+        assert_eq!(history.previous(), None);
+        for line in ["a", "b", "c"] {
+            history.push(line);
+        }
+        assert_eq!(history.previous(), Some("c"));
+        assert_eq!(history.previous(), Some("b"));
+        assert_eq!(history.previous(), Some("a"));
+        assert_eq!(history.previous(), Some("a"));
+        assert_eq!(history.next(), Some("b"));
+        assert_eq!(history.next(), Some("c"));
+        assert_eq!(history.next(), None);
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous(), Some("c"));
+        history.reset();
+        assert_eq!(history.previous(), Some("c"));
+    }
+
+    #[test]
+    fn appends_the_commands_of_every_session() {
+        let path = history_file("sessions");
+        let mut first = History::from_file(Some(path.clone()));
+        let mut second = History::from_file(Some(path.clone()));
+        first.push("topics");
+        second.push("groups");
+        first.push("brokers");
+
+        assert_eq!(History::from_file(Some(path.clone())).entries, vec!["topics", "groups", "brokers"]);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn compacts_the_file_once_it_holds_twice_the_history() {
+        let path = history_file("compaction");
+        let lines = (0..2 * HISTORY_SIZE - 1).map(|i| i.to_string()).collect::<Vec<_>>();
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        let mut history = History::from_file(Some(path.clone()));
+        assert_eq!(history.entries.len(), HISTORY_SIZE);
+
+        history.push("topics");
+
+        let saved = read_lines(&path);
+        assert_eq!(saved.len(), HISTORY_SIZE);
+        assert_eq!(saved.last().map(String::as_str), Some("topics"));
+        let _ = fs::remove_file(path);
+    }
+}
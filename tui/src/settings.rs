@@ -0,0 +1,112 @@
+use crate::theme::ThemeName;
+use common::kafka;
+use common::kafka::client::Config;
+use common::kafka::iam::{self, TokenOptions};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Settings of the app, flags given on the command line go before them.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Settings {
+    pub(crate) theme: Option<ThemeName>,
+    /// Clusters to connect to, by name.
+    pub(crate) contexts: BTreeMap<String, Profile>,
+}
+
+impl Settings {
+    /// The settings of `~/.kafka-utils/config.yaml` when it exists, as in:
+    ///
+    /// ```yaml
+    /// theme: light
+    /// contexts:
+    ///   local:
+    ///     bootstrap-servers: localhost:9092
+    ///   prod:
+    ///     bootstrap-servers: b-1.prod.kafka.eu-west-1.amazonaws.com:9098
+    ///     iam-auth: true
+    ///     production: true
+    ///     read-only: true
+    /// ```
+    pub(crate) fn load() -> Result<Self, String> {
+        let Some(path) = std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".kafka-utils").join("config.yaml")) else {
+            return Ok(Self::default());
+        };
+        let Ok(file) = std::fs::read_to_string(&path) else {
+            return Ok(Self::default());
+        };
+        serde_yaml::from_str(&file).map_err(|err| format!("Invalid settings in {}: {err}", path.display()))
+    }
+}
+
+/// How to connect to a cluster, with the defaults of the command line flags.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Profile {
+    pub(crate) bootstrap_servers: String,
+    #[serde(default)]
+    pub(crate) iam_auth: bool,
+    #[serde(default = "default_aws_region")]
+    pub(crate) aws_region: String,
+    /// In milliseconds.
+    #[serde(default = "default_timeout")]
+    pub(crate) timeout: u64,
+    /// In seconds, at most 900.
+    #[serde(default = "default_iam_token_lifetime", deserialize_with = "token_lifetime")]
+    pub(crate) iam_token_lifetime: u64,
+    #[serde(default)]
+    pub(crate) iam_fips: bool,
+    #[serde(default)]
+    pub(crate) iam_endpoint: Option<String>,
+    /// Marks the cluster with a red border, so it is not mistaken for another one.
+    #[serde(default)]
+    pub(crate) production: bool,
+    /// Refuses every operation changing the cluster.
+    #[serde(default)]
+    pub(crate) read_only: bool,
+}
+
+fn default_aws_region() -> String {
+    "eu-west-1".to_string()
+}
+
+fn default_timeout() -> u64 {
+    10000
+}
+
+fn default_iam_token_lifetime() -> u64 {
+    900
+}
+
+// A lifetime MSK accepts, as checked for `--iam-token-lifetime`.
+fn token_lifetime<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let lifetime = u64::deserialize(deserializer)?;
+    if !(1..=iam::MAX_EXPIRY_SECONDS).contains(&lifetime) {
+        return Err(D::Error::custom(format!("iam-token-lifetime {lifetime} is not in 1..={}", iam::MAX_EXPIRY_SECONDS)));
+    }
+    Ok(lifetime)
+}
+
+impl Profile {
+    /// The config of the Kafka clients, it must be created within the tokio runtime.
+    pub(crate) fn config(&self) -> Config {
+        kafka::client::create_config(
+            self.bootstrap_servers.clone(),
+            self.iam_auth,
+            self.aws_region.clone(),
+            TokenOptions::new(self.iam_token_lifetime, self.iam_fips, self.iam_endpoint.clone()),
+            Duration::from_millis(self.timeout),
+        )
+            .read_only(self.read_only)
+    }
+}
+
+/// The cluster the app is connected to, with the name it is known by.
+#[derive(Debug, Clone)]
+pub(crate) struct Cluster {
+    pub(crate) name: String,
+    pub(crate) profile: Profile,
+}
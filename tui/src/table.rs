@@ -1,73 +1,209 @@
-use ratatui::prelude::Constraint;
-use ratatui::style::palette::tailwind::CYAN;
-use ratatui::style::Color;
+use crate::filter::{fields_of, Filter, RowFields};
+use ratatui::prelude::{Alignment, Constraint, Text};
 use ratatui::widgets::{Cell, Row, TableState};
-use std::collections::HashSet;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone)]
-pub struct LocalTable<'a> {
-    pub(crate) colors: TableColors,
+pub struct LocalTable {
+    /// The cursor, over the visible rows.
     pub(crate) state: TableState,
-    pub(crate) definition: TableDefinition<'a>,
+    pub(crate) definition: TableDefinition,
+    /// Selected rows, as indexes in the data, so they stay selected whatever the filter shows.
     pub(crate) selected: HashSet<usize>,
+    /// Rows that changed, or appeared, with the last refresh.
+    pub(crate) changed: HashSet<usize>,
+    pub(crate) filter: Option<Filter>,
+    pub(crate) sort: Option<Sort>,
+    /// Rows shown, as indexes in the data, all of them unless filtered, in the order of the sort.
+    pub(crate) visible: Vec<usize>,
+    /// Rows that fit on screen, as last drawn.
+    pub(crate) page: u16,
 }
 
-impl<'a> LocalTable<'a> {
+/// The column rows are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sort {
+    pub(crate) column: usize,
+    pub(crate) descending: bool,
+}
+
+impl LocalTable {
     pub(crate) fn new() -> Self {
         Self {
-            colors: TableColors::new(),
             state: TableState::default(),
             definition: TableDefinition::empty(),
             selected: HashSet::new(),
+            changed: HashSet::new(),
+            filter: None,
+            sort: None,
+            visible: Vec::new(),
+            page: 1,
         }
     }
 
-    pub(crate) fn toggle_selected(&mut self) {
-        match self.state.selected() {
-            Some(selected) => {
-                if self.selected.contains(&selected) {
-                    self.selected.remove(&selected);
-                } else {
-                    self.selected.insert(selected);
-                }
-            }
-            None => {}
-        };
+    /// The highlighted row, as an index in the data.
+    pub(crate) fn current(&self) -> Option<usize> {
+        self.state.selected().and_then(|i| self.visible.get(i)).copied()
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct TableColors {
-    pub(crate) border: Color,
-    pub(crate) header_fg: Color,
-}
+    /// The selected rows, or the highlighted one when none is selected, as indexes in the data.
+    pub(crate) fn selected_or_current(&self) -> Vec<usize> {
+        let mut indexes = self.selected.iter().copied().collect::<Vec<_>>();
+        if indexes.is_empty() {
+            indexes.extend(self.current());
+        }
+        indexes.sort();
+        indexes
+    }
 
-impl TableColors {
-    const fn new() -> Self {
-        Self {
-            border: CYAN.c400,
-            header_fg: Color::White,
+    /// Clears the cursor, the selection, the changes, the filter and the sort, when switching to other data.
+    pub(crate) fn reset(&mut self, data: &TableData) {
+        self.state.select(None);
+        self.selected.clear();
+        self.changed.clear();
+        self.filter = None;
+        self.sort = None;
+        self.show_rows(data, None);
+    }
+
+    /// Follows the rows to their new position once the data was reloaded, so the cursor and the selection
+    /// stay on the same rows, and marks the rows that changed.
+    pub(crate) fn refresh(&mut self, old: &TableData, new: &TableData) {
+        let positions = new.keys.iter().enumerate()
+            .map(|(i, key)| (key.id.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        let position = |i: &usize| old.keys.get(*i).and_then(|key| positions.get(key.id.as_str())).copied();
+
+        self.selected = self.selected.iter().filter_map(position).collect();
+        let current = self.current().map(|current| {
+            position(&current).unwrap_or(current.min(new.keys.len().saturating_sub(1)))
+        });
+
+        let previous = old.keys.iter()
+            .map(|key| (key.id.as_str(), key.content.as_str()))
+            .collect::<HashMap<_, _>>();
+        self.changed = new.keys.iter().enumerate()
+            .filter(|(_, key)| previous.get(key.id.as_str()) != Some(&key.content.as_str()))
+            .map(|(i, _)| i)
+            .collect();
+        self.show_rows(new, current);
+    }
+
+    /// Shows the rows matching the filter, keeping the cursor on the current row while it is shown.
+    pub(crate) fn set_filter(&mut self, filter: Option<Filter>, data: &TableData) {
+        let current = self.current();
+        self.filter = filter;
+        self.show_rows(data, current);
+    }
+
+    /// Sorts by the next sortable column, the first one when unsorted, and unsorts after the last one.
+    pub(crate) fn next_sort(&mut self, data: &TableData) {
+        let next = self.sort.map(|sort| sort.column + 1).unwrap_or(0);
+        let column = self.definition.columns.iter().enumerate()
+            .skip(next)
+            .find(|(_, column)| column.sort_key.is_some())
+            .map(|(i, _)| i);
+        let current = self.current();
+        self.sort = column.map(|column| Sort { column, descending: false });
+        self.show_rows(data, current);
+    }
+
+    /// Reverses the order of the sort.
+    pub(crate) fn reverse_sort(&mut self, data: &TableData) {
+        if let Some(sort) = &mut self.sort {
+            sort.descending = !sort.descending;
+            let current = self.current();
+            self.show_rows(data, current);
+        }
+    }
+
+    // Narrows the rows to the filter and sorts them, with the cursor on `current` or the first row when it is
+    // filtered out.
+    fn show_rows(&mut self, data: &TableData, current: Option<usize>) {
+        self.visible = (0..data.rows.len())
+            .filter(|i| match &self.filter {
+                Some(filter) => data.fields.get(*i).is_some_and(|fields| filter.matches(fields)),
+                None => true,
+            })
+            .collect();
+        let sort_key = self.sort.and_then(|sort| self.definition.columns.get(sort.column)?.sort_key.map(|key| (sort, key)));
+        if let Some((sort, key)) = sort_key {
+            let no_fields = RowFields::new();
+            let fields = |i: &usize| data.fields.get(*i).unwrap_or(&no_fields);
+            self.visible.sort_by(|a, b| {
+                let ordering = key.compare(fields(a), fields(b));
+                if sort.descending { ordering.reverse() } else { ordering }
+            });
+        }
+        let position = current.and_then(|current| self.visible.iter().position(|i| *i == current));
+        let fallback = (current.is_some() && !self.visible.is_empty()).then_some(0);
+        self.state.select(position.or(fallback));
+    }
+
+    /// Moves the cursor to the next visible row, back to the first one after the last one.
+    pub(crate) fn next_match(&mut self) {
+        if !self.visible.is_empty() {
+            let next = self.state.selected().map(|i| (i + 1) % self.visible.len()).unwrap_or(0);
+            self.state.select(Some(next));
+        }
+    }
+
+    /// Moves the cursor to the previous visible row, to the last one before the first one.
+    pub(crate) fn previous_match(&mut self) {
+        if !self.visible.is_empty() {
+            let len = self.visible.len();
+            let previous = self.state.selected().map(|i| (i + len - 1) % len).unwrap_or(len - 1);
+            self.state.select(Some(previous));
+        }
+    }
+
+    /// Moves the cursor to the last visible row.
+    pub(crate) fn select_last(&mut self) {
+        self.state.select(self.visible.len().checked_sub(1));
+    }
+
+    /// Moves the cursor a screen of rows down, staying on the last row.
+    pub(crate) fn page_down(&mut self) {
+        let last = self.visible.len().saturating_sub(1);
+        let next = self.state.selected().map(|i| i + self.page as usize).unwrap_or(0);
+        self.state.select((!self.visible.is_empty()).then_some(next.min(last)));
+    }
+
+    /// Moves the cursor a screen of rows up, staying on the first row.
+    pub(crate) fn page_up(&mut self) {
+        self.state.scroll_up_by(self.page);
+    }
+
+    pub(crate) fn toggle_selected(&mut self) {
+        if let Some(selected) = self.current() {
+            if self.selected.contains(&selected) {
+                self.selected.remove(&selected);
+            } else {
+                self.selected.insert(selected);
+            }
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct TableDefinition<'a> {
-    pub(crate) header: Vec<Cell<'a>>,
+pub struct TableDefinition {
+    pub(crate) columns: Vec<Column>,
     pub(crate) selectable: bool,
 }
 
-impl<'a> TableDefinition<'a> {
-    pub(crate) fn new(header: Vec<Cell<'a>>) -> Self {
+impl TableDefinition {
+    pub(crate) fn new(columns: Vec<Column>) -> Self {
         Self {
-            header,
+            columns,
             selectable: false,
         }
     }
     fn empty() -> Self {
         Self {
-            header: vec![],
+            columns: vec![],
             selectable: false,
         }
     }
@@ -76,12 +212,82 @@ impl<'a> TableDefinition<'a> {
         self.selectable = selectable;
         self
     }
+
+    /// The header cells, the sorted column marked with its order.
+    pub(crate) fn header<'a>(&self, sort: Option<Sort>) -> Vec<Cell<'a>> {
+        self.columns.iter().enumerate()
+            .map(|(i, column)| {
+                let title = match sort {
+                    Some(sort) if sort.column == i => format!("{} {}", column.title, if sort.descending { "▼" } else { "▲" }),
+                    _ => column.title.to_string(),
+                };
+                Cell::from(Text::from(title).alignment(column.alignment))
+            })
+            .collect()
+    }
+}
+
+/// A column of a table, which rows can be sorted by when it has a sort key.
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub(crate) title: &'static str,
+    pub(crate) alignment: Alignment,
+    pub(crate) sort_key: Option<SortKey>,
+}
+
+impl Column {
+    pub(crate) fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            alignment: Alignment::Left,
+            sort_key: None,
+        }
+    }
+
+    /// Aligns the title to the right, as for numbers.
+    pub(crate) fn right(mut self) -> Self {
+        self.alignment = Alignment::Right;
+        self
+    }
+
+    pub(crate) fn sort_by(mut self, sort_key: SortKey) -> Self {
+        self.sort_key = Some(sort_key);
+        self
+    }
+}
+
+/// The field of the records a column sorts rows by, see [`TableData::with_records`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Sorted ignoring case.
+    Text(&'static str),
+    /// Sorted as numbers, rows without one first.
+    Number(&'static str),
+}
+
+impl SortKey {
+    fn compare(&self, a: &RowFields, b: &RowFields) -> Ordering {
+        let field = |fields: &RowFields, name: &str| fields.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+        match self {
+            SortKey::Text(name) => field(a, name).to_lowercase().cmp(&field(b, name).to_lowercase()),
+            SortKey::Number(name) => {
+                let number = |fields| field(fields, name).parse::<f64>().ok();
+                number(a).partial_cmp(&number(b)).unwrap_or(Ordering::Equal)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct TableData<'a> {
     pub rows: Vec<Row<'a>>,
     pub widths: Vec<Constraint>,
+    pub keys: Vec<RowKey>,
+    /// What filters match, one entry per row.
+    pub(crate) fields: Vec<RowFields>,
 }
 
 impl<'a> TableData<'a> {
@@ -89,13 +295,45 @@ impl<'a> TableData<'a> {
         Self {
             rows,
             widths,
+            keys: Vec::new(),
+            fields: Vec::new(),
         }
     }
 
+    /// Identifies the rows, one key per row, to keep track of them across refreshes.
+    pub(crate) fn with_keys(mut self, keys: Vec<RowKey>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// The entries shown by the rows, one per row, for filters to match their fields.
+    pub(crate) fn with_records<T: Serialize>(mut self, records: &[T]) -> Self {
+        self.fields = records.iter().map(fields_of).collect();
+        self
+    }
+
     pub(crate) fn empty() -> Self {
         Self {
             rows: Vec::new(),
             widths: Vec::new(),
+            keys: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// What identifies a row across refreshes, and what it showed to tell whether it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowKey {
+    pub(crate) id: String,
+    pub(crate) content: String,
+}
+
+impl RowKey {
+    pub(crate) fn new(id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            content: content.into(),
         }
     }
 }
@@ -103,3 +341,190 @@ impl<'a> TableData<'a> {
 pub(crate) fn constraint_len_calculator(item: &str) -> u16 {
     item.width() as u16
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // One row per id, with its content.
+    fn data(rows: &[(&str, &str)]) -> TableData<'static> {
+        TableData::new(rows.iter().map(|(id, _)| Row::new(vec![Cell::from(id.to_string())])).collect(), vec![])
+            .with_keys(rows.iter().map(|(id, content)| RowKey::new(*id, *content)).collect())
+    }
+
+    // One row per record, named by its `name` field, sortable by name, size and nothing.
+    fn records(records: &[serde_json::Value]) -> (LocalTable, TableData<'static>) {
+        let data = TableData::new(records.iter().map(|_| Row::default()).collect(), vec![]).with_records(records);
+        let mut table = LocalTable::new();
+        table.definition = TableDefinition::new(vec![
+            Column::new("Name").sort_by(SortKey::Text("name")),
+            Column::new("Notes"),
+            Column::new("Size").right().sort_by(SortKey::Number("size")),
+        ]);
+        table.reset(&data);
+        (table, data)
+    }
+
+    fn names(table: &LocalTable, data: &TableData) -> Vec<String> {
+        table.visible.iter()
+            .map(|i| data.fields[*i].iter().find(|(n, _)| n == "name").map(|(_, v)| v.clone()).unwrap_or_default())
+            .collect()
+    }
+
+    fn table_on(data: &TableData, cursor: usize) -> LocalTable {
+        let mut table = LocalTable::new();
+        table.reset(data);
+        table.state.select(Some(cursor));
+        table
+    }
+
+    #[test]
+    fn refresh_follows_moved_rows() {
+        let old = data(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let new = data(&[("c", "3"), ("a", "1"), ("b", "2")]);
+        let mut table = table_on(&old, 1);
+        table.selected.insert(0);
+
+        table.refresh(&old, &new);
+
+        assert_eq!(table.current(), Some(2));
+        assert_eq!(table.selected, HashSet::from([1]));
+    }
+
+    #[test]
+    fn refresh_drops_removed_rows_from_the_selection() {
+        let old = data(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let new = data(&[("b", "2"), ("c", "3")]);
+        let mut table = table_on(&old, 2);
+        table.selected.extend([0, 2]);
+
+        table.refresh(&old, &new);
+
+        assert_eq!(table.selected, HashSet::from([1]));
+        assert_eq!(table.current(), Some(1));
+    }
+
+    #[test]
+    fn refresh_moves_the_cursor_next_to_a_removed_row() {
+        let old = data(&[("a", "1"), ("b", "2"), ("c", "3")]);
+
+        let mut table = table_on(&old, 1);
+        table.refresh(&old, &data(&[("a", "1"), ("c", "3")]));
+        assert_eq!(table.current(), Some(1));
+
+        // the last row removed, the cursor goes to the new last one.
+        let mut table = table_on(&old, 2);
+        table.refresh(&old, &data(&[("a", "1"), ("b", "2")]));
+        assert_eq!(table.current(), Some(1));
+
+        let mut table = table_on(&old, 0);
+        table.refresh(&old, &data(&[]));
+        assert_eq!(table.current(), None);
+    }
+
+    #[test]
+    fn refresh_without_cursor_keeps_none() {
+        let old = data(&[("a", "1")]);
+        let mut table = LocalTable::new();
+        table.reset(&old);
+
+        table.refresh(&old, &data(&[("a", "1"), ("b", "2")]));
+
+        assert_eq!(table.current(), None);
+    }
+
+    #[test]
+    fn refresh_marks_changed_and_new_rows() {
+        let old = data(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let new = data(&[("a", "1"), ("b", "20"), ("d", "4")]);
+        let mut table = table_on(&old, 0);
+
+        table.refresh(&old, &new);
+
+        assert_eq!(table.changed, HashSet::from([1, 2]));
+        table.refresh(&new, &new);
+        assert!(table.changed.is_empty());
+    }
+
+    #[test]
+    fn sort_cycles_through_sortable_columns_then_unsorts() {
+        let (mut table, data) = records(&[json!({"name": "b", "size": 1}), json!({"name": "a", "size": 2})]);
+
+        table.next_sort(&data);
+        assert_eq!(table.sort, Some(Sort { column: 0, descending: false }));
+        table.next_sort(&data);
+        assert_eq!(table.sort, Some(Sort { column: 2, descending: false }));
+        table.next_sort(&data);
+        assert_eq!(table.sort, None);
+        assert_eq!(table.visible, vec![0, 1]);
+    }
+
+    #[test]
+    fn sort_orders_numbers_as_numbers_and_text_ignoring_case() {
+        let (mut table, data) = records(&[
+            json!({"name": "beta", "size": 10}),
+            json!({"name": "Alpha", "size": 9}),
+            json!({"name": "gamma", "size": 100}),
+        ]);
+
+        table.next_sort(&data);
+        assert_eq!(names(&table, &data), vec!["Alpha", "beta", "gamma"]);
+        table.next_sort(&data);
+        assert_eq!(names(&table, &data), vec!["Alpha", "beta", "gamma"]);
+        assert_eq!(table.visible, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn sort_puts_rows_without_a_number_first() {
+        let (mut table, data) = records(&[
+            json!({"name": "sized", "size": 1}),
+            json!({"name": "unknown", "size": null}),
+            json!({"name": "failed", "size": "timed out"}),
+        ]);
+        table.sort = Some(Sort { column: 2, descending: false });
+        table.set_filter(None, &data);
+
+        assert_eq!(names(&table, &data)[2], "sized");
+    }
+
+    #[test]
+    fn reverse_sort_orders_descending() {
+        let (mut table, data) = records(&[json!({"name": "a", "size": 1}), json!({"name": "c", "size": 3}), json!({"name": "b", "size": 2})]);
+
+        table.reverse_sort(&data);
+        assert_eq!(table.sort, None);
+        table.next_sort(&data);
+        table.reverse_sort(&data);
+        assert_eq!(table.sort, Some(Sort { column: 0, descending: true }));
+        assert_eq!(names(&table, &data), vec!["c", "b", "a"]);
+        table.reverse_sort(&data);
+        assert_eq!(names(&table, &data), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn sort_keeps_the_cursor_on_the_same_row() {
+        let (mut table, data) = records(&[json!({"name": "c", "size": 1}), json!({"name": "a", "size": 2}), json!({"name": "b", "size": 3})]);
+        table.state.select(Some(0));
+
+        table.next_sort(&data);
+        assert_eq!(table.current(), Some(0));
+        assert_eq!(table.state.selected(), Some(2));
+        table.reverse_sort(&data);
+        assert_eq!(table.current(), Some(0));
+        assert_eq!(table.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn header_marks_the_sorted_column_with_its_order() {
+        let (table, _) = records(&[]);
+        let header = |sort| table.definition.header(sort);
+
+        assert_eq!(header(None)[0], Cell::from(Text::from("Name").alignment(Alignment::Left)));
+        let ascending = header(Some(Sort { column: 2, descending: false }));
+        assert_eq!(ascending[0], Cell::from(Text::from("Name").alignment(Alignment::Left)));
+        assert_eq!(ascending[2], Cell::from(Text::from("Size ▲").alignment(Alignment::Right)));
+        let descending = header(Some(Sort { column: 0, descending: true }));
+        assert_eq!(descending[0], Cell::from(Text::from("Name ▼").alignment(Alignment::Left)));
+    }
+}
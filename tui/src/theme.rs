@@ -0,0 +1,144 @@
+use clap::ValueEnum;
+use ratatui::prelude::{Color, Modifier, Style};
+use ratatui::style::palette::tailwind::CYAN;
+use serde::Deserialize;
+
+/// The themes to pick from, with `--theme` or in the settings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ThemeName {
+    Dark,
+    Light,
+    HighContrast,
+    Monochrome,
+}
+
+/// The styles of the app, every color it draws with comes from one.
+#[derive(Debug, Clone)]
+pub(crate) struct Theme {
+    pub(crate) border: Style,
+    /// The border of production clusters.
+    pub(crate) production: Style,
+    pub(crate) header: Style,
+    /// The row under the cursor.
+    pub(crate) cursor: Style,
+    /// Rows selected with space.
+    pub(crate) selected: Style,
+    /// Rows that changed with the last refresh.
+    pub(crate) changed: Style,
+    /// Values set on purpose, as configs overriding their default.
+    pub(crate) highlight: Style,
+    /// Rows and hints of lesser interest, as internal topics.
+    pub(crate) dimmed: Style,
+    pub(crate) input: Style,
+    /// The background of popups, dialogs and the help.
+    pub(crate) popup: Style,
+    pub(crate) error: Style,
+    pub(crate) warning: Style,
+    pub(crate) success: Style,
+}
+
+impl Theme {
+    /// Monochrome when `NO_COLOR` is set, else the theme given with `--theme`, else the configured one.
+    ///
+    /// The terminal drops every color with `NO_COLOR` whatever the theme, only monochrome still tells
+    /// the cursor and the selection apart then.
+    pub(crate) fn select(flag: Option<ThemeName>, configured: Option<ThemeName>) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let name = match flag.or(configured) {
+            _ if no_color => ThemeName::Monochrome,
+            name => name.unwrap_or(ThemeName::Dark),
+        };
+        Self::of(name)
+    }
+
+    pub(crate) fn of(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+            ThemeName::HighContrast => Self::high_contrast(),
+            ThemeName::Monochrome => Self::monochrome(),
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            border: Style::new().fg(CYAN.c400),
+            production: Style::new().fg(Color::Red),
+            header: Style::new().fg(Color::White),
+            cursor: Style::new().add_modifier(Modifier::REVERSED),
+            selected: Style::new().add_modifier(Modifier::BOLD),
+            changed: Style::new().fg(Color::Yellow),
+            highlight: Style::new().fg(Color::LightCyan),
+            dimmed: Style::new().add_modifier(Modifier::DIM),
+            input: Style::new().fg(Color::Yellow),
+            popup: Style::new().bg(Color::Black),
+            error: Style::new().fg(Color::Red),
+            warning: Style::new().fg(Color::Yellow),
+            success: Style::new().fg(Color::Green),
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            border: Style::new().fg(Color::Blue),
+            production: Style::new().fg(Color::Red),
+            header: Style::new().fg(Color::Black).add_modifier(Modifier::BOLD),
+            cursor: Style::new().fg(Color::White).bg(Color::Blue),
+            selected: Style::new().add_modifier(Modifier::BOLD),
+            changed: Style::new().fg(Color::Magenta),
+            highlight: Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            dimmed: Style::new().fg(Color::Gray),
+            input: Style::new().fg(Color::Blue),
+            popup: Style::new().fg(Color::Black).bg(Color::White),
+            error: Style::new().fg(Color::Red),
+            // yellow does not read on white.
+            warning: Style::new().fg(Color::Indexed(130)),
+            success: Style::new().fg(Color::Green),
+        }
+    }
+
+    fn high_contrast() -> Self {
+        Self {
+            border: Style::new().fg(Color::White).add_modifier(Modifier::BOLD),
+            production: Style::new().fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            header: Style::new().fg(Color::Black).bg(Color::White).add_modifier(Modifier::BOLD),
+            cursor: Style::new().fg(Color::Black).bg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            selected: Style::new().fg(Color::LightCyan).add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            changed: Style::new().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            highlight: Style::new().fg(Color::LightCyan).add_modifier(Modifier::BOLD),
+            // dim text is hard to read, gray is enough.
+            dimmed: Style::new().fg(Color::Gray),
+            input: Style::new().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            popup: Style::new().fg(Color::White).bg(Color::Black),
+            error: Style::new().fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            warning: Style::new().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+            success: Style::new().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+        }
+    }
+
+    // No colors at all, only modifiers, as asked by `NO_COLOR`.
+    fn monochrome() -> Self {
+        Self {
+            border: Style::new(),
+            production: Style::new().add_modifier(Modifier::BOLD),
+            header: Style::new().add_modifier(Modifier::BOLD),
+            cursor: Style::new().add_modifier(Modifier::REVERSED),
+            selected: Style::new().add_modifier(Modifier::BOLD),
+            changed: Style::new().add_modifier(Modifier::UNDERLINED),
+            highlight: Style::new().add_modifier(Modifier::BOLD),
+            dimmed: Style::new().add_modifier(Modifier::DIM),
+            input: Style::new().add_modifier(Modifier::BOLD),
+            popup: Style::new(),
+            error: Style::new().add_modifier(Modifier::BOLD),
+            warning: Style::new().add_modifier(Modifier::BOLD),
+            success: Style::new(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::of(ThemeName::Dark)
+    }
+}